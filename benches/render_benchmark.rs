@@ -1,11 +1,14 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use interstellar_triangulum::renderer::RenderEngine;
-use interstellar_triangulum::script::{Layer, Metadata, Resolution, Scene, VideoScript};
+use interstellar_triangulum::script::{
+    Layer, Metadata, Resolution, Scene, VideoScript, SCRIPT_VERSION,
+};
 use interstellar_triangulum::AssetLoader;
 use std::path::PathBuf;
 
 fn create_test_script() -> VideoScript {
     VideoScript {
+        version: SCRIPT_VERSION,
         metadata: Metadata {
             title: "Benchmark".into(),
             resolution: Resolution::Named("1920x1080".into()),
@@ -13,6 +16,8 @@ fn create_test_script() -> VideoScript {
             duration: 1.0,
             description: None,
             citations: vec![],
+            encoder: None,
+            transparent: false,
         },
         scenes: vec![Scene {
             id: "bench".into(),
@@ -30,6 +35,8 @@ fn create_test_script() -> VideoScript {
                 },
                 position: Default::default(),
                 effects: vec![],
+                transform: Default::default(),
+                animation: Default::default(),
             }],
             transition: None,
         }],
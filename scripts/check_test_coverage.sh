@@ -8,10 +8,14 @@ echo "🔍 Checking test coverage compliance..."
 # Files to check (must have corresponding tests)
 FILES_WITH_FUNCTIONS=(
     "src/script.rs"
+    "src/animation.rs"
     "src/parser.rs"
     "src/assets.rs"
+    "src/video.rs"
     "src/renderer/frame_buffer.rs"
     "src/renderer/compositor.rs"
+    "src/renderer/effects.rs"
+    "src/renderer/text.rs"
     "src/renderer/timeline.rs"
     "src/renderer/engine.rs"
 )
@@ -212,10 +212,11 @@ impl CredibilityAnalyzer {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::script::{Layer, Metadata, Resolution, Scene, SceneType};
+    use crate::script::{Layer, Metadata, Resolution, Scene, SceneType, SCRIPT_VERSION};
 
     fn create_test_script(citations: Vec<String>, text: &str) -> VideoScript {
         VideoScript {
+            version: SCRIPT_VERSION,
             metadata: Metadata {
                 title: "Test".into(),
                 resolution: Resolution::Named("1920x1080".into()),
@@ -223,6 +224,8 @@ mod tests {
                 duration: 0.0,
                 description: None,
                 citations,
+                encoder: None,
+                transparent: false,
             },
             scenes: vec![Scene {
                 id: "test".into(),
@@ -240,6 +243,8 @@ mod tests {
                     },
                     position: crate::script::Position { x: 0, y: 0 },
                     effects: vec![],
+                    transform: Default::default(),
+                    animation: Default::default(),
                 }],
                 transition: None,
             }],
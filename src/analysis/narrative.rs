@@ -288,10 +288,11 @@ impl NarrativeAnalyzer {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::script::{Layer, Metadata, Resolution, Scene, SceneType};
+    use crate::script::{Layer, Metadata, Resolution, Scene, SceneType, SCRIPT_VERSION};
 
     fn create_test_script(scenes: Vec<Scene>) -> VideoScript {
         VideoScript {
+            version: SCRIPT_VERSION,
             metadata: Metadata {
                 title: "Test".into(),
                 resolution: Resolution::Named("1920x1080".into()),
@@ -299,6 +300,8 @@ mod tests {
                 duration: 0.0,
                 description: None,
                 citations: vec![],
+                encoder: None,
+                transparent: false,
             },
             scenes,
             audio: None,
@@ -322,6 +325,8 @@ mod tests {
                 },
                 position: crate::script::Position { x: 0, y: 0 },
                 effects: vec![],
+                transform: Default::default(),
+                animation: Default::default(),
             }],
             transition: None,
         }
@@ -190,7 +190,7 @@ impl Serialize for SceneRetention {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::script::{Layer, Metadata, Resolution, SceneType};
+    use crate::script::{Layer, Metadata, Resolution, SceneType, SCRIPT_VERSION};
 
     fn create_test_scene(duration: f32, layer_count: usize) -> Scene {
         let mut layers = Vec::new();
@@ -199,6 +199,7 @@ mod tests {
                 source: format!("image_{}.png", i).into(),
                 effects: vec![],
                 transform: Default::default(),
+                animation: Default::default(),
             });
         }
 
@@ -233,6 +234,7 @@ mod tests {
     #[test]
     fn test_predict_dropoff() {
         let script = VideoScript {
+            version: SCRIPT_VERSION,
             metadata: Metadata {
                 title: "Test".into(),
                 resolution: Resolution::Named("1920x1080".into()),
@@ -240,6 +242,8 @@ mod tests {
                 duration: 30.0,
                 description: None,
                 citations: vec![],
+                encoder: None,
+                transparent: false,
             },
             scenes: vec![
                 create_test_scene(5.0, 3),  // Good momentum
@@ -262,6 +266,7 @@ mod tests {
     #[test]
     fn test_generate_heatmap() {
         let script = VideoScript {
+            version: SCRIPT_VERSION,
             metadata: Metadata {
                 title: "Test".into(),
                 resolution: Resolution::Named("1920x1080".into()),
@@ -269,6 +274,8 @@ mod tests {
                 duration: 20.0,
                 description: None,
                 citations: vec![],
+                encoder: None,
+                transparent: false,
             },
             scenes: vec![
                 create_test_scene(5.0, 3),
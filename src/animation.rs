@@ -0,0 +1,431 @@
+use crate::script::{Color, Position, Transform};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Keyframe tracks for animatable layer properties.
+///
+/// Times are in seconds from the start of the scene. A property without
+/// keyframes keeps its static value from the layer definition.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
+pub struct Animation {
+    /// Replaces `transform.position`
+    #[serde(default)]
+    pub position: Vec<Keyframe<Position>>,
+    #[serde(default)]
+    pub scale: Vec<Keyframe<f32>>,
+    #[serde(default)]
+    pub rotation: Vec<Keyframe<f32>>,
+    #[serde(default)]
+    pub opacity: Vec<Keyframe<f32>>,
+    /// Text color (text layers only)
+    #[serde(default)]
+    pub color: Vec<Keyframe<Color>>,
+}
+
+/// A property value at a point in time
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Keyframe<T> {
+    /// Seconds from the start of the scene
+    pub time: f32,
+    pub value: T,
+    /// Easing used from this keyframe to the next one
+    #[serde(default)]
+    pub easing: Easing,
+}
+
+/// Easing curve applied between two keyframes
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Easing {
+    #[default]
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    /// CSS-style cubic Bézier with control points (x1, y1) and (x2, y2)
+    CubicBezier {
+        x1: f32,
+        y1: f32,
+        x2: f32,
+        y2: f32,
+    },
+    /// Damped spring (unit mass) released from the start value; may overshoot and
+    /// snaps to the next keyframe when the segment ends
+    Spring {
+        #[serde(default = "default_stiffness")]
+        stiffness: f32,
+        #[serde(default = "default_damping")]
+        damping: f32,
+    },
+}
+
+fn default_stiffness() -> f32 {
+    100.0
+}
+
+fn default_damping() -> f32 {
+    10.0
+}
+
+impl Easing {
+    /// Map linear progress `t` (0..1) through the curve.
+    ///
+    /// `duration` is the segment length in seconds, used by physically based curves.
+    pub fn apply(&self, t: f32, duration: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match *self {
+            Easing::Linear => t,
+            Easing::EaseIn => cubic_bezier(0.42, 0.0, 1.0, 1.0, t),
+            Easing::EaseOut => cubic_bezier(0.0, 0.0, 0.58, 1.0, t),
+            Easing::EaseInOut => cubic_bezier(0.42, 0.0, 0.58, 1.0, t),
+            Easing::CubicBezier { x1, y1, x2, y2 } => cubic_bezier(x1, y1, x2, y2, t),
+            Easing::Spring { stiffness, damping } => {
+                if t >= 1.0 {
+                    1.0
+                } else {
+                    spring(stiffness, damping, t * duration)
+                }
+            }
+        }
+    }
+}
+
+/// Evaluate a cubic Bézier timing curve from (0,0) to (1,1) at `x`
+fn cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32, x: f32) -> f32 {
+    let x1 = x1.clamp(0.0, 1.0);
+    let x2 = x2.clamp(0.0, 1.0);
+    let bezier = |p1: f32, p2: f32, s: f32| {
+        let inv = 1.0 - s;
+        3.0 * inv * inv * s * p1 + 3.0 * inv * s * s * p2 + s * s * s
+    };
+    let slope = |p1: f32, p2: f32, s: f32| {
+        let inv = 1.0 - s;
+        3.0 * inv * inv * p1 + 6.0 * inv * s * (p2 - p1) + 3.0 * s * s * (1.0 - p2)
+    };
+
+    // Newton-Raphson on x(s) = x, falling back to bisection if the slope vanishes
+    let mut s = x;
+    for _ in 0..8 {
+        let error = bezier(x1, x2, s) - x;
+        if error.abs() < 1e-6 {
+            return bezier(y1, y2, s);
+        }
+        let d = slope(x1, x2, s);
+        if d.abs() < 1e-6 {
+            break;
+        }
+        s = (s - error / d).clamp(0.0, 1.0);
+    }
+
+    let (mut lo, mut hi) = (0.0, 1.0);
+    s = x;
+    for _ in 0..32 {
+        let value = bezier(x1, x2, s);
+        if (value - x).abs() < 1e-6 {
+            break;
+        }
+        if value < x {
+            lo = s;
+        } else {
+            hi = s;
+        }
+        s = (lo + hi) / 2.0;
+    }
+
+    bezier(y1, y2, s)
+}
+
+/// Position of a unit-mass damped spring moving from 0 to 1 after `time` seconds
+fn spring(stiffness: f32, damping: f32, time: f32) -> f32 {
+    let omega = stiffness.max(f32::EPSILON).sqrt();
+    let zeta = damping.max(0.0) / (2.0 * omega);
+
+    if (zeta - 1.0).abs() < 1e-3 {
+        // Critically damped
+        1.0 - (-omega * time).exp() * (1.0 + omega * time)
+    } else if zeta < 1.0 {
+        // Under-damped: oscillates around the target
+        let omega_d = omega * (1.0 - zeta * zeta).sqrt();
+        let envelope = (-zeta * omega * time).exp();
+        1.0 - envelope
+            * ((omega_d * time).cos() + (zeta * omega / omega_d) * (omega_d * time).sin())
+    } else {
+        // Over-damped
+        let root = (zeta * zeta - 1.0).sqrt();
+        let r1 = -omega * (zeta - root);
+        let r2 = -omega * (zeta + root);
+        1.0 - (r2 * (r1 * time).exp() - r1 * (r2 * time).exp()) / (r2 - r1)
+    }
+}
+
+/// Values that can be blended between keyframes
+pub trait Interpolate: Clone {
+    /// Blend from `self` to `other`; `t` may leave 0..1 for overshooting curves
+    fn interpolate(&self, other: &Self, t: f32) -> Self;
+}
+
+impl Interpolate for f32 {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Interpolate for Position {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        Position {
+            x: (self.x as f32).interpolate(&(other.x as f32), t).round() as i32,
+            y: (self.y as f32).interpolate(&(other.y as f32), t).round() as i32,
+        }
+    }
+}
+
+impl Interpolate for Color {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        let channel = |a: u8, b: u8| {
+            (a as f32)
+                .interpolate(&(b as f32), t)
+                .round()
+                .clamp(0.0, 255.0) as u8
+        };
+        Color {
+            r: channel(self.r, other.r),
+            g: channel(self.g, other.g),
+            b: channel(self.b, other.b),
+            a: channel(self.a, other.a),
+        }
+    }
+}
+
+/// Sample a keyframe track at `time`, or `None` if the track is empty.
+///
+/// Values hold before the first and after the last keyframe. Keyframes must be
+/// sorted by time (enforced by `ScriptParser`).
+pub fn sample<T: Interpolate>(track: &[Keyframe<T>], time: f32) -> Option<T> {
+    let first = track.first()?;
+    if time <= first.time {
+        return Some(first.value.clone());
+    }
+
+    for pair in track.windows(2) {
+        let (from, to) = (&pair[0], &pair[1]);
+        if time < to.time {
+            let duration = to.time - from.time;
+            let progress = (time - from.time) / duration;
+            let eased = from.easing.apply(progress, duration);
+            return Some(from.value.interpolate(&to.value, eased));
+        }
+    }
+
+    track.last().map(|k| k.value.clone())
+}
+
+impl Animation {
+    /// Whether no property is animated
+    pub fn is_empty(&self) -> bool {
+        self.position.is_empty()
+            && self.scale.is_empty()
+            && self.rotation.is_empty()
+            && self.opacity.is_empty()
+            && self.color.is_empty()
+    }
+
+    /// Transform at `time` seconds into the scene
+    pub fn transform_at(&self, base: &Transform, time: f32) -> Transform {
+        Transform {
+            position: sample(&self.position, time).unwrap_or_else(|| base.position.clone()),
+            scale: sample(&self.scale, time).unwrap_or(base.scale),
+            rotation: sample(&self.rotation, time).unwrap_or(base.rotation),
+            opacity: sample(&self.opacity, time).unwrap_or(base.opacity),
+            anchor: base.anchor,
+        }
+    }
+
+    /// Text color at `time` seconds into the scene
+    pub fn color_at(&self, base: &Color, time: f32) -> Color {
+        sample(&self.color, time).unwrap_or_else(|| base.clone())
+    }
+
+    /// Times of every keyframe track, by property name (used for validation)
+    pub fn tracks(&self) -> [(&'static str, Vec<f32>); 5] {
+        fn times<T>(track: &[Keyframe<T>]) -> Vec<f32> {
+            track.iter().map(|k| k.time).collect()
+        }
+        [
+            ("position", times(&self.position)),
+            ("scale", times(&self.scale)),
+            ("rotation", times(&self.rotation)),
+            ("opacity", times(&self.opacity)),
+            ("color", times(&self.color)),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyframe<T>(time: f32, value: T, easing: Easing) -> Keyframe<T> {
+        Keyframe {
+            time,
+            value,
+            easing,
+        }
+    }
+
+    #[test]
+    fn test_linear_sampling() {
+        let track = vec![
+            keyframe(1.0, 0.0, Easing::Linear),
+            keyframe(3.0, 10.0, Easing::Linear),
+        ];
+
+        assert_eq!(sample(&track, 0.0), Some(0.0)); // Holds before first keyframe
+        assert_eq!(sample(&track, 2.0), Some(5.0));
+        assert_eq!(sample(&track, 5.0), Some(10.0)); // Holds after last keyframe
+        assert_eq!(sample::<f32>(&[], 1.0), None);
+    }
+
+    #[test]
+    fn test_easing_endpoints() {
+        let curves = [
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+            Easing::CubicBezier {
+                x1: 0.25,
+                y1: 0.1,
+                x2: 0.25,
+                y2: 1.0,
+            },
+            Easing::Spring {
+                stiffness: 100.0,
+                damping: 10.0,
+            },
+        ];
+
+        for easing in curves {
+            assert!(easing.apply(0.0, 1.0).abs() < 1e-4, "{:?}", easing);
+            assert!((easing.apply(1.0, 1.0) - 1.0).abs() < 1e-4, "{:?}", easing);
+        }
+    }
+
+    #[test]
+    fn test_ease_in_out_shape() {
+        let easing = Easing::EaseInOut;
+        assert!(easing.apply(0.25, 1.0) < 0.25); // Slow start
+        assert!((easing.apply(0.5, 1.0) - 0.5).abs() < 1e-3); // Symmetric
+        assert!(easing.apply(0.75, 1.0) > 0.75); // Slow end
+    }
+
+    #[test]
+    fn test_cubic_bezier_linear_equivalent() {
+        let easing = Easing::CubicBezier {
+            x1: 0.0,
+            y1: 0.0,
+            x2: 1.0,
+            y2: 1.0,
+        };
+        for t in [0.1, 0.3, 0.6, 0.9] {
+            assert!((easing.apply(t, 1.0) - t).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_spring_overshoots_when_underdamped() {
+        let easing = Easing::Spring {
+            stiffness: 200.0,
+            damping: 5.0,
+        };
+        let peak = (1..100)
+            .map(|i| easing.apply(i as f32 / 100.0, 1.0))
+            .fold(f32::MIN, f32::max);
+        assert!(peak > 1.0);
+
+        // Critically and over-damped springs approach the target without overshoot
+        for damping in [2.0 * 200f32.sqrt(), 60.0] {
+            let easing = Easing::Spring {
+                stiffness: 200.0,
+                damping,
+            };
+            assert!((1..100).all(|i| easing.apply(i as f32 / 100.0, 1.0) <= 1.0));
+        }
+    }
+
+    #[test]
+    fn test_transform_at() {
+        let animation = Animation {
+            position: vec![
+                keyframe(0.0, Position { x: -100, y: 50 }, Easing::Linear),
+                keyframe(1.0, Position { x: 100, y: 50 }, Easing::Linear),
+            ],
+            opacity: vec![
+                keyframe(0.0, 0.0, Easing::EaseIn),
+                keyframe(0.5, 1.0, Easing::Linear),
+            ],
+            ..Default::default()
+        };
+        let base = Transform {
+            scale: 2.0,
+            ..Default::default()
+        };
+
+        let transform = animation.transform_at(&base, 0.5);
+        assert_eq!(transform.position, Position { x: 0, y: 50 });
+        assert_eq!(transform.opacity, 1.0);
+        assert_eq!(transform.scale, 2.0); // Not animated
+
+        assert!(!animation.is_empty());
+        assert!(Animation::default().is_empty());
+    }
+
+    #[test]
+    fn test_color_interpolation_clamps() {
+        let from = Color {
+            r: 0,
+            g: 200,
+            b: 255,
+            a: 255,
+        };
+        let to = Color {
+            r: 255,
+            g: 100,
+            b: 255,
+            a: 255,
+        };
+
+        let mid = from.interpolate(&to, 0.5);
+        assert_eq!((mid.r, mid.g), (128, 150));
+
+        // Overshooting curves must not wrap channels
+        let over = from.interpolate(&to, 1.5);
+        assert_eq!(over.r, 255);
+        assert_eq!(over.g, 50);
+    }
+
+    #[test]
+    fn test_deserialize_animation() {
+        let json = r#"{
+            "scale": [
+                {"time": 0.0, "value": 0.5, "easing": {"spring": {"stiffness": 120.0}}},
+                {"time": 1.0, "value": 1.0}
+            ],
+            "rotation": [
+                {"time": 0.0, "value": 0.0, "easing": {"cubic_bezier": {"x1": 0.3, "y1": 0.0, "x2": 0.2, "y2": 1.0}}},
+                {"time": 1.0, "value": 90.0, "easing": "ease_in_out"}
+            ]
+        }"#;
+
+        let animation: Animation = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            animation.scale[0].easing,
+            Easing::Spring {
+                stiffness: 120.0,
+                damping: 10.0
+            }
+        );
+        assert_eq!(animation.scale[1].easing, Easing::Linear);
+        assert_eq!(animation.rotation[1].easing, Easing::EaseInOut);
+        assert!(animation.position.is_empty());
+    }
+}
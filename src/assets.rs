@@ -1,4 +1,5 @@
-use anyhow::{Context, Result};
+use crate::error::{AssetError, AssetKind};
+use crate::video::VideoDecoder;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
@@ -18,7 +19,7 @@ pub struct ImageAsset {
     pub height: u32,
 }
 
-/// Video asset (placeholder for now, will use FFmpeg later)
+/// Video asset (properties probed with ffprobe)
 #[derive(Debug, Clone)]
 pub struct VideoAsset {
     pub path: PathBuf,
@@ -26,6 +27,7 @@ pub struct VideoAsset {
     pub height: u32,
     pub fps: f32,
     pub duration: f32,
+    pub has_audio: bool,
 }
 
 /// Font asset (placeholder for now)
@@ -56,13 +58,16 @@ impl AssetLoader {
     }
 
     /// Load an image asset (stub for now)
-    pub fn load_image(&mut self, path: &Path) -> Result<&ImageAsset> {
+    pub fn load_image(&mut self, path: &Path) -> Result<&ImageAsset, AssetError> {
         let full_path = self.resolve_path(path);
 
         if !self.assets.contains_key(&full_path) {
             // Verify file exists
             if !full_path.exists() {
-                anyhow::bail!("Image file not found: {}", full_path.display());
+                return Err(AssetError::NotFound {
+                    kind: AssetKind::Image,
+                    path: full_path,
+                });
             }
 
             // TODO: Use actual image library to load pixels
@@ -78,28 +83,37 @@ impl AssetLoader {
 
         match self.assets.get(&full_path).unwrap() {
             Asset::Image(img) => Ok(img),
-            _ => anyhow::bail!("Asset is not an image"),
+            _ => Err(AssetError::WrongKind {
+                path: full_path,
+                expected: AssetKind::Image,
+            }),
         }
     }
 
-    /// Load a video asset (stub implementation)
-    pub fn load_video(&mut self, path: &Path) -> Result<&VideoAsset> {
+    /// Load a video asset
+    pub fn load_video(&mut self, path: &Path) -> Result<&VideoAsset, AssetError> {
         let full_path = self.resolve_path(path);
 
         if !self.assets.contains_key(&full_path) {
             // Verify file exists
             if !full_path.exists() {
-                anyhow::bail!("Video file not found: {}", full_path.display());
+                return Err(AssetError::NotFound {
+                    kind: AssetKind::Video,
+                    path: full_path,
+                });
             }
 
-            // TODO: Use FFmpeg to get actual video properties
-            // For now, create a stub
+            let info = VideoDecoder::probe(&full_path).map_err(|source| AssetError::Probe {
+                path: full_path.clone(),
+                source,
+            })?;
             let asset = Asset::Video(VideoAsset {
                 path: full_path.clone(),
-                width: 1920,
-                height: 1080,
-                fps: 30.0,
-                duration: 10.0,
+                width: info.width,
+                height: info.height,
+                fps: info.fps,
+                duration: info.duration,
+                has_audio: info.has_audio,
             });
 
             self.assets.insert(full_path.clone(), asset);
@@ -107,17 +121,32 @@ impl AssetLoader {
 
         match self.assets.get(&full_path).unwrap() {
             Asset::Video(vid) => Ok(vid),
-            _ => anyhow::bail!("Asset is not a video"),
+            _ => Err(AssetError::WrongKind {
+                path: full_path,
+                expected: AssetKind::Video,
+            }),
         }
     }
 
     /// Load a font asset
-    pub fn load_font(&mut self, path: &Path) -> Result<&FontAsset> {
+    pub fn load_font(&mut self, path: &Path) -> Result<&FontAsset, AssetError> {
         let full_path = self.resolve_path(path);
 
         if !self.assets.contains_key(&full_path) {
-            let data = std::fs::read(&full_path)
-                .with_context(|| format!("Failed to load font: {}", full_path.display()))?;
+            let data = std::fs::read(&full_path).map_err(|source| {
+                if source.kind() == std::io::ErrorKind::NotFound {
+                    AssetError::NotFound {
+                        kind: AssetKind::Font,
+                        path: full_path.clone(),
+                    }
+                } else {
+                    AssetError::Read {
+                        kind: AssetKind::Font,
+                        path: full_path.clone(),
+                        source,
+                    }
+                }
+            })?;
 
             let asset = Asset::Font(FontAsset {
                 path: full_path.clone(),
@@ -129,12 +158,15 @@ impl AssetLoader {
 
         match self.assets.get(&full_path).unwrap() {
             Asset::Font(font) => Ok(font),
-            _ => anyhow::bail!("Asset is not a font"),
+            _ => Err(AssetError::WrongKind {
+                path: full_path,
+                expected: AssetKind::Font,
+            }),
         }
     }
 
     /// Resolve a path relative to the base path
-    fn resolve_path(&self, path: &Path) -> PathBuf {
+    pub fn resolve_path(&self, path: &Path) -> PathBuf {
         if path.is_absolute() {
             path.to_path_buf()
         } else {
@@ -164,6 +196,13 @@ impl AssetLoader {
         }
     }
 
+    /// Take over the assets another loader has loaded (e.g. a render worker's)
+    pub fn merge(&mut self, other: AssetLoader) {
+        for (path, asset) in other.assets {
+            self.assets.entry(path).or_insert(asset);
+        }
+    }
+
     /// Clear all loaded assets from memory
     pub fn clear(&mut self) {
         self.assets.clear();
@@ -214,13 +253,35 @@ mod tests {
         assert_eq!(stats.total, 1);
     }
 
+    #[test]
+    fn test_merge() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("a.png"), b"fake image data").unwrap();
+        fs::write(temp_dir.path().join("b.png"), b"fake image data").unwrap();
+
+        let mut loader = AssetLoader::new(temp_dir.path());
+        loader.load_image(Path::new("a.png")).unwrap();
+        let mut worker = AssetLoader::new(temp_dir.path());
+        worker.load_image(Path::new("a.png")).unwrap();
+        worker.load_image(Path::new("b.png")).unwrap();
+
+        loader.merge(worker);
+        assert_eq!(loader.stats().images, 2);
+    }
+
     #[test]
     fn test_load_nonexistent_image() {
         let temp_dir = TempDir::new().unwrap();
         let mut loader = AssetLoader::new(temp_dir.path());
 
         let result = loader.load_image(Path::new("nonexistent.png"));
-        assert!(result.is_err());
+        assert!(matches!(
+            result,
+            Err(AssetError::NotFound {
+                kind: AssetKind::Image,
+                ..
+            })
+        ));
     }
 
     #[test]
@@ -260,19 +321,16 @@ mod tests {
     }
 
     #[test]
-    fn test_load_video() {
+    fn test_load_video_invalid() {
         let temp_dir = TempDir::new().unwrap();
         let vid_path = temp_dir.path().join("test.mp4");
         fs::write(&vid_path, b"fake video").unwrap();
 
+        // Metadata is probed from the file, so garbage data is rejected
         let mut loader = AssetLoader::new(temp_dir.path());
         let result = loader.load_video(Path::new("test.mp4"));
-        assert!(result.is_ok());
-
-        let video = result.unwrap();
-        assert_eq!(video.width, 1920);
-        assert_eq!(video.height, 1080);
-        assert_eq!(video.fps, 30.0);
+        assert!(result.is_err());
+        assert_eq!(loader.stats().videos, 0);
     }
 
     #[test]
@@ -1,4 +1,4 @@
-use anyhow::{Context, Result};
+use crate::error::AudioError;
 use hound;
 use std::fs::File;
 use std::path::Path;
@@ -15,8 +15,11 @@ pub struct AudioDecoder;
 impl AudioDecoder {
     /// Decode an audio file to a vector of samples (f32)
     /// Returns (samples, sample_rate, channels)
-    pub fn decode(path: &Path) -> Result<(Vec<f32>, u32, u32)> {
-        let src = File::open(path).context("Failed to open audio file")?;
+    pub fn decode(path: &Path) -> Result<(Vec<f32>, u32, u32), AudioError> {
+        let src = File::open(path).map_err(|source| AudioError::Open {
+            path: path.to_path_buf(),
+            source,
+        })?;
         let mss = MediaSourceStream::new(Box::new(src), Default::default());
 
         let mut hint = Hint::new();
@@ -29,19 +32,27 @@ impl AudioDecoder {
 
         let probed = symphonia::default::get_probe()
             .format(&hint, mss, &fmt_opts, &meta_opts)
-            .context("Unsupported audio format")?;
+            .map_err(|source| AudioError::UnsupportedFormat {
+                path: path.to_path_buf(),
+                source,
+            })?;
 
         let mut format = probed.format;
         let track = format
             .tracks()
             .iter()
             .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
-            .context("No supported audio track found")?;
+            .ok_or_else(|| AudioError::NoTrack {
+                path: path.to_path_buf(),
+            })?;
 
         let dec_opts: DecoderOptions = Default::default();
         let mut decoder = symphonia::default::get_codecs()
             .make(&track.codec_params, &dec_opts)
-            .context("Unsupported codec")?;
+            .map_err(|source| AudioError::UnsupportedCodec {
+                path: path.to_path_buf(),
+                source,
+            })?;
 
         let track_id = track.id;
         let sample_rate = track.codec_params.sample_rate.unwrap_or(44100);
@@ -54,18 +65,16 @@ impl AudioDecoder {
                 continue;
             }
 
-            match decoder.decode(&packet) {
-                Ok(decoded) => {
-                    let mut sample_buf =
-                        SampleBuffer::<f32>::new(decoded.capacity() as u64, *decoded.spec());
-                    sample_buf.copy_interleaved_ref(decoded);
-                    all_samples.extend_from_slice(sample_buf.samples());
-                }
-                Err(e) => {
-                    eprintln!("Error decoding packet: {}", e);
-                    break;
-                }
-            }
+            let decoded = decoder
+                .decode(&packet)
+                .map_err(|source| AudioError::Decode {
+                    path: path.to_path_buf(),
+                    source,
+                })?;
+            let mut sample_buf =
+                SampleBuffer::<f32>::new(decoded.capacity() as u64, *decoded.spec());
+            sample_buf.copy_interleaved_ref(decoded);
+            all_samples.extend_from_slice(sample_buf.samples());
         }
 
         Ok((all_samples, sample_rate, channels))
@@ -168,7 +177,7 @@ impl AudioMixer {
     }
 
     /// Export mixed audio to WAV file
-    pub fn export(&self, path: &Path, samples: &[f32]) -> Result<()> {
+    pub fn export(&self, path: &Path, samples: &[f32]) -> Result<(), AudioError> {
         let spec = hound::WavSpec {
             channels: self.output_channels as u16,
             sample_rate: self.output_sample_rate,
@@ -176,16 +185,17 @@ impl AudioMixer {
             sample_format: hound::SampleFormat::Float,
         };
 
-        let mut writer =
-            hound::WavWriter::create(path, spec).context("Failed to create WAV writer")?;
+        let export_error = |source| AudioError::Export {
+            path: path.to_path_buf(),
+            source,
+        };
+        let mut writer = hound::WavWriter::create(path, spec).map_err(export_error)?;
 
         for &sample in samples {
-            writer
-                .write_sample(sample)
-                .context("Failed to write sample")?;
+            writer.write_sample(sample).map_err(export_error)?;
         }
 
-        writer.finalize().context("Failed to finalize WAV file")?;
+        writer.finalize().map_err(export_error)?;
         Ok(())
     }
 }
@@ -0,0 +1,903 @@
+use crate::diagnostics::{escape_pointer, Diagnostic};
+use crate::formats::ScriptFormat;
+use crate::parser::{substitute, ScriptParser};
+use crate::schema::closest;
+use crate::script::SCRIPT_VERSION;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Keys an included fragment may have
+const FRAGMENT_KEYS: [&str; 5] = ["$schema", "version", "include", "variables", "definitions"];
+
+/// A script document with its includes, definitions and `use` instances
+/// resolved into plain scenes and layers
+#[derive(Debug, Default)]
+pub(crate) struct Composition {
+    /// Pointers of the composed document and where their values come from in
+    /// the original one; values under them follow their closest recorded parent
+    origins: Vec<(String, String)>,
+    /// Issues found while composing, pointing into the original document
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// A named scene or layer group
+struct Definition {
+    body: Value,
+    /// Where it's defined in the script (`None` for definitions from fragments)
+    pointer: Option<String>,
+    /// Whether it's a layer given on its own rather than in a list
+    single: bool,
+}
+
+impl Definition {
+    /// Where the `index`th layer of a layer group is defined in the script
+    fn member(&self, index: usize) -> Option<String> {
+        let pointer = self.pointer.as_ref()?;
+        Some(match self.single {
+            true => pointer.clone(),
+            false => format!("{}/{}", pointer, index),
+        })
+    }
+}
+
+/// What the script and its includes define
+#[derive(Default)]
+struct Definitions {
+    scenes: HashMap<String, Definition>,
+    layers: HashMap<String, Definition>,
+    variables: Map<String, Value>,
+}
+
+impl Composition {
+    /// Resolve `include`, `definitions` and `use` in `document`, the script at
+    /// `path` if it's a file, reading the included files relative to `base_dir`
+    pub fn resolve(document: &mut Value, path: Option<&Path>, base_dir: &Path) -> Self {
+        let mut composition = Self::default();
+        let Value::Object(map) = document else {
+            return composition;
+        };
+        let include = map.shift_remove("include");
+        let definitions = map.shift_remove("definitions");
+        if include.is_none() && definitions.is_none() && !uses_definitions(map) {
+            return composition;
+        }
+
+        // The script's own definitions and variables win over included ones,
+        // and earlier includes over later ones
+        let mut known = Definitions::default();
+        if let Some(definitions) = definitions {
+            composition.definitions(definitions, None, &mut known, "/definitions");
+        }
+        known.variables = match map.shift_remove("variables") {
+            Some(Value::Object(variables)) => variables,
+            // Left for interpolation to report
+            Some(other) => {
+                map.insert("variables".into(), other);
+                Map::new()
+            }
+            None => Map::new(),
+        };
+        if let Some(include) = include {
+            let mut stack: Vec<_> = path.map(Include::new).into_iter().collect();
+            composition.includes(include, None, base_dir, &mut stack, "/include", &mut known);
+        }
+        if !known.variables.is_empty() && !map.contains_key("variables") {
+            let variables = std::mem::take(&mut known.variables);
+            map.insert("variables".into(), Value::Object(variables));
+        }
+
+        if let Some(Value::Array(scenes)) = map.get_mut("scenes") {
+            let scenes = std::mem::take(scenes);
+            let composed = composition.scenes(scenes, &known);
+            map.insert("scenes".into(), Value::Array(composed));
+        }
+        composition
+    }
+
+    /// Pointer in the original document of the value at `pointer` in the
+    /// composed one
+    pub fn original_pointer(&self, pointer: &str) -> String {
+        self.origins
+            .iter()
+            .filter_map(|(composed, original)| {
+                let rest = pointer.strip_prefix(composed.as_str())?;
+                (rest.is_empty() || rest.starts_with('/'))
+                    .then(|| (composed.len(), format!("{}{}", original, rest)))
+            })
+            .max_by_key(|(length, _)| *length)
+            .map_or_else(|| pointer.to_string(), |(_, original)| original)
+    }
+
+    fn error(&mut self, code: &'static str, pointer: impl Into<String>, message: String) {
+        self.diagnostics
+            .push(Diagnostic::error(code, pointer, message));
+    }
+
+    /// Collect `definitions`, found at `pointer` in the script, or in the
+    /// `fragment` file included at `pointer`
+    fn definitions(
+        &mut self,
+        definitions: Value,
+        fragment: Option<&Path>,
+        known: &mut Definitions,
+        pointer: &str,
+    ) {
+        let context = match fragment {
+            Some(fragment) => format!("in {}: ", fragment.display()),
+            None => String::new(),
+        };
+        // Fragments aren't located, so their issues are reported at the include
+        let at = |inner: &str| match fragment {
+            Some(_) => pointer.to_string(),
+            None => inner.to_string(),
+        };
+        let Value::Object(definitions) = definitions else {
+            self.error(
+                "invalid-definition",
+                pointer,
+                format!(
+                    "{}definitions must map `scenes` and `layers` to named definitions",
+                    context
+                ),
+            );
+            return;
+        };
+        for (group, named) in definitions {
+            let group_pointer = format!("{}/{}", pointer, escape_pointer(&group));
+            let (target, is_scene) = match group.as_str() {
+                "scenes" => (&mut known.scenes, true),
+                "layers" => (&mut known.layers, false),
+                _ => {
+                    let message = format!(
+                        "{}unknown definitions `{}`, definitions are `scenes` or `layers`",
+                        context, group
+                    );
+                    self.error("invalid-definition", at(&group_pointer), message);
+                    continue;
+                }
+            };
+            let Value::Object(named) = named else {
+                let message = format!("{}definitions of {} must be named", context, group);
+                self.error("invalid-definition", at(&group_pointer), message);
+                continue;
+            };
+            for (name, body) in named {
+                let body_pointer = format!("{}/{}", group_pointer, escape_pointer(&name));
+                let single = !is_scene && body.is_object();
+                let body = match (is_scene, body) {
+                    (true, body @ Value::Object(_)) | (false, body @ Value::Array(_)) => body,
+                    // A single layer is a group of one
+                    (false, body @ Value::Object(_)) => Value::Array(vec![body]),
+                    _ => {
+                        let expected = match is_scene {
+                            true => "a scene",
+                            false => "a list of layers",
+                        };
+                        let message =
+                            format!("{}definition `{}` must be {}", context, name, expected);
+                        self.error("invalid-definition", at(&body_pointer), message);
+                        continue;
+                    }
+                };
+                target.entry(name).or_insert(Definition {
+                    body,
+                    pointer: fragment.is_none().then_some(body_pointer),
+                    single,
+                });
+            }
+        }
+    }
+
+    /// Read the fragments listed in an `include` of `fragment` (or of the
+    /// script), with `stack` the files being included. Issues are reported at
+    /// `site`, the script's include that led to the fragment.
+    fn includes(
+        &mut self,
+        include: Value,
+        fragment: Option<&Path>,
+        base_dir: &Path,
+        stack: &mut Vec<Include>,
+        site: &str,
+        known: &mut Definitions,
+    ) {
+        let context = match fragment {
+            Some(fragment) => format!("in {}: ", fragment.display()),
+            None => String::new(),
+        };
+        let paths = match include {
+            Value::String(path) => vec![Value::String(path)],
+            Value::Array(paths) => paths,
+            _ => Vec::new(),
+        };
+        if paths.is_empty() || paths.iter().any(|path| !path.is_string()) {
+            self.error(
+                "invalid-include",
+                site,
+                format!("{}include must list the paths of script fragments", context),
+            );
+            return;
+        }
+
+        for (index, path) in paths.iter().enumerate() {
+            let path = base_dir.join(path.as_str().unwrap_or_default());
+            // Issues in nested fragments are reported at the script's include
+            let site = match fragment {
+                None => format!("{}/{}", site, index),
+                Some(_) => site.to_string(),
+            };
+            let included = Include::new(&path);
+            if let Some(start) = stack
+                .iter()
+                .position(|file| file.canonical == included.canonical)
+            {
+                let cycle: Vec<_> = stack[start..]
+                    .iter()
+                    .chain([&included])
+                    .map(|file| file.path.display().to_string())
+                    .collect();
+                self.error(
+                    "include-cycle",
+                    site,
+                    format!("include cycle: {}", cycle.join(" -> ")),
+                );
+                continue;
+            }
+            let Some(fragment) = self.read_fragment(&path, &site, &context) else {
+                continue;
+            };
+
+            stack.push(included);
+            for (key, value) in fragment {
+                match key.as_str() {
+                    "definitions" => self.definitions(value, Some(&path), known, &site),
+                    "variables" => match value {
+                        Value::Object(values) => {
+                            for (name, value) in values {
+                                known.variables.entry(name).or_insert(value);
+                            }
+                        }
+                        _ => self.error(
+                            "invalid-include",
+                            site.as_str(),
+                            format!(
+                                "{}variables of {} must map names to values",
+                                context,
+                                path.display()
+                            ),
+                        ),
+                    },
+                    "include" => {
+                        let fragment_dir = path.parent().unwrap_or(Path::new(""));
+                        self.includes(value, Some(&path), fragment_dir, stack, &site, known);
+                    }
+                    _ => {}
+                }
+            }
+            stack.pop();
+        }
+    }
+
+    /// Read an included file, which must only hold `FRAGMENT_KEYS`
+    fn read_fragment(
+        &mut self,
+        path: &Path,
+        site: &str,
+        context: &str,
+    ) -> Option<Map<String, Value>> {
+        let source = match std::fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(error) => {
+                let message = format!(
+                    "{}can't read included file {}: {}",
+                    context,
+                    path.display(),
+                    error
+                );
+                self.error("invalid-include", site, message);
+                return None;
+            }
+        };
+        let format = ScriptFormat::detect(path);
+        let fragment = match format.read(&source) {
+            Ok((Value::Object(fragment), _)) => fragment,
+            Ok(_) => {
+                let message = format!(
+                    "{}included file {} must be a map of {}",
+                    context,
+                    path.display(),
+                    fragment_keys()
+                );
+                self.error("invalid-include", site, message);
+                return None;
+            }
+            Err(error) => {
+                let position = match (error.line, error.column) {
+                    (Some(line), Some(column)) => format!(":{}:{}", line, column),
+                    _ => String::new(),
+                };
+                let message = format!(
+                    "{}invalid {} in included file {}{}: {}",
+                    context,
+                    format,
+                    path.display(),
+                    position,
+                    error.message
+                );
+                self.error("invalid-include", site, message);
+                return None;
+            }
+        };
+        if let Some(key) = fragment
+            .keys()
+            .find(|key| !FRAGMENT_KEYS.contains(&key.as_str()))
+        {
+            let message = format!(
+                "{}included file {} has `{}`, fragments only have {}",
+                context,
+                path.display(),
+                key,
+                fragment_keys()
+            );
+            self.error("invalid-include", site, message);
+            return None;
+        }
+
+        // Fragments are versioned like scripts, their definitions are upgraded the same way
+        let mut fragment = Value::Object(fragment);
+        let mut dropped = Vec::new();
+        match ScriptParser::migrate(&mut fragment, &mut dropped) {
+            Err(error) => {
+                let message = format!("{}included file {}: {}", context, path.display(), error);
+                self.error("unsupported-version", site, message);
+                return None;
+            }
+            Ok(version) if version < SCRIPT_VERSION => {
+                let message = format!(
+                    "{}included file {} is version {}, upgrade it to version {} with `migrate`",
+                    context,
+                    path.display(),
+                    version,
+                    SCRIPT_VERSION
+                );
+                self.diagnostics
+                    .push(Diagnostic::warning("outdated-version", site, message));
+            }
+            Ok(_) => {}
+        }
+        for diagnostic in dropped {
+            let message = format!(
+                "{}included file {} at {}: {}",
+                context,
+                path.display(),
+                diagnostic.pointer,
+                diagnostic.message
+            );
+            self.diagnostics
+                .push(Diagnostic::warning(diagnostic.code, site, message));
+        }
+        match fragment {
+            Value::Object(fragment) => Some(fragment),
+            _ => None,
+        }
+    }
+
+    /// Instantiate the scenes that `use` a definition
+    fn scenes(&mut self, scenes: Vec<Value>, known: &Definitions) -> Vec<Value> {
+        let mut composed = Vec::with_capacity(scenes.len());
+        for (index, scene) in scenes.into_iter().enumerate() {
+            let pointer = format!("/scenes/{}", index);
+            let target = format!("/scenes/{}", composed.len());
+            let mut instance = match Instance::of(scene) {
+                Ok(instance) => instance,
+                // Not a map: left for the parser to report where it's written
+                Err(scene) => {
+                    self.origins.push((target, pointer));
+                    composed.push(scene);
+                    continue;
+                }
+            };
+            let scene = match instance.name.take() {
+                None => {
+                    self.origins.push((target.clone(), pointer.clone()));
+                    let mut scene = Value::Object(instance.overrides);
+                    let values = Map::new();
+                    self.layers(
+                        &mut scene,
+                        &target,
+                        Some(&pointer),
+                        &pointer,
+                        &values,
+                        known,
+                    );
+                    scene
+                }
+                Some(name) => match self.instantiate(instance, &name, &pointer, &target, known) {
+                    Some(scene) => scene,
+                    None => continue,
+                },
+            };
+            composed.push(scene);
+        }
+        composed
+    }
+
+    /// A scene instance, composed at `target`
+    fn instantiate(
+        &mut self,
+        instance: Instance,
+        name: &str,
+        pointer: &str,
+        target: &str,
+        known: &Definitions,
+    ) -> Option<Value> {
+        let definition = self.lookup(&known.scenes, name, "scene", pointer)?;
+        let mut scene = definition.body.clone();
+        let origin = definition.pointer.as_deref().unwrap_or(pointer);
+        self.origins.push((target.to_string(), origin.to_string()));
+        let values = self.values(&instance, pointer);
+        self.diagnostics
+            .extend(substitute(&mut scene, origin, &values));
+
+        // Fields given next to `use` replace the definition's
+        let layers_origin = match instance.overrides.contains_key("layers") {
+            true => Some(pointer.to_string()),
+            false => definition.pointer.clone(),
+        };
+        let Value::Object(fields) = &mut scene else {
+            unreachable!("scene definitions are maps");
+        };
+        for (key, value) in instance.overrides {
+            let key_pointer = format!("/{}", escape_pointer(&key));
+            self.origins.push((
+                format!("{}{}", target, key_pointer),
+                format!("{}{}", pointer, key_pointer),
+            ));
+            fields.insert(key, value);
+        }
+
+        let layers_origin = layers_origin.as_deref();
+        self.layers(&mut scene, target, layers_origin, pointer, &values, known);
+        Some(scene)
+    }
+
+    /// Expand the layer groups a scene uses, filling their placeholders with
+    /// their own `with` values and then the scene's. `origin` is where the
+    /// scene's layers are in the script, `site` where to report them otherwise.
+    fn layers(
+        &mut self,
+        scene: &mut Value,
+        target: &str,
+        origin: Option<&str>,
+        site: &str,
+        scene_values: &Map<String, Value>,
+        known: &Definitions,
+    ) {
+        let Some(Value::Array(layers)) = scene.get_mut("layers") else {
+            return;
+        };
+        let layers = std::mem::take(layers);
+        let mut composed = Vec::with_capacity(layers.len());
+        for (index, layer) in layers.into_iter().enumerate() {
+            let layer_origin = origin.map_or_else(
+                || site.to_string(),
+                |origin| format!("{}/layers/{}", origin, index),
+            );
+            let instance = match Instance::of(layer) {
+                Ok(instance) if instance.name.is_some() => instance,
+                Ok(instance) => {
+                    let layer = Value::Object(instance.overrides);
+                    self.push_layer(&mut composed, layer, target, &layer_origin);
+                    continue;
+                }
+                // Not a map: left for the parser to report
+                Err(layer) => {
+                    self.push_layer(&mut composed, layer, target, &layer_origin);
+                    continue;
+                }
+            };
+            let name = instance.name.clone().unwrap_or_default();
+            if let Some(key) = instance.overrides.keys().next() {
+                self.error(
+                    "invalid-definition",
+                    format!("{}/{}", layer_origin, escape_pointer(key)),
+                    format!("layer groups take `with` values, not `{}`", key),
+                );
+                continue;
+            }
+            let Some(definition) = self.lookup(&known.layers, &name, "layer", &layer_origin) else {
+                continue;
+            };
+            let mut values = scene_values.clone();
+            values.extend(self.values(&instance, &layer_origin));
+            let Value::Array(group) = definition.body.clone() else {
+                unreachable!("layer definitions are lists");
+            };
+            for (member, mut layer) in group.into_iter().enumerate() {
+                let origin = definition
+                    .member(member)
+                    .unwrap_or_else(|| layer_origin.clone());
+                if layer.get("use").is_some() {
+                    self.error(
+                        "invalid-definition",
+                        origin,
+                        format!("layer group `{}` can't use other definitions", name),
+                    );
+                    continue;
+                }
+                self.diagnostics
+                    .extend(substitute(&mut layer, &origin, &values));
+                self.push_layer(&mut composed, layer, target, &origin);
+            }
+        }
+        scene["layers"] = Value::Array(composed);
+    }
+
+    fn push_layer(&mut self, layers: &mut Vec<Value>, layer: Value, scene: &str, origin: &str) {
+        self.origins.push((
+            format!("{}/layers/{}", scene, layers.len()),
+            origin.to_string(),
+        ));
+        layers.push(layer);
+    }
+
+    /// The `with` values of an instance at `pointer`
+    fn values(&mut self, instance: &Instance, pointer: &str) -> Map<String, Value> {
+        match &instance.with {
+            None => Map::new(),
+            Some(Value::Object(values)) => values.clone(),
+            Some(_) => {
+                self.error(
+                    "invalid-definition",
+                    format!("{}/with", pointer),
+                    "`with` must map placeholder names to values".into(),
+                );
+                Map::new()
+            }
+        }
+    }
+
+    fn lookup<'a>(
+        &mut self,
+        definitions: &'a HashMap<String, Definition>,
+        name: &str,
+        kind: &str,
+        pointer: &str,
+    ) -> Option<&'a Definition> {
+        let definition = definitions.get(name);
+        if definition.is_none() {
+            let message = match closest(name, definitions.keys()) {
+                Some(known) => format!(
+                    "undefined {} definition `{}`, did you mean `{}`?",
+                    kind, name, known
+                ),
+                None => format!("undefined {} definition `{}`", kind, name),
+            };
+            self.error("undefined-definition", format!("{}/use", pointer), message);
+        }
+        definition
+    }
+}
+
+/// A file being included, by the path it was named by and the canonical one
+/// that tells whether it's already being included
+struct Include {
+    path: PathBuf,
+    canonical: PathBuf,
+}
+
+impl Include {
+    fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            canonical: path.canonicalize().unwrap_or_else(|_| path.to_path_buf()),
+        }
+    }
+}
+
+/// A scene or layer, split into the definition it uses (if any), its `with`
+/// values and its other fields
+struct Instance {
+    name: Option<String>,
+    with: Option<Value>,
+    overrides: Map<String, Value>,
+}
+
+impl Instance {
+    /// The scene or layer `value`, or `value` back if it isn't a map
+    fn of(value: Value) -> Result<Self, Value> {
+        let Value::Object(mut map) = value else {
+            return Err(value);
+        };
+        let Some(name) = map.get("use").and_then(Value::as_str).map(str::to_string) else {
+            return Ok(Self {
+                name: None,
+                with: None,
+                overrides: map,
+            });
+        };
+        map.shift_remove("use");
+        let with = map.shift_remove("with");
+        Ok(Self {
+            name: Some(name),
+            with,
+            overrides: map,
+        })
+    }
+}
+
+/// Whether any scene or layer of the script uses a definition
+fn uses_definitions(script: &Map<String, Value>) -> bool {
+    let uses = |value: &Value| value.get("use").is_some();
+    script
+        .get("scenes")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .any(|scene| {
+            uses(scene)
+                || scene
+                    .get("layers")
+                    .and_then(Value::as_array)
+                    .is_some_and(|layers| layers.iter().any(uses))
+        })
+}
+
+fn fragment_keys() -> String {
+    let keys: Vec<_> = FRAGMENT_KEYS[2..]
+        .iter()
+        .map(|key| format!("`{}`", key))
+        .collect();
+    keys.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::Severity;
+    use serde_json::json;
+    use std::fs;
+
+    #[test]
+    fn test_resolve_definitions() {
+        let mut document = json!({
+            "version": 2,
+            "definitions": {
+                "scenes": {
+                    "intro": {
+                        "id": "intro",
+                        "duration": 2.0,
+                        "layers": [{"use": "logo"}, {"type": "text", "content": "{{headline}}"}]
+                    }
+                },
+                "layers": {"logo": {"type": "image", "source": "logo.png"}}
+            },
+            "metadata": {"title": "T"},
+            "scenes": [
+                {"use": "intro", "with": {"headline": "Hello"}, "id": "first"},
+                {"id": "body", "duration": 1.0, "layers": [{"type": "image", "source": "a.png"}]}
+            ]
+        });
+        let composition = Composition::resolve(&mut document, None, Path::new(""));
+        assert!(composition.diagnostics.is_empty());
+        assert_eq!(
+            document,
+            json!({
+                "version": 2,
+                "metadata": {"title": "T"},
+                "scenes": [
+                    {
+                        "id": "first",
+                        "duration": 2.0,
+                        "layers": [
+                            {"type": "image", "source": "logo.png"},
+                            {"type": "text", "content": "Hello"}
+                        ]
+                    },
+                    {"id": "body", "duration": 1.0, "layers": [{"type": "image", "source": "a.png"}]}
+                ]
+            })
+        );
+
+        // Issues in the composed script point where their values are written
+        let origins = [
+            ("/scenes/0/duration", "/definitions/scenes/intro/duration"),
+            ("/scenes/0/id", "/scenes/0/id"),
+            (
+                "/scenes/0/layers/0/source",
+                "/definitions/layers/logo/source",
+            ),
+            (
+                "/scenes/0/layers/1/font",
+                "/definitions/scenes/intro/layers/1/font",
+            ),
+            ("/scenes/1/layers/0", "/scenes/1/layers/0"),
+            ("/metadata/fps", "/metadata/fps"),
+        ];
+        for (composed, original) in origins {
+            assert_eq!(composition.original_pointer(composed), original);
+        }
+    }
+
+    #[test]
+    fn test_resolve_undefined() {
+        let mut document = json!({
+            "definitions": {"scenes": {"outro": {"id": "outro", "duration": 1.0, "layers": []}}},
+            "scenes": [
+                {"use": "outr"},
+                {"id": "a", "duration": 1.0, "layers": [{"use": "cta", "source": "x.png"}]}
+            ]
+        });
+        let composition = Composition::resolve(&mut document, None, Path::new(""));
+        let found: Vec<_> = composition
+            .diagnostics
+            .iter()
+            .map(|d| (d.code, d.pointer.as_str(), d.message.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "undefined-definition",
+                    "/scenes/0/use",
+                    "undefined scene definition `outr`, did you mean `outro`?"
+                ),
+                (
+                    "invalid-definition",
+                    "/scenes/1/layers/0/source",
+                    "layer groups take `with` values, not `source`"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_non_maps() {
+        // Scenes and layers that aren't maps are kept for the parser to report
+        let mut document = json!({
+            "definitions": {"scenes": {"intro": {"id": "intro", "duration": 1.0, "layers": []}}},
+            "scenes": [
+                {"use": "intro"},
+                "garbage",
+                {"id": "a", "duration": 1.0, "layers": [{"type": "image", "source": "a.png"}, 42]}
+            ]
+        });
+        let composition = Composition::resolve(&mut document, None, Path::new(""));
+        assert!(composition.diagnostics.is_empty());
+        assert_eq!(document["scenes"][1], "garbage");
+        assert_eq!(document["scenes"][2]["layers"][1], 42);
+        assert_eq!(composition.original_pointer("/scenes/1"), "/scenes/1");
+        assert_eq!(
+            composition.original_pointer("/scenes/2/layers/1"),
+            "/scenes/2/layers/1"
+        );
+
+        let check = crate::parser::ScriptParser::check_source(
+            r#"{
+  "version": 2,
+  "definitions": {"layers": {"logo": {"type": "image", "source": "logo.png"}}},
+  "metadata": {"title": "T", "resolution": "640x360", "fps": 30, "duration": 1.0},
+  "scenes": [{"id": "a", "duration": 1.0, "layers": [{"use": "logo"}, "garbage"]}]
+}"#
+            .to_string(),
+            &crate::parser::ParseOptions::default(),
+        );
+        assert!(check.script.is_none());
+        let error = &check.diagnostics[0];
+        assert_eq!(
+            (error.code, error.pointer.as_str(), error.line),
+            ("invalid-structure", "/scenes/0/layers/1", Some(5))
+        );
+    }
+
+    #[test]
+    fn test_resolve_includes() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let shared = temp_dir.path().join("shared");
+        fs::create_dir(&shared).unwrap();
+        fs::write(
+            shared.join("brand.yaml"),
+            "version: 2\ninclude: [colors.json]\n\
+             definitions:\n  layers:\n    logo:\n      - {type: image, source: \"{{logo}}\"}\n",
+        )
+        .unwrap();
+        fs::write(
+            shared.join("colors.json"),
+            r#"{"version": 2, "variables": {"logo": "brand.png", "accent": "red"}}"#,
+        )
+        .unwrap();
+        let script = temp_dir.path().join("script.json");
+
+        let mut document = json!({
+            "include": ["shared/brand.yaml"],
+            "variables": {"accent": "blue"},
+            "scenes": [{"id": "a", "duration": 1.0, "layers": [{"use": "logo"}]}]
+        });
+        let composition = Composition::resolve(&mut document, Some(&script), temp_dir.path());
+        assert!(composition.diagnostics.is_empty());
+        assert_eq!(
+            document["variables"],
+            json!({"accent": "blue", "logo": "brand.png"})
+        );
+        assert_eq!(document["scenes"][0]["layers"][0]["source"], "{{logo}}");
+        // Layers from fragments are reported at their use
+        assert_eq!(
+            composition.original_pointer("/scenes/0/layers/0/source"),
+            "/scenes/0/layers/0/source"
+        );
+
+        fs::write(
+            shared.join("colors.json"),
+            r#"{"version": 2, "include": ["../script.json"]}"#,
+        )
+        .unwrap();
+        fs::write(&script, "{}").unwrap();
+        let mut document = json!({"include": ["shared/brand.yaml", "missing.json"]});
+        let composition = Composition::resolve(&mut document, Some(&script), temp_dir.path());
+        let found: Vec<_> = composition
+            .diagnostics
+            .iter()
+            .map(|d| (d.code, d.pointer.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("include-cycle", "/include/0"),
+                ("invalid-include", "/include/1")
+            ]
+        );
+        assert_eq!(
+            composition.diagnostics[0].message,
+            format!(
+                "include cycle: {} -> {dir}/brand.yaml -> {dir}/colors.json -> {dir}/../script.json",
+                script.display(),
+                dir = shared.display()
+            )
+        );
+
+        // Fragments are upgraded like scripts, and refused if they're too new
+        fs::write(shared.join("colors.json"), r#"{"version": 2}"#).unwrap();
+        fs::write(
+            shared.join("brand.yaml"),
+            "include: [colors.json]\n\
+             definitions:\n  layers:\n    logo: {type: image, source: a.png, effects: [fade_in]}\n",
+        )
+        .unwrap();
+        let mut document = json!({
+            "include": ["shared/brand.yaml"],
+            "scenes": [{"id": "a", "duration": 1.0, "layers": [{"use": "logo"}]}]
+        });
+        let composition = Composition::resolve(&mut document, Some(&script), temp_dir.path());
+        assert_eq!(
+            document["scenes"][0]["layers"][0]["effects"],
+            json!([{"fade_in": {}}])
+        );
+        let found: Vec<_> = composition
+            .diagnostics
+            .iter()
+            .map(|d| (d.severity, d.code, d.pointer.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![(Severity::Warning, "outdated-version", "/include/0")]
+        );
+
+        fs::write(shared.join("colors.json"), r#"{"version": 99}"#).unwrap();
+        let mut document = json!({"include": ["shared/brand.yaml"]});
+        let composition = Composition::resolve(&mut document, Some(&script), temp_dir.path());
+        let error = &composition.diagnostics[1];
+        assert_eq!(
+            (error.code, error.pointer.as_str()),
+            ("unsupported-version", "/include/0")
+        );
+        assert_eq!(
+            error.message,
+            format!(
+                "in {dir}/brand.yaml: included file {dir}/colors.json: Unsupported script version 99 \
+                 (this build reads versions 1 to {})",
+                SCRIPT_VERSION,
+                dir = shared.display()
+            )
+        );
+    }
+}
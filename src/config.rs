@@ -1,3 +1,4 @@
+use crate::script::EncoderSettings;
 use serde::Deserialize;
 use std::path::PathBuf;
 
@@ -6,12 +7,17 @@ pub struct AppConfig {
     pub renderer: RendererConfig,
     pub video: VideoConfig,
     pub assets: AssetsConfig,
+    /// Default encoder settings (scripts and the command line can override them)
+    #[serde(default)]
+    pub encoder: EncoderSettings,
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct RendererConfig {
     pub engine: String, // "native" or "blender"
     pub output_dir: PathBuf,
+    /// Frames the CPU renderer keeps in flight at once (0 = twice the cores, 1 = sequential)
+    pub max_in_flight_frames: usize,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -31,6 +37,7 @@ impl Default for AppConfig {
             renderer: RendererConfig {
                 engine: "native".to_string(),
                 output_dir: PathBuf::from("output"),
+                max_in_flight_frames: 0,
             },
             video: VideoConfig {
                 default_resolution: "1920x1080".to_string(),
@@ -39,6 +46,7 @@ impl Default for AppConfig {
             assets: AssetsConfig {
                 base_path: PathBuf::from("."),
             },
+            encoder: EncoderSettings::default(),
         }
     }
 }
@@ -48,6 +56,7 @@ impl AppConfig {
         let builder = config::Config::builder()
             .set_default("renderer.engine", "native")?
             .set_default("renderer.output_dir", "output")?
+            .set_default("renderer.max_in_flight_frames", 0)?
             .set_default("video.default_resolution", "1920x1080")?
             .set_default("video.default_fps", 30)?
             .set_default("assets.base_path", ".")?
@@ -68,6 +77,7 @@ mod tests {
     fn test_default_config() {
         let config = AppConfig::default();
         assert_eq!(config.renderer.engine, "native");
+        assert_eq!(config.renderer.max_in_flight_frames, 0);
         assert_eq!(config.video.default_resolution, "1920x1080");
         assert_eq!(config.video.default_fps, 30);
     }
@@ -1,43 +1,56 @@
 use crate::analysis::credibility::CredibilityReport;
+use crate::events::{ConsoleObserver, Level, Observer};
 use crate::script::VideoScript;
 
 pub struct CredibilityContext;
 
 impl CredibilityContext {
     pub fn run(script: &VideoScript) -> CredibilityReport {
+        Self::run_with(script, &ConsoleObserver)
+    }
+
+    /// Analyse the script's credibility, reporting the findings to `observer`
+    pub fn run_with(script: &VideoScript, observer: &dyn Observer) -> CredibilityReport {
         // Pillar 3: Credibility (Trustworthy) - Analysis
-        println!("\n🛡️ Analyzing Credibility...");
+        observer.info("Analyzing Credibility...");
         let report = crate::analysis::credibility::CredibilityAnalyzer::analyze(script);
 
-        println!("   Score: {}/100", report.score);
+        observer.info(format!("Credibility score: {}/100", report.score));
 
         if !report.claims.is_empty() {
-            println!("   🔍 Detected {} claims:", report.claims.len());
             for claim in &report.claims {
-                let status = if claim.verified {
-                    "✅ Verified"
+                let (level, status) = if claim.verified {
+                    (Level::Info, "Verified")
                 } else {
-                    "⚠️ Unverified"
+                    (Level::Warning, "Unverified")
                 };
-                println!("      - [{}] \"{}\" ({})", status, claim.text, claim.reason);
+                observer.message(
+                    level,
+                    format!("{} claim: \"{}\" ({})", status, claim.text, claim.reason),
+                );
             }
         } else {
-            println!("   ✅ No specific claims detected");
+            observer.info("No specific claims detected");
         }
 
         if !report.citations.is_empty() {
-            println!("   📚 Citations:");
             for citation in &report.citations {
-                println!("      - {}", citation);
+                observer.info(format!("Citation: {}", citation));
             }
         } else {
-            println!("   ⚠️  No citations provided");
+            observer.warning("No citations provided");
         }
 
-        println!("\n   ✅ Quality Checklist:");
         for item in &report.checklist {
-            let icon = if item.passed { "✓" } else { "❌" };
-            println!("      {} [{}] {}", icon, item.category, item.message);
+            let level = if item.passed {
+                Level::Info
+            } else {
+                Level::Warning
+            };
+            observer.message(
+                level,
+                format!("Checklist [{}] {}", item.category, item.message),
+            );
         }
 
         report
@@ -1,3 +1,4 @@
+use crate::events::{ConsoleObserver, Level, Observer, Stage};
 use crate::script::VideoScript;
 
 use crate::analysis::narrative::NarrativeReport;
@@ -6,88 +7,82 @@ pub struct NarrativeContext;
 
 impl NarrativeContext {
     pub fn run(script: &VideoScript) -> NarrativeReport {
+        Self::run_with(script, &ConsoleObserver)
+    }
+
+    /// Analyse the narrative, reporting the findings to `observer`
+    pub fn run_with(script: &VideoScript, observer: &dyn Observer) -> NarrativeReport {
         // Pillar 2: Narrative (Engaging) - Analysis
-        println!("\n📊 Analyzing Narrative Structure...");
+        observer.stage_started(Stage::Analysis);
+        observer.info("Analyzing Narrative Structure...");
         let report = crate::analysis::narrative::NarrativeAnalyzer::analyze(script);
 
-        println!("   Score: {}/100", report.score);
+        observer.info(format!("Narrative score: {}/100", report.score));
 
         // Structure validation
         if !report.structure_valid {
-            println!("   ❌ Structure Issues:");
             for error in &report.structure_errors {
-                println!("      - {}", error);
+                observer.warning(format!("Structure issue: {}", error));
             }
         } else {
-            println!("   ✅ Structure: Valid (Hook → Body → Payoff)");
+            observer.info("Structure: Valid (Hook → Body → Payoff)");
         }
 
         // Structure recommendations
-        if !report.structure_recommendations.is_empty() {
-            println!("   💡 Structure Recommendations:");
-            for rec in &report.structure_recommendations {
-                let emoji = match rec.severity {
-                    crate::analysis::narrative::Severity::Error => "❌",
-                    crate::analysis::narrative::Severity::Warning => "⚠️",
-                    crate::analysis::narrative::Severity::Info => "ℹ️",
-                };
-                println!("      {} [{}] {}", emoji, rec.category, rec.message);
-            }
+        for rec in &report.structure_recommendations {
+            let level = match rec.severity {
+                crate::analysis::narrative::Severity::Error => Level::Error,
+                crate::analysis::narrative::Severity::Warning => Level::Warning,
+                crate::analysis::narrative::Severity::Info => Level::Info,
+            };
+            observer.message(level, format!("[{}] {}", rec.category, rec.message));
         }
 
         // Pacing alerts
         if !report.pacing_alerts.is_empty() {
-            println!("   ⚠️ Pacing Alerts:");
             for alert in &report.pacing_alerts {
-                println!("      - {}", alert.message);
+                observer.warning(format!("Pacing: {}", alert.message));
             }
         } else {
-            println!("   ✅ Pacing: Optimal");
+            observer.info("Pacing: Optimal");
         }
 
         // Retention warnings
-        if !report.retention_warnings.is_empty() {
-            println!("   ⚠️ Retention Warnings:");
-            for warning in &report.retention_warnings {
-                println!("      - {}", warning.message);
-            }
+        for warning in &report.retention_warnings {
+            observer.warning(format!("Retention: {}", warning.message));
         }
 
         // Advanced Retention Analysis
-        println!("\n🎯 Analyzing Retention Metrics...");
+        observer.info("Analyzing Retention Metrics...");
         let heatmap = crate::analysis::retention::RetentionAnalyzer::generate_heatmap(script);
-        println!(
-            "   Overall Retention Score: {:.1}/100",
+        observer.info(format!(
+            "Overall Retention Score: {:.1}/100",
             heatmap.overall_retention_score
-        );
+        ));
 
         if !heatmap.critical_moments.is_empty() {
-            println!("   ⚠️  Critical Moments (Low Retention):");
             for scene_idx in &heatmap.critical_moments {
                 let scene_retention = &heatmap.scene_scores[*scene_idx];
-                println!(
-                    "      - Scene {}: {:.1} momentum, {:.1} retention",
+                observer.warning(format!(
+                    "Low retention in scene {}: {:.1} momentum, {:.1} retention",
                     scene_idx + 1,
                     scene_retention.momentum,
                     scene_retention.retention_score
-                );
+                ));
             }
         } else {
-            println!("   ✅ No critical retention drop-offs detected");
+            observer.info("No critical retention drop-offs detected");
         }
 
         let dropoff_predictions =
             crate::analysis::retention::RetentionAnalyzer::predict_dropoff(script);
-        if !dropoff_predictions.is_empty() {
-            println!("   📉 Drop-off Predictions:");
-            for pred in dropoff_predictions.iter().take(3) {
-                println!(
-                    "      - Scene {}: {:.0}% predicted drop-off ({})",
-                    pred.scene_index + 1,
-                    pred.predicted_dropoff_percent,
-                    pred.reason
-                );
-            }
+        for pred in dropoff_predictions.iter().take(3) {
+            observer.info(format!(
+                "Predicted drop-off in scene {}: {:.0}% ({})",
+                pred.scene_index + 1,
+                pred.predicted_dropoff_percent,
+                pred.reason
+            ));
         }
 
         report
@@ -1,105 +1,303 @@
-use crate::script::VideoScript;
+use crate::error::{RenderError, Result};
+use crate::events::{Observer, Stage};
+use crate::renderer::{
+    EncodeOptions, FrameSelection, RendererOptions, RendererRegistry, Timeline, VideoEncoder,
+};
+use crate::script::{EncoderSettings, Layer, VideoScript};
 use crate::AssetLoader;
-use anyhow::Result;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
 pub struct PerformanceContext;
 
+/// How a script is rendered and encoded
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    pub output_dir: PathBuf,
+    /// Name of the backend in the renderer registry
+    pub renderer: String,
+    pub renderer_options: RendererOptions,
+    /// Write every frame to the output directory (resumable) instead of streaming them
+    pub keep_frames: bool,
+    /// Render with a transparent background even if the script doesn't ask for one
+    pub transparent: bool,
+    /// Encoder settings, already layered over the script's and the config's
+    pub encoder: EncoderSettings,
+    /// Part of the timeline to render (everything if unset)
+    pub frames: Option<FrameSelection>,
+}
+
 impl PerformanceContext {
     pub fn run(
         script: &VideoScript,
         loader: &mut AssetLoader,
-        output_dir: &Path,
-        use_blender: bool,
-        use_gpu: bool,
+        options: &RenderOptions,
+        renderers: &RendererRegistry,
     ) -> Result<()> {
-        // 1. Rendering
-        println!("\n🎬 Rendering frames...");
-
+        let output_dir = options.output_dir.as_path();
+        let keep_frames = options.keep_frames;
+        let observer = options.renderer_options.observer.as_ref();
         if !output_dir.exists() {
-            std::fs::create_dir_all(output_dir)?;
+            create_dir(output_dir)?;
         }
 
-        if use_blender {
-            println!("🎨 Using Blender Backend");
-            let renderer =
-                crate::renderer::BlenderRenderer::new(script.clone(), output_dir.to_path_buf());
-            renderer.render()?;
+        let timeline = Timeline::from_script(script);
+        let frames = match &options.frames {
+            Some(selection) => timeline.frame_range(selection)?,
+            None => 0..timeline.total_frames(),
+        };
+        // A single moment (`--at`) is kept as an image instead of becoming a video
+        let single_frame = matches!(options.frames, Some(FrameSelection::At(_)));
+        let encoder_available = VideoEncoder::is_available();
+        let encode = encoder_available && !single_frame;
+
+        // 1. Audio Processing (mixed first so it can be muxed while frames stream in)
+        let audio_path_opt = if encode {
+            Self::mix_audio(script, loader, output_dir, observer)
         } else {
-            println!("🎨 Using Native Engine (CPU/GPU)");
-            let mut engine = crate::renderer::RenderEngine::new(script.clone(), use_gpu);
-            engine.render(output_dir, loader)?;
-        }
+            None
+        };
 
-        // 2. Audio Processing
-        let mut audio_path_opt = None;
-        if let Some(audio_config) = &script.audio {
-            println!("\n🎵 Processing audio...");
-            let mut mixer = crate::AudioMixer::new(44100, 2);
-
-            for track in &audio_config.tracks {
-                println!("  Loading track: {}", track.source.display());
-                // Resolve path relative to script (using loader's base path would be better, but script paths are relative to script file)
-                // We need the base path here. Loader has it.
-                let base_path = loader.base_path();
-                let track_path = if track.source.is_absolute() {
-                    track.source.clone()
-                } else {
-                    base_path.join(&track.source)
-                };
-
-                match crate::AudioDecoder::decode(&track_path) {
-                    Ok((samples, rate, channels)) => {
-                        mixer.add_track(samples, rate, channels, track.start_time, track.volume);
-                    }
-                    Err(e) => println!("  ⚠️  Failed to load audio track: {}", e),
-                }
-            }
+        // 2. Rendering & Encoding
+        observer.stage_started(Stage::Render);
+        let render_started = Instant::now();
 
-            let mixed_audio = mixer.mix(script.metadata.duration);
-            let output_audio = output_dir.join("audio.wav");
-            if let Err(e) = mixer.export(&output_audio, &mixed_audio) {
-                println!("  ⚠️  Failed to export mixed audio: {}", e);
-            } else {
-                println!("  ✓ Mixed audio exported to: {}", output_audio.display());
-                audio_path_opt = Some(output_audio);
+        let transparent = script.metadata.transparent;
+        let output_video = VideoEncoder::output_path(&options.encoder, transparent);
+        if let Some(parent) = output_video.parent().filter(|p| !p.as_os_str().is_empty()) {
+            if encode && !parent.exists() {
+                create_dir(parent)?;
             }
         }
+        let (width, height) = script.metadata.resolution.dimensions();
+        let encode_options = EncodeOptions {
+            fps: script.metadata.fps,
+            width,
+            height,
+            transparent,
+            audio_path: audio_path_opt.as_deref(),
+            audio_offset: timeline.frame_to_time(frames.start),
+        };
 
-        // 3. Video Encoding
-        if crate::renderer::VideoEncoder::is_available() {
-            let output_video = Path::new("output.mp4");
-            let frame_pattern = if use_blender {
-                output_dir.join("frame_%04d.png")
-            } else {
-                output_dir.join("frame_%d.ppm")
-            };
+        let mut renderer = renderers.create(
+            &options.renderer,
+            script.clone(),
+            &options.renderer_options,
+            loader,
+        )?;
+        observer.info(format!("Using {} renderer", renderer.name()));
+        let capabilities = renderer.capabilities();
+        for feature in capabilities.unsupported(script) {
+            observer.warning(format!("Not supported by this renderer: {}", feature));
+        }
+
+        if encode && (keep_frames || !capabilities.streaming) {
+            // Frames on disk are checkpointed, so an interrupted render resumes
+            renderer.render_range(frames.clone(), output_dir, loader)?;
+            observer.stage_finished(Stage::Render, render_started);
 
-            crate::renderer::VideoEncoder::encode(
-                frame_pattern.to_str().unwrap(),
-                output_video,
-                script.metadata.fps,
-                script.metadata.resolution.dimensions().0,
-                script.metadata.resolution.dimensions().1,
-                audio_path_opt.as_deref(),
+            observer.stage_started(Stage::Encode);
+            let encode_started = Instant::now();
+            observer.info(format!("Encoding video to {}", output_video.display()));
+            let frame_pattern = output_dir.join(renderer.frame_format().pattern());
+            VideoEncoder::encode(
+                &frame_pattern,
+                frames.start,
+                &options.encoder,
+                &encode_options,
             )?;
+            observer.stage_finished(Stage::Encode, encode_started);
 
-            println!("✨ Video created successfully: {}", output_video.display());
+            observer.info("Video created successfully");
+            observer.output_written(&output_video);
+            if keep_frames {
+                observer.info(format!("Frames are saved in: {}", output_dir.display()));
+            }
+        } else if encode {
+            // Frames are piped straight into FFmpeg without touching the disk,
+            // so rendering and encoding run as one stage
+            observer.stage_started(Stage::Encode);
+            observer.info(format!("Streaming frames to {}", output_video.display()));
+            let mut stream = VideoEncoder::start_stream(&options.encoder, &encode_options)?;
+            renderer.stream_range(frames.clone(), loader, &mut |_, frame_buffer| {
+                Ok(stream.write_frame(frame_buffer)?)
+            })?;
+            stream.finish()?;
+            observer.stage_finished(Stage::Render, render_started);
+            observer.stage_finished(Stage::Encode, render_started);
+
+            observer.info("Video created successfully");
+            observer.output_written(&output_video);
         } else {
-            println!("⚠️  FFmpeg not found. Skipping video encoding.");
-            println!("   Frames are saved in: {}", output_dir.display());
-            println!("\n💡 To enable video generation, install FFmpeg:");
-            if cfg!(target_os = "macos") {
-                println!("   brew install ffmpeg");
+            renderer.render_range(frames.clone(), output_dir, loader)?;
+            observer.stage_finished(Stage::Render, render_started);
+        }
+
+        if single_frame {
+            observer.info(format!("Frame {} rendered", frames.start));
+            observer
+                .output_written(output_dir.join(renderer.frame_format().file_name(frames.start)));
+            return Ok(());
+        }
+
+        if !encoder_available {
+            observer.warning("FFmpeg not found. Skipping video encoding.");
+            observer.info(format!("Frames are saved in: {}", output_dir.display()));
+            let install = if cfg!(target_os = "macos") {
+                "brew install ffmpeg"
             } else if cfg!(target_os = "windows") {
-                println!("   choco install ffmpeg");
+                "choco install ffmpeg"
             } else if cfg!(target_os = "linux") {
-                println!("   sudo apt-get install ffmpeg");
+                "sudo apt-get install ffmpeg"
             } else {
-                println!("   Install FFmpeg from https://ffmpeg.org/download.html");
-            }
+                "Install FFmpeg from https://ffmpeg.org/download.html"
+            };
+            observer.info(format!(
+                "To enable video generation, install FFmpeg: {}",
+                install
+            ));
         }
 
         Ok(())
     }
+
+    /// Render the single frame selected by `options.frames` to a PNG file at
+    /// `output`, returning its frame number
+    pub fn render_still(
+        script: &VideoScript,
+        loader: &mut AssetLoader,
+        options: &RenderOptions,
+        renderers: &RendererRegistry,
+        output: &Path,
+    ) -> Result<u32> {
+        let timeline = Timeline::from_script(script);
+        let selection = options.frames.unwrap_or(FrameSelection::At(0.0));
+        let frame = timeline.frame_range(&selection)?.start;
+
+        if let Some(parent) = output.parent().filter(|p| !p.as_os_str().is_empty()) {
+            create_dir(parent)?;
+        }
+
+        let observer = options.renderer_options.observer.as_ref();
+        observer.stage_started(Stage::Render);
+        let started = Instant::now();
+        let mut renderer = renderers.create(
+            &options.renderer,
+            script.clone(),
+            &options.renderer_options,
+            loader,
+        )?;
+        if renderer.capabilities().streaming {
+            renderer.stream_range(frame..frame + 1, loader, &mut |_, frame_buffer| {
+                frame_buffer.save_png(output).map_err(|source| {
+                    RenderError::Output {
+                        path: output.to_path_buf(),
+                        source,
+                    }
+                    .into()
+                })
+            })?;
+        } else {
+            renderer.render_range(frame..frame + 1, &options.output_dir, loader)?;
+            let frame_path = options
+                .output_dir
+                .join(renderer.frame_format().file_name(frame));
+            std::fs::copy(&frame_path, output).map_err(|e| RenderError::Output {
+                path: output.to_path_buf(),
+                source: anyhow::Error::new(e)
+                    .context(format!("Renderer did not write {}", frame_path.display())),
+            })?;
+        }
+        observer.stage_finished(Stage::Render, started);
+
+        Ok(frame)
+    }
+
+    /// Mix the script's audio tracks and video clip audio into a WAV file in `output_dir`
+    fn mix_audio(
+        script: &VideoScript,
+        loader: &AssetLoader,
+        output_dir: &Path,
+        observer: &dyn Observer,
+    ) -> Option<PathBuf> {
+        // Video layers whose audio is routed into the mix, with the timeline
+        // time at which their scene starts
+        let timeline = Timeline::from_script(script);
+        let video_clips: Vec<_> = script
+            .scenes
+            .iter()
+            .zip(timeline.scene_ranges())
+            .flat_map(|(scene, (start_frame, _))| {
+                let start_time = timeline.frame_to_time(start_frame);
+                scene.layers.iter().filter_map(move |layer| match layer {
+                    Layer::Video {
+                        source, playback, ..
+                    } if playback.audio => Some((start_time, scene.duration, source, playback)),
+                    _ => None,
+                })
+            })
+            .collect();
+
+        if script.audio.is_none() && video_clips.is_empty() {
+            return None;
+        }
+
+        observer.stage_started(Stage::Audio);
+        let started = Instant::now();
+        let mut mixer = crate::AudioMixer::new(44100, 2);
+
+        let tracks = script.audio.iter().flat_map(|audio| &audio.tracks);
+        for track in tracks {
+            observer.info(format!("Loading track: {}", track.source.display()));
+            // Resolve path relative to script (using loader's base path would be better, but script paths are relative to script file)
+            // We need the base path here. Loader has it.
+            let base_path = loader.base_path();
+            let track_path = if track.source.is_absolute() {
+                track.source.clone()
+            } else {
+                base_path.join(&track.source)
+            };
+
+            match crate::AudioDecoder::decode(&track_path) {
+                Ok((samples, rate, channels)) => {
+                    mixer.add_track(samples, rate, channels, track.start_time, track.volume);
+                }
+                Err(e) => observer.warning(format!("Failed to load audio track: {}", e)),
+            }
+        }
+
+        for (start_time, duration, source, playback) in video_clips {
+            observer.info(format!("Loading video audio: {}", source.display()));
+            match crate::AudioDecoder::decode(&loader.resolve_path(source)) {
+                Ok((samples, rate, channels)) => {
+                    let clip = crate::VideoDecoder::clip_audio(
+                        &samples, rate, channels, playback, duration,
+                    );
+                    mixer.add_track(clip, rate, channels, start_time, playback.volume);
+                }
+                Err(e) => observer.warning(format!("Failed to load video audio: {}", e)),
+            }
+        }
+
+        let mixed_audio = mixer.mix(script.metadata.duration);
+        let output_audio = output_dir.join("audio.wav");
+        if let Err(e) = mixer.export(&output_audio, &mixed_audio) {
+            observer.warning(format!("Failed to export mixed audio: {}", e));
+            return None;
+        }
+
+        observer.info(format!(
+            "Mixed audio exported to: {}",
+            output_audio.display()
+        ));
+        observer.stage_finished(Stage::Audio, started);
+        Some(output_audio)
+    }
+}
+
+fn create_dir(path: &Path) -> Result<(), RenderError> {
+    std::fs::create_dir_all(path).map_err(|e| RenderError::Output {
+        path: path.to_path_buf(),
+        source: e.into(),
+    })
 }
@@ -0,0 +1,434 @@
+use crate::error::location;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+use std::path::Path;
+
+/// How serious an issue found in a script is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// The script can't be rendered
+    Error,
+    /// The script renders, but probably not as intended
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        })
+    }
+}
+
+/// An issue found in a script, located by the JSON pointer of the offending
+/// value (e.g. `/scenes/1/layers/0/trim_out`)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Stable identifier of the kind of issue, e.g. `empty-scene`
+    pub code: &'static str,
+    pub message: String,
+    pub pointer: String,
+    /// Id of the offending scene
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scene: Option<String>,
+    /// Index of the offending layer within the scene
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layer: Option<usize>,
+    /// 1-based line of the value in the script file, once located
+    pub line: Option<usize>,
+    /// 1-based column (in characters) of the value, once located
+    pub column: Option<usize>,
+}
+
+impl Diagnostic {
+    pub fn error(
+        code: &'static str,
+        pointer: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self::new(Severity::Error, code, pointer.into(), message.into())
+    }
+
+    pub fn warning(
+        code: &'static str,
+        pointer: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self::new(Severity::Warning, code, pointer.into(), message.into())
+    }
+
+    fn new(severity: Severity, code: &'static str, pointer: String, message: String) -> Self {
+        Self {
+            severity,
+            code,
+            message,
+            pointer,
+            scene: None,
+            layer: None,
+            line: None,
+            column: None,
+        }
+    }
+
+    /// Attribute the issue to a scene, or to one of its layers
+    pub fn in_scene(mut self, scene: &str, layer: Option<usize>) -> Self {
+        self.scene = Some(scene.to_string());
+        self.layer = layer;
+        self
+    }
+
+    /// Fill in the line and column from the script's source
+    pub fn locate(&mut self, source_map: &SourceMap) {
+        if let Some((line, column)) = source_map.position(&self.pointer) {
+            self.line = Some(line);
+            self.column = Some(column);
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// Compiler-style report with the offending line of `source`:
+    ///
+    /// ```text
+    /// error[empty-scene]: Scene 'intro': must have at least one layer
+    ///   --> script.json:12:23
+    ///    |
+    /// 12 |       "layers": [],
+    ///    |                 ^
+    ///    = at /scenes/0/layers
+    /// ```
+    pub fn render(&self, path: &Path, source: &str) -> String {
+        let mut out = format!("{}[{}]: {}\n", self.severity, self.code, self);
+        let (Some(line), Some(column)) = (self.line, self.column) else {
+            out.push_str(&format!(" --> {}\n", path.display()));
+            if !self.pointer.is_empty() {
+                out.push_str(&format!("  = at {}\n", self.pointer));
+            }
+            return out;
+        };
+
+        let gutter = " ".repeat(line.to_string().len());
+        let text = source.lines().nth(line - 1).unwrap_or_default();
+        // Tabs keep their width so the caret lines up
+        let padding: String = text
+            .chars()
+            .take(column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+
+        out.push_str(&format!(
+            "{}--> {}:{}:{}\n",
+            gutter,
+            path.display(),
+            line,
+            column
+        ));
+        out.push_str(&format!("{} |\n", gutter));
+        out.push_str(&format!("{} | {}\n", line, text));
+        out.push_str(&format!("{} | {}^\n", gutter, padding));
+        if !self.pointer.is_empty() {
+            out.push_str(&format!("{} = at {}\n", gutter, self.pointer));
+        }
+        out
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", location(&self.scene, &self.layer), self.message)
+    }
+}
+
+/// Where the values of a script file are, by JSON pointer
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    /// 1-based line and column (in characters)
+    positions: HashMap<String, (usize, usize)>,
+    /// Byte ranges, for the values whose end is known
+    spans: HashMap<String, Span>,
+}
+
+/// Where a value is in a script file, in bytes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    /// Start of the key, if the value is an object member
+    pub key: Option<usize>,
+    pub value: Range<usize>,
+}
+
+impl SourceMap {
+    /// Map the values of JSON `source`. Malformed JSON maps as far as it can be read.
+    pub fn json(source: &str) -> Self {
+        let mut scanner = Scanner {
+            bytes: source.as_bytes(),
+            pos: 0,
+            spans: HashMap::new(),
+        };
+        scanner.value(String::new(), None, 0);
+        Self {
+            positions: scanner
+                .spans
+                .iter()
+                .map(|(pointer, span)| (pointer.clone(), line_column(source, span.value.start)))
+                .collect(),
+            spans: scanner
+                .spans
+                .into_iter()
+                .filter(|(_, span)| !span.value.is_empty())
+                .collect(),
+        }
+    }
+
+    /// Record that the value at `pointer` starts at `line` and `column` (1-based)
+    pub fn insert(&mut self, pointer: String, line: usize, column: usize) {
+        self.positions.insert(pointer, (line, column));
+    }
+
+    /// Record the byte range of the value at `pointer`
+    pub fn insert_span(&mut self, pointer: String, span: Span) {
+        self.spans.insert(pointer, span);
+    }
+
+    /// Byte range of the value at `pointer`, if the whole value was mapped
+    pub fn span(&self, pointer: &str) -> Option<&Span> {
+        self.spans.get(pointer)
+    }
+
+    /// Line and column of the value at `pointer`, or of its closest mapped
+    /// parent (values filled in by defaults aren't in the file)
+    pub fn position(&self, pointer: &str) -> Option<(usize, usize)> {
+        let mut pointer = pointer;
+        loop {
+            if let Some(position) = self.positions.get(pointer) {
+                return Some(*position);
+            }
+            pointer = &pointer[..pointer.rfind('/')?];
+        }
+    }
+}
+
+/// 1-based line and column (in characters) of a byte offset in `source`
+pub(crate) fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let mut offset = offset.min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let before = &source[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+/// Segment of a JSON pointer for an object key
+pub(crate) fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Nesting deeper than this isn't mapped (scripts are a few levels deep)
+const MAX_DEPTH: usize = 64;
+
+struct Scanner<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    /// Values read so far; a value that isn't finished has an empty range
+    spans: HashMap<String, Span>,
+}
+
+impl Scanner<'_> {
+    fn value(&mut self, pointer: String, key: Option<usize>, depth: usize) {
+        self.skip_whitespace();
+        if depth > MAX_DEPTH {
+            return;
+        }
+        let start = self.pos;
+        self.spans.insert(
+            pointer.clone(),
+            Span {
+                key,
+                value: start..start,
+            },
+        );
+        let finished = match self.peek() {
+            Some(b'{') => self.object(&pointer, depth),
+            Some(b'[') => self.array(&pointer, depth),
+            Some(b'"') => {
+                self.string();
+                true
+            }
+            _ => {
+                while let Some(byte) = self.peek() {
+                    if matches!(byte, b',' | b'}' | b']') || byte.is_ascii_whitespace() {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+                true
+            }
+        };
+        if finished {
+            if let Some(span) = self.spans.get_mut(&pointer) {
+                span.value.end = self.pos.min(self.bytes.len());
+            }
+        }
+    }
+
+    /// Read an object from its `{`, true if it's closed
+    fn object(&mut self, pointer: &str, depth: usize) -> bool {
+        self.pos += 1;
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b'"') => {}
+                Some(b'}') => {
+                    self.pos += 1;
+                    return true;
+                }
+                _ => return false,
+            }
+            let key_start = self.pos;
+            let key = self.string();
+            self.skip_whitespace();
+            if self.peek() != Some(b':') {
+                return false;
+            }
+            self.pos += 1;
+            self.value(
+                format!("{}/{}", pointer, escape_pointer(&key)),
+                Some(key_start),
+                depth + 1,
+            );
+            if let Some(closed) = self.separator(b'}') {
+                return closed;
+            }
+        }
+    }
+
+    /// Read an array from its `[`, true if it's closed
+    fn array(&mut self, pointer: &str, depth: usize) -> bool {
+        self.pos += 1;
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return true;
+        }
+        for index in 0.. {
+            self.value(format!("{}/{}", pointer, index), None, depth + 1);
+            if let Some(closed) = self.separator(b']') {
+                return closed;
+            }
+        }
+        false
+    }
+
+    /// Skip a `,` (`None`, another item follows) or the closing bracket
+    /// (`Some(true)`); `Some(false)` if there's neither
+    fn separator(&mut self, close: u8) -> Option<bool> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b',') => {
+                self.pos += 1;
+                None
+            }
+            Some(byte) if byte == close => {
+                self.pos += 1;
+                Some(true)
+            }
+            _ => Some(false),
+        }
+    }
+
+    /// Read a string literal starting at its opening quote
+    fn string(&mut self) -> String {
+        let start = self.pos;
+        self.pos += 1;
+        while let Some(byte) = self.peek() {
+            self.pos += 1;
+            match byte {
+                b'\\' => self.pos += 1,
+                b'"' => break,
+                _ => {}
+            }
+        }
+        let literal = &self.bytes[start..self.pos.min(self.bytes.len())];
+        serde_json::from_slice(literal).unwrap_or_default()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|byte| byte.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"{
+  "title": "Demo",
+  "scenes": [
+    {"id": "intro", "layers": []},
+    {"id": "a/b", "layers": [{"type": "image"}]}
+  ]
+}"#;
+
+    #[test]
+    fn test_source_map() {
+        let map = SourceMap::json(SOURCE);
+        let position = |pointer| map.position(pointer).unwrap();
+        assert_eq!(position(""), (1, 1));
+        assert_eq!(position("/title"), (2, 12));
+        assert_eq!(position("/scenes/0/layers"), (4, 31));
+        assert_eq!(position("/scenes/1/layers/0/type"), (5, 39));
+        // Values missing from the file fall back to their parent
+        assert_eq!(position("/scenes/1/duration"), (5, 5));
+        assert_eq!(position("/scenes/1/id"), (5, 12));
+
+        let span = map.span("/scenes/0/id").unwrap();
+        assert_eq!(
+            &SOURCE[span.key.unwrap()..span.value.end],
+            r#""id": "intro""#
+        );
+        assert_eq!(
+            &SOURCE[map.span("/scenes/1").unwrap().value.clone()],
+            r#"{"id": "a/b", "layers": [{"type": "image"}]}"#
+        );
+        assert!(SourceMap::json(r#"{"a": [1, 2"#).span("/a").is_none());
+    }
+
+    #[test]
+    fn test_render() {
+        let mut diagnostic = Diagnostic::error(
+            "empty-scene",
+            "/scenes/0/layers",
+            "must have at least one layer",
+        )
+        .in_scene("intro", None);
+        diagnostic.locate(&SourceMap::json(SOURCE));
+        assert_eq!(
+            diagnostic.render(Path::new("demo.json"), SOURCE),
+            "error[empty-scene]: Scene 'intro': must have at least one layer\n \
+             --> demo.json:4:31\n  \
+             |\n\
+             4 |     {\"id\": \"intro\", \"layers\": []},\n  \
+             |                               ^\n  \
+             = at /scenes/0/layers\n"
+        );
+
+        let json = serde_json::to_value(&diagnostic).unwrap();
+        assert_eq!(json["severity"], "error");
+        assert_eq!(json["line"], 4);
+        assert!(json.get("layer").is_none());
+    }
+}
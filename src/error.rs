@@ -0,0 +1,352 @@
+use crate::diagnostics::Diagnostic;
+use crate::formats::ScriptFormat;
+use std::fmt;
+use std::ops::Range;
+use std::path::PathBuf;
+
+/// Result of the library's public API
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Any failure of the library, by the part that failed.
+///
+/// `is_user_error` and `is_retryable` tell whether the script (or command line)
+/// needs fixing, or whether running the same job again may succeed.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    #[error(transparent)]
+    Asset(#[from] AssetError),
+    #[error(transparent)]
+    Audio(#[from] AudioError),
+    #[error(transparent)]
+    Render(#[from] RenderError),
+    #[error(transparent)]
+    Encoder(#[from] EncoderError),
+    #[error(transparent)]
+    Blender(#[from] BlenderError),
+}
+
+impl Error {
+    /// Whether the failure comes from the script, its assets or the options
+    /// given, and its message is meant for the person who wrote them
+    pub fn is_user_error(&self) -> bool {
+        match self {
+            Error::Parse(_) => true,
+            Error::Asset(error) => !matches!(error, AssetError::Read { .. }),
+            Error::Audio(error) => !matches!(error, AudioError::Export { .. }),
+            Error::Render(error) => matches!(
+                error,
+                RenderError::UnknownRenderer { .. }
+                    | RenderError::FrameRange { .. }
+                    | RenderError::NoFrames
+                    | RenderError::StreamingUnsupported { .. }
+            ),
+            Error::Encoder(error) => matches!(error, EncoderError::InvalidSettings(_)),
+            Error::Blender(_) => false,
+        }
+    }
+
+    /// Whether the failure may be transient (I/O, a crashed external process),
+    /// so the same job can be run again
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::Parse(_) => false,
+            Error::Asset(error) => matches!(error, AssetError::Read { .. }),
+            Error::Audio(error) => matches!(error, AudioError::Export { .. }),
+            Error::Render(error) => matches!(error, RenderError::Output { .. }),
+            Error::Encoder(error) => matches!(
+                error,
+                EncoderError::Spawn { .. }
+                    | EncoderError::Write { .. }
+                    | EncoderError::Failed { .. }
+            ),
+            Error::Blender(error) => !matches!(error, BlenderError::NotInstalled),
+        }
+    }
+}
+
+/// Failure to read or validate a script
+#[derive(Debug, thiserror::Error)]
+pub enum ParseError {
+    #[error("Failed to read script file: {}", .path.display())]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    /// The file isn't valid in its format, or isn't shaped like a script
+    #[error("Failed to parse {format} script {}{}: {message}", .path.display(), position(.line, .column))]
+    Syntax {
+        path: PathBuf,
+        format: ScriptFormat,
+        line: Option<usize>,
+        column: Option<usize>,
+        message: String,
+    },
+    /// The script can't be written in a format (e.g. TOML has no `null`)
+    #[error("The script can't be written as {format}: {message}")]
+    Unrepresentable {
+        format: ScriptFormat,
+        message: String,
+    },
+    /// The script was written for a newer version of the format
+    #[error(
+        "Unsupported script version {version} (this build reads versions 1 to {})",
+        crate::script::SCRIPT_VERSION
+    )]
+    UnsupportedVersion { version: String },
+    /// The script is well-formed but describes something impossible
+    #[error("{}{message}", location(.scene, .layer))]
+    Invalid {
+        /// Id of the offending scene
+        scene: Option<String>,
+        /// Index of the offending layer within the scene
+        layer: Option<usize>,
+        message: String,
+    },
+}
+
+impl From<Diagnostic> for ParseError {
+    fn from(diagnostic: Diagnostic) -> Self {
+        ParseError::Invalid {
+            scene: diagnostic.scene,
+            layer: diagnostic.layer,
+            message: diagnostic.message,
+        }
+    }
+}
+
+/// ":12:5" suffix of a file name
+fn position(line: &Option<usize>, column: &Option<usize>) -> String {
+    match (line, column) {
+        (Some(line), Some(column)) => format!(":{}:{}", line, column),
+        _ => String::new(),
+    }
+}
+
+/// "Scene 'intro' layer 2: " prefix of a validation message
+pub(crate) fn location(scene: &Option<String>, layer: &Option<usize>) -> String {
+    match (scene, layer) {
+        (Some(scene), Some(layer)) => format!("Scene '{}' layer {}: ", scene, layer),
+        (Some(scene), None) => format!("Scene '{}': ", scene),
+        _ => String::new(),
+    }
+}
+
+/// Kind of asset a script references
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetKind {
+    Image,
+    Video,
+    Font,
+}
+
+impl fmt::Display for AssetKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AssetKind::Image => "image",
+            AssetKind::Video => "video",
+            AssetKind::Font => "font",
+        })
+    }
+}
+
+/// Failure to load an asset referenced by a script
+#[derive(Debug, thiserror::Error)]
+pub enum AssetError {
+    #[error("Missing {kind} file: {}", .path.display())]
+    NotFound { kind: AssetKind, path: PathBuf },
+    #[error("Failed to read {kind} file: {}", .path.display())]
+    Read {
+        kind: AssetKind,
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    /// The file exists but isn't a video ffprobe can read
+    #[error("Failed to probe video: {}", .path.display())]
+    Probe {
+        path: PathBuf,
+        #[source]
+        source: anyhow::Error,
+    },
+    /// The same file was loaded before as another kind of asset
+    #[error("{} is not a {expected}", .path.display())]
+    WrongKind { path: PathBuf, expected: AssetKind },
+}
+
+/// Failure to decode or write audio
+#[derive(Debug, thiserror::Error)]
+pub enum AudioError {
+    #[error("Failed to open audio file: {}", .path.display())]
+    Open {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Unsupported audio format: {}", .path.display())]
+    UnsupportedFormat {
+        path: PathBuf,
+        #[source]
+        source: symphonia::core::errors::Error,
+    },
+    #[error("No supported audio track found in {}", .path.display())]
+    NoTrack { path: PathBuf },
+    #[error("Unsupported audio codec in {}", .path.display())]
+    UnsupportedCodec {
+        path: PathBuf,
+        #[source]
+        source: symphonia::core::errors::Error,
+    },
+    #[error("Failed to decode audio in {}", .path.display())]
+    Decode {
+        path: PathBuf,
+        #[source]
+        source: symphonia::core::errors::Error,
+    },
+    #[error("Failed to export audio to {}", .path.display())]
+    Export {
+        path: PathBuf,
+        #[source]
+        source: hound::Error,
+    },
+}
+
+/// Failure of a rendering backend or of the render pipeline
+#[derive(Debug, thiserror::Error)]
+pub enum RenderError {
+    #[error("Unknown renderer '{name}' (available: {})", .available.join(", "))]
+    UnknownRenderer {
+        name: String,
+        available: Vec<String>,
+    },
+    #[error("Frames {}..{} are outside the timeline (0..{total})", .frames.start, .frames.end)]
+    FrameRange { frames: Range<u32>, total: u32 },
+    #[error("The script has no frames to put on a storyboard")]
+    NoFrames,
+    #[error("The {renderer} renderer can't stream frames")]
+    StreamingUnsupported { renderer: String },
+    #[error("Failed to render frame {frame}{}", scene_suffix(.scene))]
+    Frame {
+        frame: u32,
+        /// Scene playing at the frame
+        scene: Option<String>,
+        #[source]
+        source: anyhow::Error,
+    },
+    /// Failed to write a frame, the render manifest or the scene cache
+    #[error("Failed to write {}", .path.display())]
+    Output {
+        path: PathBuf,
+        #[source]
+        source: anyhow::Error,
+    },
+}
+
+fn scene_suffix(scene: &Option<String>) -> String {
+    scene
+        .as_ref()
+        .map(|scene| format!(" (scene '{}')", scene))
+        .unwrap_or_default()
+}
+
+/// Failure to encode a video with FFmpeg
+#[derive(Debug, thiserror::Error)]
+pub enum EncoderError {
+    #[error("FFmpeg not found. Please install ffmpeg to enable video encoding.")]
+    NotInstalled,
+    /// The encoder settings can't produce a valid video
+    #[error("{0}")]
+    InvalidSettings(String),
+    #[error("Failed to execute ffmpeg")]
+    Spawn(#[source] std::io::Error),
+    #[error("Frame size mismatch: expected {expected} bytes, got {actual}")]
+    FrameSize { expected: usize, actual: usize },
+    #[error("Encoder input already closed")]
+    InputClosed,
+    #[error("FFmpeg stopped accepting frames")]
+    Write(#[source] std::io::Error),
+    #[error("FFmpeg encoding failed{}", exit_code(.code))]
+    Failed {
+        /// Exit code of FFmpeg (`None` if it was killed)
+        code: Option<i32>,
+    },
+}
+
+/// Failure of a Blender render
+#[derive(Debug, thiserror::Error)]
+pub enum BlenderError {
+    #[error("Blender not found. Please install Blender to use the blender renderer.")]
+    NotInstalled,
+    #[error("Failed to spawn Blender process")]
+    Spawn(#[source] std::io::Error),
+    #[error("Blender job for frames {}..{} failed{}", .frames.start, .frames.end, exit_code(.code))]
+    JobFailed {
+        frames: Range<u32>,
+        code: Option<i32>,
+    },
+    #[error("{failed} Blender job(s) failed")]
+    JobsFailed { failed: usize },
+    /// Failed to write the Python script, a frame, the manifest or the scene cache
+    #[error("Failed to write {}", .path.display())]
+    Output {
+        path: PathBuf,
+        #[source]
+        source: anyhow::Error,
+    },
+}
+
+fn exit_code(code: &Option<i32>) -> String {
+    code.map(|code| format!(" (exit code {})", code))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages() {
+        let error = ParseError::Invalid {
+            scene: Some("intro".into()),
+            layer: Some(2),
+            message: "invalid video playback options".into(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Scene 'intro' layer 2: invalid video playback options"
+        );
+
+        let error = RenderError::Frame {
+            frame: 42,
+            scene: Some("outro".into()),
+            source: anyhow::anyhow!("Video unavailable"),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Failed to render frame 42 (scene 'outro')"
+        );
+        let source = std::error::Error::source(&error).unwrap();
+        assert_eq!(source.to_string(), "Video unavailable");
+    }
+
+    #[test]
+    fn test_classification() {
+        let missing: Error = AssetError::NotFound {
+            kind: AssetKind::Image,
+            path: "bg.png".into(),
+        }
+        .into();
+        assert!(missing.is_user_error());
+        assert!(!missing.is_retryable());
+
+        let crashed: Error = EncoderError::Failed { code: Some(1) }.into();
+        assert!(!crashed.is_user_error());
+        assert!(crashed.is_retryable());
+        assert_eq!(crashed.to_string(), "FFmpeg encoding failed (exit code 1)");
+
+        let not_installed: Error = BlenderError::NotInstalled.into();
+        assert!(!not_installed.is_user_error() && !not_installed.is_retryable());
+    }
+}
@@ -0,0 +1,310 @@
+use crate::diagnostics::Diagnostic;
+use serde::Serialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Part of the pipeline an event belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    Analysis,
+    Assets,
+    Audio,
+    Render,
+    Encode,
+}
+
+/// Severity of a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Level {
+    Info,
+    Warning,
+    Error,
+}
+
+/// Something that happened while analysing, rendering or encoding a script
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    StageStarted {
+        stage: Stage,
+    },
+    StageFinished {
+        stage: Stage,
+        seconds: f32,
+    },
+    /// A frame was rendered (or taken from a cache); `completed` counts the
+    /// frames done so far out of `total`
+    FrameRendered {
+        frame: u32,
+        completed: u32,
+        total: u32,
+        /// Estimated seconds until all frames are done
+        eta_seconds: Option<f32>,
+    },
+    Message {
+        level: Level,
+        message: String,
+    },
+    /// An issue found in the script at `path`
+    Diagnostic {
+        path: PathBuf,
+        #[serde(flatten)]
+        diagnostic: Diagnostic,
+        /// Compiler-style report quoting the offending line
+        #[serde(skip)]
+        rendered: String,
+    },
+    /// A file meant for the user was written (video, image, report, ...)
+    OutputWritten {
+        path: PathBuf,
+    },
+}
+
+/// Receives the events of a render. Called from worker threads, so
+/// implementations must be thread-safe.
+///
+/// Closures taking an `&Event` are observers, e.g. one forwarding events to a
+/// channel: `move |event: &Event| { let _ = sender.send(event.clone()); }`
+pub trait Observer: Send + Sync {
+    fn on_event(&self, event: &Event);
+}
+
+impl<F: Fn(&Event) + Send + Sync> Observer for F {
+    fn on_event(&self, event: &Event) {
+        self(event)
+    }
+}
+
+impl fmt::Debug for dyn Observer + '_ {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Observer")
+    }
+}
+
+/// Observer shared between the parts of a render
+pub type SharedObserver = Arc<dyn Observer>;
+
+impl dyn Observer + '_ {
+    pub fn info(&self, message: impl Into<String>) {
+        self.message(Level::Info, message);
+    }
+
+    pub fn warning(&self, message: impl Into<String>) {
+        self.message(Level::Warning, message);
+    }
+
+    pub fn error(&self, message: impl Into<String>) {
+        self.message(Level::Error, message);
+    }
+
+    pub fn message(&self, level: Level, message: impl Into<String>) {
+        self.on_event(&Event::Message {
+            level,
+            message: message.into(),
+        });
+    }
+
+    /// Report `diagnostic` of the script at `path`, whose text is `source`
+    pub fn diagnostic(&self, path: &Path, source: &str, diagnostic: &Diagnostic) {
+        self.on_event(&Event::Diagnostic {
+            path: path.to_path_buf(),
+            diagnostic: diagnostic.clone(),
+            rendered: diagnostic.render(path, source),
+        });
+    }
+
+    pub fn stage_started(&self, stage: Stage) {
+        self.on_event(&Event::StageStarted { stage });
+    }
+
+    pub fn stage_finished(&self, stage: Stage, started: Instant) {
+        self.on_event(&Event::StageFinished {
+            stage,
+            seconds: started.elapsed().as_secs_f32(),
+        });
+    }
+
+    pub fn output_written(&self, path: impl Into<PathBuf>) {
+        self.on_event(&Event::OutputWritten { path: path.into() });
+    }
+}
+
+/// Prints events for people reading a terminal
+#[derive(Debug, Default)]
+pub struct ConsoleObserver;
+
+/// Frames between two progress lines of the console observer
+const CONSOLE_PROGRESS_INTERVAL: u32 = 30;
+
+impl Observer for ConsoleObserver {
+    fn on_event(&self, event: &Event) {
+        match event {
+            Event::StageStarted { stage } => match stage {
+                Stage::Analysis => println!("\n📊 Analyzing script..."),
+                Stage::Assets => println!("\n🎨 Loading assets..."),
+                Stage::Audio => println!("\n🎵 Processing audio..."),
+                Stage::Render => println!("\n🎬 Rendering frames..."),
+                Stage::Encode => println!("🎥 Encoding video..."),
+            },
+            Event::StageFinished { stage, seconds } => {
+                if matches!(stage, Stage::Render | Stage::Encode) {
+                    println!("  ✓ Done in {:.2}s", seconds);
+                }
+            }
+            Event::FrameRendered {
+                completed,
+                total,
+                eta_seconds,
+                ..
+            } => {
+                if completed % CONSOLE_PROGRESS_INTERVAL == 0 || completed == total {
+                    match eta_seconds {
+                        Some(eta) if completed < total => println!(
+                            "  Rendering frame {}/{} (about {:.0}s left)",
+                            completed, total, eta
+                        ),
+                        _ => println!("  Rendering frame {}/{}", completed, total),
+                    }
+                }
+            }
+            Event::Message { level, message } => {
+                // Continuation lines line up under the first one
+                let message = message.replace('\n', "\n    ");
+                match level {
+                    Level::Info => println!("  {}", message),
+                    Level::Warning => println!("  ⚠️  {}", message),
+                    Level::Error => eprintln!("  ❌ {}", message),
+                }
+            }
+            Event::Diagnostic { rendered, .. } => eprintln!("{}", rendered),
+            Event::OutputWritten { path } => println!("✨ Output saved to: {}", path.display()),
+        }
+    }
+}
+
+/// Prints each event as a line of JSON (newline-delimited JSON)
+#[derive(Debug, Default)]
+pub struct JsonObserver;
+
+impl Observer for JsonObserver {
+    fn on_event(&self, event: &Event) {
+        if let Ok(json) = serde_json::to_string(event) {
+            println!("{}", json);
+        }
+    }
+}
+
+/// Counts completed frames and estimates the time left
+#[derive(Debug, Clone)]
+pub struct FrameProgress {
+    started: Instant,
+    completed: u32,
+    total: u32,
+}
+
+impl FrameProgress {
+    pub fn new(total: u32) -> Self {
+        Self {
+            started: Instant::now(),
+            completed: 0,
+            total,
+        }
+    }
+
+    /// Record `frame` as done and build its event
+    pub fn frame_done(&mut self, frame: u32) -> Event {
+        self.completed += 1;
+        let elapsed = self.started.elapsed().as_secs_f32();
+        let remaining = self.total.saturating_sub(self.completed);
+        let eta_seconds = (self.completed > 0 && remaining > 0)
+            .then(|| elapsed / self.completed as f32 * remaining as f32);
+
+        Event::FrameRendered {
+            frame,
+            completed: self.completed,
+            total: self.total,
+            eta_seconds,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_event_json() {
+        let event = Event::FrameRendered {
+            frame: 12,
+            completed: 3,
+            total: 10,
+            eta_seconds: None,
+        };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"event":"frame_rendered","frame":12,"completed":3,"total":10,"eta_seconds":null}"#
+        );
+
+        let event = Event::Message {
+            level: Level::Warning,
+            message: "Font missing".into(),
+        };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"event":"message","level":"warning","message":"Font missing"}"#
+        );
+    }
+
+    #[test]
+    fn test_closure_observer() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let collected = events.clone();
+        let observer: SharedObserver =
+            Arc::new(move |event: &Event| collected.lock().unwrap().push(event.clone()));
+
+        observer.stage_started(Stage::Audio);
+        observer.warning("No audio tracks");
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                Event::StageStarted {
+                    stage: Stage::Audio
+                },
+                Event::Message {
+                    level: Level::Warning,
+                    message: "No audio tracks".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_frame_progress() {
+        let mut progress = FrameProgress::new(2);
+        let Event::FrameRendered {
+            completed,
+            eta_seconds,
+            ..
+        } = progress.frame_done(5)
+        else {
+            panic!("expected a frame event");
+        };
+        assert_eq!(completed, 1);
+        assert!(eta_seconds.is_some());
+
+        // Nothing is left after the last frame
+        assert!(matches!(
+            progress.frame_done(6),
+            Event::FrameRendered {
+                completed: 2,
+                eta_seconds: None,
+                ..
+            }
+        ));
+    }
+}
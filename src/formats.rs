@@ -0,0 +1,792 @@
+use crate::diagnostics::{escape_pointer, line_column, Diagnostic, SourceMap, Span};
+use crate::error::ParseError;
+use serde_json::{Map, Number, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use yaml_rust2::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust2::scanner::{Marker, TScalarStyle};
+use yaml_rust2::{Yaml, YamlEmitter};
+
+/// Text format of a script file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptFormat {
+    Json,
+    Toml,
+    /// YAML 1.2 (allows comments); only the first document of a file is read
+    Yaml,
+}
+
+impl fmt::Display for ScriptFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ScriptFormat::Json => "JSON",
+            ScriptFormat::Toml => "TOML",
+            ScriptFormat::Yaml => "YAML",
+        })
+    }
+}
+
+impl std::str::FromStr for ScriptFormat {
+    type Err = String;
+
+    /// Parse a format by name (`json`, `toml` or `yaml`)
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "json" => Ok(ScriptFormat::Json),
+            "toml" => Ok(ScriptFormat::Toml),
+            "yaml" | "yml" => Ok(ScriptFormat::Yaml),
+            _ => Err(format!(
+                "unknown format `{}`, expected one of `json`, `toml`, `yaml`",
+                name
+            )),
+        }
+    }
+}
+
+impl ScriptFormat {
+    /// Format of a file by its extension (`.json`, `.toml`, `.yaml` or `.yml`)
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "json" => Some(ScriptFormat::Json),
+            "toml" => Some(ScriptFormat::Toml),
+            "yaml" | "yml" => Some(ScriptFormat::Yaml),
+            _ => None,
+        }
+    }
+
+    /// Format of a file by its extension, JSON if it has no known one
+    pub fn detect(path: &Path) -> Self {
+        Self::from_path(path).unwrap_or(ScriptFormat::Json)
+    }
+
+    /// Parse `source` into a JSON value, with where each value is in `source`
+    pub fn read(self, source: &str) -> Result<(Value, SourceMap), SyntaxError> {
+        match self {
+            ScriptFormat::Json => {
+                let value = serde_json::from_str(source).map_err(|error| {
+                    syntax_error(
+                        strip_position(&error.to_string()),
+                        error.line(),
+                        error.column(),
+                    )
+                })?;
+                Ok((value, SourceMap::json(source)))
+            }
+            ScriptFormat::Toml => read_toml(source),
+            ScriptFormat::Yaml => read_yaml(source),
+        }
+    }
+
+    /// Write a document read by `read` in this format. Keys keep their order;
+    /// TOML leaves out `null` values, which it has no way to write.
+    pub fn write(self, value: &Value) -> Result<String, ParseError> {
+        match self {
+            ScriptFormat::Json => {
+                let mut text = serde_json::to_string_pretty(value).expect("JSON values serialize");
+                text.push('\n');
+                Ok(text)
+            }
+            ScriptFormat::Toml => toml::to_string_pretty(&without_nulls(value)).map_err(|error| {
+                ParseError::Unrepresentable {
+                    format: self,
+                    message: error.to_string(),
+                }
+            }),
+            ScriptFormat::Yaml => {
+                let mut text = String::new();
+                let mut emitter = YamlEmitter::new(&mut text);
+                emitter.multiline_strings(true);
+                emitter
+                    .dump(&to_yaml(value))
+                    .map_err(|error| ParseError::Unrepresentable {
+                        format: self,
+                        message: format!("{:?}", error),
+                    })?;
+                // The emitter starts the document with `---`, which a single document doesn't need
+                let mut text = text.strip_prefix("---\n").unwrap_or(&text).to_string();
+                text.push('\n');
+                Ok(text)
+            }
+        }
+    }
+
+    /// Edit `source` in place so it reads as `document`, changing only the
+    /// values that differ: comments and formatting elsewhere are kept. `None`
+    /// if a change can't be made that way (then `write` the whole document).
+    pub fn rewrite(self, source: &str, document: &Value) -> Option<String> {
+        crate::rewrite::rewrite(self, source, document)
+    }
+
+    /// Whether `source` may have comments, which `write` would drop. A `#`
+    /// in a string can count as one.
+    pub fn has_comments(self, source: &str) -> bool {
+        self != ScriptFormat::Json
+            && source.lines().any(|line| {
+                line.trim_start().starts_with('#') || line.contains(" #") || line.contains("\t#")
+            })
+    }
+}
+
+/// Text that isn't valid in its format
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxError {
+    pub message: String,
+    /// 1-based line and column, if known
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+impl From<SyntaxError> for Diagnostic {
+    fn from(error: SyntaxError) -> Self {
+        let mut diagnostic = Diagnostic::error("invalid-syntax", "", error.message);
+        diagnostic.line = error.line;
+        diagnostic.column = error.column;
+        diagnostic
+    }
+}
+
+fn syntax_error(message: String, line: usize, column: usize) -> SyntaxError {
+    SyntaxError {
+        message,
+        line: (line > 0).then_some(line),
+        column: (line > 0).then_some(column.max(1)),
+    }
+}
+
+/// Drop the " at line L column C" serde_json appends; diagnostics carry it separately
+pub(crate) fn strip_position(message: &str) -> String {
+    match message.rfind(" at line ") {
+        Some(end) => message[..end].to_string(),
+        None => message.to_string(),
+    }
+}
+
+fn read_toml(source: &str) -> Result<(Value, SourceMap), SyntaxError> {
+    let document = toml_edit::ImDocument::parse(source).map_err(|error| {
+        let (line, column) = error
+            .span()
+            .map_or((0, 0), |span| line_column(source, span.start));
+        syntax_error(error.message().to_string(), line, column)
+    })?;
+
+    let mut source_map = SourceMap::default();
+    source_map.insert(String::new(), 1, 1);
+    let mut reader = TomlReader {
+        source,
+        source_map: &mut source_map,
+    };
+    let value = reader.table(document.as_table(), "");
+    Ok((value, source_map))
+}
+
+struct TomlReader<'a> {
+    source: &'a str,
+    source_map: &'a mut SourceMap,
+}
+
+impl TomlReader<'_> {
+    fn locate(&mut self, pointer: &str, span: Option<std::ops::Range<usize>>) {
+        if let Some(span) = span {
+            let (line, column) = line_column(self.source, span.start);
+            self.source_map.insert(pointer.to_string(), line, column);
+        }
+    }
+
+    fn table(&mut self, table: &toml_edit::Table, pointer: &str) -> Value {
+        let mut map = Map::new();
+        for (key, item) in table.iter() {
+            let pointer = format!("{}/{}", pointer, escape_pointer(key));
+            let value = match item {
+                toml_edit::Item::None => continue,
+                toml_edit::Item::Value(value) => self.value(value, &pointer),
+                toml_edit::Item::Table(table) => {
+                    self.locate(&pointer, table.span());
+                    self.table(table, &pointer)
+                }
+                toml_edit::Item::ArrayOfTables(tables) => {
+                    self.locate(&pointer, tables.span());
+                    let tables = tables
+                        .iter()
+                        .enumerate()
+                        .map(|(index, table)| {
+                            let pointer = format!("{}/{}", pointer, index);
+                            self.locate(&pointer, table.span());
+                            self.table(table, &pointer)
+                        })
+                        .collect();
+                    Value::Array(tables)
+                }
+            };
+            map.insert(key.to_string(), value);
+        }
+        Value::Object(map)
+    }
+
+    fn value(&mut self, value: &toml_edit::Value, pointer: &str) -> Value {
+        self.locate(pointer, value.span());
+        match value {
+            toml_edit::Value::String(s) => Value::String(s.value().clone()),
+            toml_edit::Value::Integer(i) => Value::from(*i.value()),
+            toml_edit::Value::Float(f) => {
+                Number::from_f64(*f.value()).map_or(Value::Null, Value::Number)
+            }
+            toml_edit::Value::Boolean(b) => Value::Bool(*b.value()),
+            toml_edit::Value::Datetime(d) => Value::String(d.value().to_string()),
+            toml_edit::Value::Array(array) => Value::Array(
+                array
+                    .iter()
+                    .enumerate()
+                    .map(|(index, value)| self.value(value, &format!("{}/{}", pointer, index)))
+                    .collect(),
+            ),
+            toml_edit::Value::InlineTable(table) => {
+                let mut map = Map::new();
+                for (key, value) in table.iter() {
+                    let pointer = format!("{}/{}", pointer, escape_pointer(key));
+                    map.insert(key.to_string(), self.value(value, &pointer));
+                }
+                Value::Object(map)
+            }
+        }
+    }
+}
+
+fn read_yaml(source: &str) -> Result<(Value, SourceMap), SyntaxError> {
+    let mut builder = YamlBuilder {
+        source,
+        offsets: source
+            .char_indices()
+            .map(|(offset, _)| offset)
+            .chain([source.len()])
+            .collect(),
+        ..Default::default()
+    };
+    Parser::new_from_str(source)
+        .load(&mut builder, false)
+        .map_err(|error| {
+            let marker = error.marker();
+            syntax_error(error.info().to_string(), marker.line(), marker.col() + 1)
+        })?;
+    if let Some(error) = builder.error {
+        return Err(error);
+    }
+    let value = builder.root.unwrap_or(Value::Null);
+    Ok((value, builder.source_map))
+}
+
+/// Builds a JSON value from YAML parser events, noting where each value is
+#[derive(Default)]
+struct YamlBuilder<'a> {
+    source: &'a str,
+    /// Byte offset of each character, as parser marks count characters
+    offsets: Vec<usize>,
+    /// Sequences and mappings being read, innermost last
+    stack: Vec<YamlNode>,
+    /// Values by anchor id, for aliases
+    anchors: HashMap<usize, Value>,
+    root: Option<Value>,
+    source_map: SourceMap,
+    /// Set once the first document is read, so later ones are ignored
+    done: bool,
+    error: Option<SyntaxError>,
+}
+
+struct YamlNode {
+    pointer: String,
+    anchor: usize,
+    kind: YamlNodeKind,
+    /// Byte offsets of where the node starts, of its own key, of the key of
+    /// the member read next, and of where its last item ends (if known)
+    start: usize,
+    key: Option<usize>,
+    next_key: Option<usize>,
+    end: Option<usize>,
+}
+
+enum YamlNodeKind {
+    Sequence(Vec<Value>),
+    /// Entries so far, and the key whose value comes next
+    Mapping(Map<String, Value>, Option<String>),
+}
+
+impl YamlBuilder<'_> {
+    /// Pointer of the next value, or `None` if the value is a mapping key
+    fn next_pointer(&self) -> Option<String> {
+        match self.stack.last() {
+            None => Some(String::new()),
+            Some(node) => match &node.kind {
+                YamlNodeKind::Sequence(items) => Some(format!("{}/{}", node.pointer, items.len())),
+                YamlNodeKind::Mapping(_, Some(key)) => {
+                    Some(format!("{}/{}", node.pointer, escape_pointer(key)))
+                }
+                YamlNodeKind::Mapping(_, None) => None,
+            },
+        }
+    }
+
+    /// Add a finished value to the node being read
+    fn push(&mut self, value: Value, anchor: usize) {
+        if anchor > 0 {
+            self.anchors.insert(anchor, value.clone());
+        }
+        match self.stack.last_mut() {
+            None => {
+                self.root = Some(value);
+                self.done = true;
+            }
+            Some(node) => match &mut node.kind {
+                YamlNodeKind::Sequence(items) => items.push(value),
+                YamlNodeKind::Mapping(map, key) => match key.take() {
+                    Some(key) => {
+                        map.insert(key, value);
+                    }
+                    None => match value {
+                        Value::String(s) => *key = Some(s),
+                        Value::Number(_) | Value::Bool(_) => *key = Some(value.to_string()),
+                        _ => {
+                            let pointer = node.pointer.clone();
+                            self.fail("mapping keys must be strings or numbers", &pointer);
+                        }
+                    },
+                },
+            },
+        }
+    }
+
+    /// A block mapping is marked at its first `:`, move it back to its first key
+    fn first_key(&mut self, mark: Marker) {
+        let offset = self.offset(mark);
+        let Some(node) = self.stack.last_mut() else {
+            return;
+        };
+        node.next_key = Some(offset);
+        let YamlNodeKind::Mapping(map, _) = &node.kind else {
+            return;
+        };
+        let key = (mark.line(), mark.col() + 1);
+        let earlier = self
+            .source_map
+            .position(&node.pointer)
+            .is_none_or(|position| key < position);
+        if map.is_empty() && earlier {
+            self.source_map.insert(node.pointer.clone(), key.0, key.1);
+            node.start = node.start.min(offset);
+        }
+    }
+
+    fn fail(&mut self, message: &str, pointer: &str) {
+        if self.error.is_none() {
+            let (line, column) = self.source_map.position(pointer).unwrap_or_default();
+            self.error = Some(syntax_error(message.to_string(), line, column));
+        }
+    }
+
+    fn start(&mut self, kind: YamlNodeKind, anchor: usize, mark: Marker) {
+        let pointer = self.next_pointer().unwrap_or_else(|| {
+            let pointer = self
+                .stack
+                .last()
+                .map(|n| n.pointer.clone())
+                .unwrap_or_default();
+            self.fail("mapping keys must be strings or numbers", &pointer);
+            pointer
+        });
+        self.source_map
+            .insert(pointer.clone(), mark.line(), mark.col() + 1);
+        let key = self.stack.last_mut().and_then(|node| node.next_key.take());
+        self.stack.push(YamlNode {
+            pointer,
+            anchor,
+            kind,
+            start: self.offset(mark),
+            key,
+            next_key: None,
+            end: None,
+        });
+    }
+
+    fn end(&mut self, mark: Marker) {
+        if let Some(node) = self.stack.pop() {
+            // Flow collections end at their bracket, block ones with their last item
+            let end = match self.source.as_bytes().get(node.start) {
+                Some(b'{' | b'[') => {
+                    let offset = self.offset(mark);
+                    matches!(self.source.as_bytes().get(offset), Some(b'}' | b']'))
+                        .then_some(offset + 1)
+                }
+                _ => node.end,
+            };
+            self.located(&node.pointer, node.key, node.start, end);
+            let value = match node.kind {
+                YamlNodeKind::Sequence(items) => Value::Array(items),
+                YamlNodeKind::Mapping(map, _) => Value::Object(map),
+            };
+            self.push(value, node.anchor);
+        }
+    }
+
+    /// Record the span of a value, and that it's the last one of its parent so far
+    fn located(&mut self, pointer: &str, key: Option<usize>, start: usize, end: Option<usize>) {
+        if let Some(end) = end {
+            self.source_map.insert_span(
+                pointer.to_string(),
+                Span {
+                    key,
+                    value: start..end,
+                },
+            );
+        }
+        if let Some(parent) = self.stack.last_mut() {
+            parent.end = end;
+        }
+    }
+
+    fn offset(&self, mark: Marker) -> usize {
+        self.offsets
+            .get(mark.index())
+            .copied()
+            .unwrap_or(self.source.len())
+    }
+
+    /// End of a scalar starting at `start`, if it's on one line or quoted
+    fn scalar_end(&self, start: usize, style: TScalarStyle, text: &str) -> Option<usize> {
+        let rest = self.source.get(start..)?;
+        let quote = match style {
+            TScalarStyle::Plain => {
+                return (!text.contains('\n') && rest.starts_with(text))
+                    .then_some(start + text.len());
+            }
+            TScalarStyle::SingleQuoted => b'\'',
+            TScalarStyle::DoubleQuoted => b'"',
+            _ => return None,
+        };
+        let bytes = rest.as_bytes();
+        if bytes.first() != Some(&quote) {
+            return None;
+        }
+        let mut pos = 1;
+        while pos < bytes.len() {
+            match bytes[pos] {
+                b'\\' if quote == b'"' => pos += 1,
+                b'\'' if quote == b'\'' && bytes.get(pos + 1) == Some(&b'\'') => pos += 1,
+                byte if byte == quote => return Some(start + pos + 1),
+                _ => {}
+            }
+            pos += 1;
+        }
+        None
+    }
+}
+
+impl MarkedEventReceiver for YamlBuilder<'_> {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        if self.done {
+            return;
+        }
+        match event {
+            Event::Scalar(text, style, anchor, tag) => {
+                match self.next_pointer() {
+                    Some(pointer) => {
+                        self.source_map
+                            .insert(pointer.clone(), mark.line(), mark.col() + 1);
+                        let start = self.offset(mark);
+                        let key = self.stack.last_mut().and_then(|node| node.next_key.take());
+                        let end = self.scalar_end(start, style, &text);
+                        self.located(&pointer, key, start, end);
+                    }
+                    None => self.first_key(mark),
+                }
+                let is_str = tag.is_some_and(|tag| tag.suffix == "str");
+                let value = if style == TScalarStyle::Plain && !is_str {
+                    yaml_scalar(&text)
+                } else {
+                    Value::String(text)
+                };
+                self.push(value, anchor);
+            }
+            Event::SequenceStart(anchor, _) => {
+                self.start(YamlNodeKind::Sequence(Vec::new()), anchor, mark)
+            }
+            Event::MappingStart(anchor, _) => {
+                self.start(YamlNodeKind::Mapping(Map::new(), None), anchor, mark)
+            }
+            Event::SequenceEnd | Event::MappingEnd => self.end(mark),
+            Event::Alias(anchor) => {
+                if let Some(pointer) = self.next_pointer() {
+                    self.source_map
+                        .insert(pointer.clone(), mark.line(), mark.col() + 1);
+                    let key = self.stack.last_mut().and_then(|node| node.next_key.take());
+                    self.located(&pointer, key, 0, None);
+                }
+                let value = self.anchors.get(&anchor).cloned().unwrap_or(Value::Null);
+                self.push(value, 0);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Value of an unquoted YAML scalar (`true`, `1.5`, `~`, ...)
+pub(crate) fn yaml_scalar(text: &str) -> Value {
+    match Yaml::from_str(text) {
+        Yaml::Integer(i) => Value::from(i),
+        Yaml::Real(real) => real
+            .parse::<f64>()
+            .ok()
+            .and_then(Number::from_f64)
+            .map_or_else(|| Value::String(text.to_string()), Value::Number),
+        Yaml::Boolean(b) => Value::Bool(b),
+        Yaml::Null => Value::Null,
+        _ => Value::String(text.to_string()),
+    }
+}
+
+fn to_yaml(value: &Value) -> Yaml {
+    match value {
+        Value::Null => Yaml::Null,
+        Value::Bool(b) => Yaml::Boolean(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => Yaml::Integer(i),
+            None => Yaml::Real(n.to_string()),
+        },
+        Value::String(s) => Yaml::String(s.clone()),
+        Value::Array(items) => Yaml::Array(items.iter().map(to_yaml).collect()),
+        Value::Object(map) => Yaml::Hash(
+            map.iter()
+                .map(|(key, value)| (Yaml::String(key.clone()), to_yaml(value)))
+                .collect(),
+        ),
+    }
+}
+
+fn without_nulls(value: &Value) -> Value {
+    match value {
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .filter(|item| !item.is_null())
+                .map(without_nulls)
+                .collect(),
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| (key.clone(), without_nulls(value)))
+                .collect(),
+        ),
+        value => value.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YAML: &str = "\
+# Comments are fine
+metadata:
+  title: Demo
+  fps: 30
+  duration: 2.5
+scenes:
+  - id: intro
+    layers:
+      - type: text
+        content: \"42\"
+        transparent: yes
+";
+
+    const TOML: &str = r#"
+[metadata]
+title = "Demo"
+fps = 30
+duration = 2.5
+
+[[scenes]]
+id = "intro"
+layers = [{ type = "text", content = "42" }]
+"#;
+
+    #[test]
+    fn test_from_path() {
+        assert_eq!(
+            ScriptFormat::from_path(Path::new("a/script.YML")),
+            Some(ScriptFormat::Yaml)
+        );
+        assert_eq!(ScriptFormat::from_path(Path::new("script.txt")), None);
+        assert_eq!(
+            ScriptFormat::detect(Path::new("script")),
+            ScriptFormat::Json
+        );
+    }
+
+    #[test]
+    fn test_read_yaml() {
+        let (value, source_map) = ScriptFormat::Yaml.read(YAML).unwrap();
+        assert_eq!(value["metadata"]["fps"], 30);
+        assert_eq!(value["metadata"]["duration"], 2.5);
+        // Quoted scalars stay strings; `yes` is only a boolean in YAML 1.1
+        assert_eq!(value["scenes"][0]["layers"][0]["content"], "42");
+        assert_eq!(value["scenes"][0]["layers"][0]["transparent"], "yes");
+        assert_eq!(source_map.position("/metadata/fps"), Some((4, 8)));
+        let span = source_map.span("/scenes/0/layers/0/content").unwrap();
+        assert_eq!(&YAML[span.key.unwrap()..span.value.end], "content: \"42\"");
+        let span = source_map.span("/metadata").unwrap();
+        assert_eq!(
+            &YAML[span.value.clone()],
+            "title: Demo\n  fps: 30\n  duration: 2.5"
+        );
+        assert_eq!(source_map.position("/scenes/0/layers/0"), Some((9, 9)));
+
+        let error = ScriptFormat::Yaml.read("scenes: [a\n").unwrap_err();
+        assert!(error.line.is_some());
+    }
+
+    #[test]
+    fn test_read_toml() {
+        let (value, source_map) = ScriptFormat::Toml.read(TOML).unwrap();
+        assert_eq!(value["metadata"]["title"], "Demo");
+        assert_eq!(value["scenes"][0]["layers"][0]["type"], "text");
+        assert_eq!(source_map.position("/metadata/duration"), Some((5, 12)));
+        assert_eq!(
+            source_map.position("/scenes/0/layers/0/content"),
+            Some((9, 38))
+        );
+
+        let error = ScriptFormat::Toml.read("title = \n").unwrap_err();
+        assert_eq!(error.line, Some(1));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let (value, _) = ScriptFormat::Yaml.read(YAML).unwrap();
+        for format in [ScriptFormat::Json, ScriptFormat::Toml, ScriptFormat::Yaml] {
+            let text = format.write(&value).unwrap();
+            let (read, _) = format.read(&text).unwrap();
+            assert_eq!(read, value, "{} round trip:\n{}", format, text);
+        }
+
+        // Keys keep the order they were written in
+        let json = ScriptFormat::Json.write(&value).unwrap();
+        assert!(json.find("\"metadata\"").unwrap() < json.find("\"scenes\"").unwrap());
+        assert!(json.find("\"title\"").unwrap() < json.find("\"fps\"").unwrap());
+
+        // TOML has no null
+        let toml = ScriptFormat::Toml
+            .write(&serde_json::json!({"title": "Demo", "description": null}))
+            .unwrap();
+        assert_eq!(toml, "title = \"Demo\"\n");
+    }
+
+    /// `source` migrated to the current version by `rewrite`
+    fn migrated(format: ScriptFormat, source: &str) -> String {
+        let (mut document, _) = format.read(source).unwrap();
+        crate::parser::ScriptParser::migrate(&mut document, &mut Vec::new()).unwrap();
+        format.rewrite(source, &document).unwrap()
+    }
+
+    #[test]
+    fn test_rewrite() {
+        let yaml = "\
+# yaml-language-server: $schema=schema.json
+metadata:
+  title: Demo   # shown in the player
+scenes:
+  - id: intro
+    layers:
+      - type: image
+        effects:
+          - fade_in   # slow
+          - color_grade:
+              lut: film.cube
+              adjustment: warm  # old
+    transition: {wipe: {duration: 0.5, direction: Left}}
+";
+        assert_eq!(
+            migrated(ScriptFormat::Yaml, yaml),
+            "\
+# yaml-language-server: $schema=schema.json
+version: 2
+metadata:
+  title: Demo   # shown in the player
+scenes:
+  - id: intro
+    layers:
+      - type: image
+        effects:
+          - {fade_in: {}}   # slow
+          - color_grade:
+              lut: film.cube
+    transition: {wipe: {duration: 0.5, direction: left}}
+"
+        );
+
+        let json = r#"{
+  "metadata": {"title": "Demo"},
+  "scenes": [
+    {
+      "id": "intro",
+      "layers": [{"type": "image", "effects": ["fade_in", {"color_grade": {"adjustment": "warm", "lut": "a.cube"}}]}],
+      "transition": {"wipe": {"direction": "Left"}}
+    }
+  ]
+}
+"#;
+        assert_eq!(
+            migrated(ScriptFormat::Json, json),
+            r#"{
+  "version": 2,
+  "metadata": {"title": "Demo"},
+  "scenes": [
+    {
+      "id": "intro",
+      "layers": [{"type": "image", "effects": [{"fade_in": {}}, {"color_grade": {"lut": "a.cube"}}]}],
+      "transition": {"wipe": {"direction": "left"}}
+    }
+  ]
+}
+"#
+        );
+
+        let toml = r#"#:schema schema.json
+[metadata]
+title = "Demo" # shown in the player
+
+[[scenes]]
+id = "intro"
+transition = { wipe = { direction = "Left" } } # wipe
+layers = [
+  { type = "image", effects = ["fade_in", { color_grade = { adjustment = "warm" } }] },
+]
+"#;
+        assert_eq!(
+            migrated(ScriptFormat::Toml, toml),
+            r#"#:schema schema.json
+version = 2
+
+[metadata]
+title = "Demo" # shown in the player
+
+[[scenes]]
+id = "intro"
+transition = { wipe = { direction = "left" } } # wipe
+layers = [
+  { type = "image", effects = [{ fade_in = {} }, { color_grade = {} }] },
+]
+"#
+        );
+
+        // A block mapping left empty becomes `{}` rather than null
+        assert_eq!(
+            migrated(
+                ScriptFormat::Yaml,
+                "scenes:\n- layers:\n  - effects:\n    - color_grade:\n        adjustment: warm\n"
+            ),
+            "version: 2\nscenes:\n- layers:\n  - effects:\n    - color_grade:\n        {}\n"
+        );
+        assert!(ScriptFormat::Yaml.has_comments(yaml));
+        assert!(!ScriptFormat::Yaml.has_comments("color: \"#fff\"\n"));
+    }
+}
@@ -1,15 +1,26 @@
 pub mod analysis;
+pub mod animation;
 pub mod assets;
 pub mod audio;
+mod composition;
 pub mod config;
 pub mod context;
+pub mod diagnostics;
+pub mod error;
+pub mod events;
+pub mod formats;
 pub mod parser;
 pub mod renderer;
+mod rewrite;
+pub mod schema;
 pub mod script;
 pub mod templates;
+pub mod video;
 
 pub use assets::AssetLoader;
 pub use audio::{AudioDecoder, AudioMixer};
-pub use parser::ScriptParser;
+pub use error::{Error, Result};
+pub use parser::{ParseOptions, ScriptParser};
 pub use renderer::{Compositor, FrameBuffer, RenderEngine, Timeline};
 pub use script::VideoScript;
+pub use video::VideoDecoder;
@@ -1,18 +1,72 @@
-use anyhow::Result;
-use clap::{Parser, Subcommand};
+use anyhow::{Context, Result};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use interstellar_triangulum::config::AppConfig;
+use interstellar_triangulum::context::credibility::CredibilityContext;
+use interstellar_triangulum::context::narrative::NarrativeContext;
+use interstellar_triangulum::context::performance::{PerformanceContext, RenderOptions};
+use interstellar_triangulum::diagnostics::{Diagnostic, Severity};
+use interstellar_triangulum::events::{
+    ConsoleObserver, JsonObserver, Level, SharedObserver, Stage,
+};
+use interstellar_triangulum::formats::ScriptFormat;
+use interstellar_triangulum::renderer::{
+    FrameSelection, RenderEngine, RendererOptions, RendererRegistry, Storyboard,
+};
+use interstellar_triangulum::schema::ScriptSchema;
+use interstellar_triangulum::script::{
+    Container, EncoderSettings, VideoCodec, VideoScript, SCRIPT_VERSION,
+};
 use interstellar_triangulum::templates::{ScriptTemplate, TemplateType};
-use interstellar_triangulum::{AssetLoader, ScriptParser};
+use interstellar_triangulum::{AssetLoader, ParseOptions, ScriptParser};
+use serde::Serialize;
+use similar::TextDiff;
 use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
 
 #[derive(Parser)]
 #[command(name = "interstellar-triangulum")]
 #[command(about = "Digital Artisan Video Engine", long_about = None)]
 struct Cli {
+    /// How progress is reported
+    #[arg(long, value_enum, global = true, default_value_t = ProgressFormat::Human)]
+    progress: ProgressFormat,
+
+    /// Format of script files: json, toml or yaml (by default from their
+    /// extension, JSON if unknown)
+    #[arg(long, global = true, value_name = "FORMAT")]
+    script_format: Option<ScriptFormat>,
+
+    /// Set a script variable, overriding the script's value (can be repeated)
+    #[arg(long = "var", global = true, value_name = "KEY=VALUE", value_parser = parse_variable)]
+    variables: Vec<(String, String)>,
+
+    /// JSON, TOML or YAML file of script variables (`--var` takes precedence)
+    #[arg(long = "vars", global = true, value_name = "FILE")]
+    vars_file: Option<std::path::PathBuf>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
 
+/// Output of the `--progress` option
+#[derive(Clone, Copy, ValueEnum)]
+enum ProgressFormat {
+    /// Readable progress lines
+    Human,
+    /// One JSON event per line
+    Json,
+}
+
+/// Output of `validate --format`
+#[derive(Clone, Copy, ValueEnum)]
+enum DiagnosticFormat {
+    /// Compiler-style reports with the offending lines
+    Human,
+    /// One JSON document listing every issue
+    Json,
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Render a script to video
@@ -21,7 +75,7 @@ enum Commands {
         #[arg(value_name = "SCRIPT")]
         script: String,
 
-        /// Renderer engine to use
+        /// Renderer engine to use (see `renderers`)
         #[arg(long)]
         renderer: Option<String>,
 
@@ -40,6 +94,96 @@ enum Commands {
         /// Force CPU rendering (disable GPU)
         #[arg(long)]
         force_cpu: bool,
+
+        /// Also write every frame to the output directory (debugging)
+        #[arg(long)]
+        keep_frames: bool,
+
+        /// Frames rendered in parallel on the CPU (0 = twice the cores, 1 = sequential)
+        #[arg(long, value_name = "N")]
+        max_in_flight_frames: Option<usize>,
+
+        /// Render with a transparent background (needs ProRes, VP9 or PNG output)
+        #[arg(long)]
+        transparent: bool,
+
+        /// Only render these frames (e.g. 120..240, 120..=239, 120..)
+        #[arg(long, value_name = "RANGE", value_parser = FrameSelection::parse_range)]
+        frames: Option<FrameSelection>,
+
+        /// Reuse frames of unchanged scenes from earlier renders, cached in the
+        /// output directory
+        #[arg(long)]
+        cache: bool,
+
+        /// Only render the frame at this time (e.g. 00:00:04.5), kept as an image
+        #[arg(long, value_name = "TIME", value_parser = FrameSelection::parse_time, conflicts_with = "frames")]
+        at: Option<FrameSelection>,
+
+        #[command(flatten)]
+        encoder: Box<EncoderArgs>,
+    },
+
+    /// Render a single frame of a script to a PNG image
+    Still {
+        /// Path to the script file
+        #[arg(value_name = "SCRIPT")]
+        script: String,
+
+        /// Time of the frame (e.g. 00:00:04.5)
+        #[arg(long, value_name = "TIME", value_parser = FrameSelection::parse_time)]
+        at: Option<FrameSelection>,
+
+        /// Frame number (instead of a time)
+        #[arg(long, conflicts_with = "at")]
+        frame: Option<u32>,
+
+        /// Output PNG file
+        #[arg(long, default_value = "still.png")]
+        output: std::path::PathBuf,
+
+        /// Renderer engine to use (see `renderers`)
+        #[arg(long)]
+        renderer: Option<String>,
+
+        /// Force CPU rendering (disable GPU)
+        #[arg(long)]
+        force_cpu: bool,
+
+        /// Render with a transparent background
+        #[arg(long)]
+        transparent: bool,
+    },
+
+    /// Render a contact sheet of a script's scenes to a PNG image
+    Storyboard {
+        /// Path to the script file
+        #[arg(value_name = "SCRIPT")]
+        script: String,
+
+        /// Show this many evenly spaced frames instead of one per scene
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+        count: Option<u32>,
+
+        /// Tiles per row
+        #[arg(long, default_value_t = 4)]
+        columns: u32,
+
+        /// Width of each tile in pixels
+        #[arg(long, default_value_t = 480)]
+        tile_width: u32,
+
+        /// Font for the captions (defaults to the script's first text font)
+        #[arg(long)]
+        font: Option<std::path::PathBuf>,
+
+        /// Output PNG file
+        #[arg(long, default_value = "storyboard.png")]
+        output: std::path::PathBuf,
+
+        /// Force CPU rendering (disable GPU)
+        #[arg(long)]
+        force_cpu: bool,
     },
 
     /// Validate script without rendering
@@ -51,6 +195,45 @@ enum Commands {
         /// Fail on warnings
         #[arg(long)]
         fail_on_warnings: bool,
+
+        /// How issues in the script are reported
+        #[arg(long, value_enum, default_value_t = DiagnosticFormat::Human)]
+        format: DiagnosticFormat,
+
+        /// Report unknown keys as warnings instead of errors
+        #[arg(long)]
+        no_strict: bool,
+    },
+
+    /// Convert a script between JSON, TOML and YAML
+    Convert {
+        /// Path to the script file
+        #[arg(value_name = "SCRIPT")]
+        script: String,
+
+        /// File to write
+        #[arg(value_name = "OUTPUT")]
+        output: std::path::PathBuf,
+
+        /// Format to write: json, toml or yaml (by default from the output's extension)
+        #[arg(long, value_name = "FORMAT")]
+        to: Option<ScriptFormat>,
+    },
+
+    /// Upgrade scripts to the current format version, rewriting them in place
+    Migrate {
+        /// Paths to the script files
+        #[arg(value_name = "SCRIPT", required = true)]
+        scripts: Vec<String>,
+
+        /// Only show the changes, without writing the files
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Write scripts that can't be edited in place even if they have comments,
+        /// which are lost
+        #[arg(long)]
+        force: bool,
     },
 
     /// Generate a script template
@@ -65,14 +248,77 @@ enum Commands {
         duration: f32,
     },
 
+    /// Print the JSON Schema of scripts, for editors to validate and complete them
+    Schema,
+
+    /// List the available rendering backends and what they support
+    Renderers,
+
     /// Clean output and cache directories
     Clean,
 }
 
+/// Encoder overrides for the `render` command
+#[derive(Args)]
+struct EncoderArgs {
+    /// Output video file
+    #[arg(long, value_name = "PATH")]
+    output_file: Option<std::path::PathBuf>,
+
+    /// Container format [possible values: mp4, mov, mkv, webm, png]
+    #[arg(long)]
+    container: Option<Container>,
+
+    /// Video codec [possible values: h264, h265, vp9, av1, prores]
+    #[arg(long)]
+    codec: Option<VideoCodec>,
+
+    /// Constant rate factor (lower is better quality)
+    #[arg(long)]
+    crf: Option<u32>,
+
+    /// Target video bitrate (e.g. 8M)
+    #[arg(long)]
+    bitrate: Option<String>,
+
+    /// Encoder preset (the profile for ProRes)
+    #[arg(long)]
+    preset: Option<String>,
+
+    /// Output pixel format (e.g. yuv420p)
+    #[arg(long)]
+    pixel_format: Option<String>,
+
+    /// Audio bitrate (e.g. 192k)
+    #[arg(long)]
+    audio_bitrate: Option<String>,
+}
+
+impl From<EncoderArgs> for EncoderSettings {
+    fn from(args: EncoderArgs) -> Self {
+        Self {
+            output: args.output_file,
+            container: args.container,
+            codec: args.codec,
+            crf: args.crf,
+            bitrate: args.bitrate,
+            preset: args.preset,
+            pixel_format: args.pixel_format,
+            audio_bitrate: args.audio_bitrate,
+        }
+    }
+}
+
 fn main() -> Result<()> {
     // Load configuration
     let config = AppConfig::load().unwrap_or_default();
     let cli = Cli::parse();
+    let renderers = RendererRegistry::new();
+    let parse_options = parse_options(&cli)?;
+    let observer: SharedObserver = match cli.progress {
+        ProgressFormat::Human => Arc::new(ConsoleObserver),
+        ProgressFormat::Json => Arc::new(JsonObserver),
+    };
 
     match cli.command {
         Some(Commands::Template {
@@ -82,6 +328,15 @@ fn main() -> Result<()> {
             let script = ScriptTemplate::generate(template_type, duration);
             println!("{}", serde_json::to_string_pretty(&script)?);
         }
+        Some(Commands::Schema) => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&ScriptSchema::generate())?
+            );
+        }
+        Some(Commands::Renderers) => {
+            list_renderers(&renderers);
+        }
         Some(Commands::Clean) => {
             let output_dir = &config.renderer.output_dir;
             let cache_dir = Path::new(".cache");
@@ -97,8 +352,38 @@ fn main() -> Result<()> {
         Some(Commands::Validate {
             script,
             fail_on_warnings,
+            format,
+            no_strict,
         }) => {
-            run_validation(&script, fail_on_warnings)?;
+            let mut file = ScriptFile::new(script, &parse_options);
+            file.options.strict = !no_strict;
+            run_validation(&file, fail_on_warnings, format, &observer)?;
+        }
+        Some(Commands::Convert { script, output, to }) => {
+            run_convert(
+                &ScriptFile::new(script, &parse_options),
+                &output,
+                to,
+                &observer,
+            )?;
+        }
+        Some(Commands::Migrate {
+            scripts,
+            dry_run,
+            force,
+        }) => {
+            let failed = scripts
+                .into_iter()
+                .map(|script| ScriptFile::new(script, &parse_options))
+                .filter(|file| {
+                    run_migrate(file, dry_run, force, &observer)
+                        .inspect_err(|error| observer.error(format!("{:#}", error)))
+                        .is_err()
+                })
+                .count();
+            if failed > 0 {
+                anyhow::bail!("{} script(s) could not be migrated", failed);
+            }
         }
         Some(Commands::Render {
             script,
@@ -107,19 +392,102 @@ fn main() -> Result<()> {
             export_report,
             fail_on_low_score,
             force_cpu,
+            keep_frames,
+            max_in_flight_frames,
+            transparent,
+            frames,
+            cache,
+            at,
+            encoder,
         }) => {
             let renderer_engine = renderer.unwrap_or(config.renderer.engine.clone());
             let output_dir = output
                 .map(std::path::PathBuf::from)
                 .unwrap_or(config.renderer.output_dir.clone());
+            let options = RenderOptions {
+                output_dir: output_dir.clone(),
+                renderer: renderer_engine,
+                renderer_options: RendererOptions {
+                    use_gpu: !force_cpu,
+                    max_in_flight_frames: max_in_flight_frames
+                        .unwrap_or(config.renderer.max_in_flight_frames),
+                    scene_cache_dir: cache.then(|| output_dir.join(".cache")),
+                    observer: observer.clone(),
+                },
+                keep_frames,
+                transparent,
+                encoder: (*encoder).into(),
+                frames: frames.or(at),
+            };
 
             run_render(
-                &script,
-                &renderer_engine,
-                &output_dir,
+                &ScriptFile::new(script, &parse_options),
                 export_report,
                 fail_on_low_score,
-                force_cpu,
+                options,
+                &config.encoder,
+                &renderers,
+            )?;
+        }
+        Some(Commands::Still {
+            script,
+            at,
+            frame,
+            output,
+            renderer,
+            force_cpu,
+            transparent,
+        }) => {
+            let renderer_engine = renderer.unwrap_or(config.renderer.engine.clone());
+            let frame = frame.unwrap_or(0);
+            let options = RenderOptions {
+                output_dir: config.renderer.output_dir.clone(),
+                renderer: renderer_engine,
+                renderer_options: RendererOptions {
+                    use_gpu: !force_cpu,
+                    observer: observer.clone(),
+                    ..Default::default()
+                },
+                keep_frames: false,
+                transparent,
+                encoder: EncoderSettings::default(),
+                frames: Some(match at {
+                    Some(at) => at,
+                    None => FrameSelection::Range {
+                        start: frame,
+                        end: Some(frame.checked_add(1).context("Frame number is too large")?),
+                    },
+                }),
+            };
+
+            run_still(
+                &ScriptFile::new(script, &parse_options),
+                &output,
+                &options,
+                &renderers,
+            )?;
+        }
+        Some(Commands::Storyboard {
+            script,
+            count,
+            columns,
+            tile_width,
+            font,
+            output,
+            force_cpu,
+        }) => {
+            run_storyboard(
+                &ScriptFile::new(script, &parse_options),
+                count,
+                columns,
+                tile_width,
+                font.as_deref(),
+                &output,
+                &RendererOptions {
+                    use_gpu: !force_cpu,
+                    observer: observer.clone(),
+                    ..Default::default()
+                },
             )?;
         }
         None => {
@@ -135,19 +503,172 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn run_validation(script_path: &str, fail_on_warnings: bool) -> Result<()> {
-    let script_path = Path::new(script_path);
-    println!("🔍 Validating script: {}", script_path.display());
+fn list_renderers(renderers: &RendererRegistry) {
+    println!("🎨 Available renderers:");
+    for name in renderers.names() {
+        let Some((description, capabilities)) = renderers.info(name) else {
+            continue;
+        };
+        let list = |items: &[&str]| {
+            if items.is_empty() {
+                "none".to_string()
+            } else {
+                items.join(", ")
+            }
+        };
+        let yes_no = |supported| if supported { "yes" } else { "no" };
+
+        println!("\n  {} - {}", name, description);
+        println!("    layers:       {}", list(capabilities.layers));
+        println!("    effects:      {}", list(capabilities.effects));
+        println!("    transitions:  {}", list(capabilities.transitions));
+        println!("    transparency: {}", yes_no(capabilities.transparency));
+        println!("    streaming:    {}", yes_no(capabilities.streaming));
+    }
+}
+
+/// A script file given on the command line
+struct ScriptFile {
+    path: std::path::PathBuf,
+    /// `--script-format` and variables, and whether unknown keys are errors
+    options: ParseOptions,
+}
+
+impl ScriptFile {
+    fn new(path: String, options: &ParseOptions) -> Self {
+        Self {
+            path: path.into(),
+            options: options.clone(),
+        }
+    }
+}
+
+/// `KEY=VALUE` of a `--var` option
+fn parse_variable(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("expected KEY=VALUE, got '{}'", arg)),
+    }
+}
+
+/// How scripts are read, from the global options
+fn parse_options(cli: &Cli) -> Result<ParseOptions> {
+    let mut variables = serde_json::Map::new();
+    if let Some(path) = &cli.vars_file {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read variables file {}", path.display()))?;
+        let format = ScriptFormat::detect(path);
+        match format.read(&text) {
+            Ok((serde_json::Value::Object(values), _)) => variables = values,
+            Ok(_) => anyhow::bail!("Variables file {} must map names to values", path.display()),
+            Err(error) => anyhow::bail!(
+                "Failed to parse {} variables file {}: {}",
+                format,
+                path.display(),
+                error.message
+            ),
+        }
+    }
+    for (key, value) in &cli.variables {
+        variables.insert(key.clone(), value.clone().into());
+    }
+    Ok(ParseOptions {
+        format: cli.script_format,
+        variables,
+        ..Default::default()
+    })
+}
 
-    let script = ScriptParser::parse_json(script_path)?;
-    println!("\n📋 Script Summary:");
-    println!("{}", ScriptParser::summarize(&script));
+/// Parse and validate a script, reporting its issues to `observer`
+fn load_script(file: &ScriptFile, observer: &SharedObserver) -> Result<VideoScript> {
+    let script_path = file.path.as_path();
+    let check = ScriptParser::check_file(script_path, &file.options)?;
+    for diagnostic in &check.diagnostics {
+        let level = match diagnostic.severity {
+            Severity::Error => Level::Error,
+            Severity::Warning => Level::Warning,
+        };
+        let location = match (diagnostic.line, diagnostic.column) {
+            (Some(line), Some(column)) => format!("{}:{}:{}", script_path.display(), line, column),
+            _ => script_path.display().to_string(),
+        };
+        observer.message(level, format!("{}: {}", location, diagnostic));
+    }
+
+    match check.script {
+        Some(script) => Ok(script),
+        None => anyhow::bail!(
+            "Invalid script {} ({} error(s), run `validate` for details)",
+            script_path.display(),
+            check.errors()
+        ),
+    }
+}
+
+/// Output of `validate --format json`
+#[derive(Serialize)]
+struct ValidationReport<'a> {
+    path: &'a Path,
+    valid: bool,
+    errors: usize,
+    warnings: usize,
+    diagnostics: &'a [Diagnostic],
+}
+
+fn run_validation(
+    file: &ScriptFile,
+    fail_on_warnings: bool,
+    format: DiagnosticFormat,
+    observer: &SharedObserver,
+) -> Result<()> {
+    let script_path = file.path.as_path();
+    let check = ScriptParser::check_file(script_path, &file.options)?;
+    let failed = check.errors() > 0 || (fail_on_warnings && check.warnings() > 0);
+
+    if let DiagnosticFormat::Json = format {
+        let report = ValidationReport {
+            path: script_path,
+            valid: check.errors() == 0,
+            errors: check.errors(),
+            warnings: check.warnings(),
+            diagnostics: &check.diagnostics,
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        if failed {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    observer.info(format!("Validating script: {}", script_path.display()));
+    for diagnostic in &check.diagnostics {
+        observer.diagnostic(script_path, &check.source, diagnostic);
+    }
+    let Some(script) = check.script.as_ref() else {
+        observer.error(format!(
+            "{} has {} error(s) and {} warning(s)",
+            script_path.display(),
+            check.errors(),
+            check.warnings()
+        ));
+        std::process::exit(1);
+    };
+    if failed {
+        observer.error(format!(
+            "Validation failed due to {} warning(s) (strict mode).",
+            check.warnings()
+        ));
+        std::process::exit(1);
+    }
+
+    observer.info(format!(
+        "Script Summary:\n{}",
+        ScriptParser::summarize(script)
+    ));
 
     // Run Analysis
-    let narrative_report =
-        interstellar_triangulum::context::narrative::NarrativeContext::run(&script);
-    let credibility_report =
-        interstellar_triangulum::context::credibility::CredibilityContext::run(&script);
+    let narrative_report = NarrativeContext::run_with(script, observer.as_ref());
+    let credibility_report = CredibilityContext::run_with(script, observer.as_ref());
 
     if fail_on_warnings {
         let has_warnings = !narrative_report.structure_valid
@@ -157,42 +678,156 @@ fn run_validation(script_path: &str, fail_on_warnings: bool) -> Result<()> {
             || credibility_report.score < 100; // Strict check
 
         if has_warnings {
-            eprintln!("\n❌ Validation failed due to warnings (strict mode).");
+            observer.error("Validation failed due to warnings (strict mode).");
             std::process::exit(1);
         }
     }
 
-    println!("\n✅ Validation complete.");
+    observer.info("Validation complete.");
+    Ok(())
+}
+
+fn run_convert(
+    file: &ScriptFile,
+    output: &Path,
+    to: Option<ScriptFormat>,
+    observer: &SharedObserver,
+) -> Result<()> {
+    let format = to
+        .or_else(|| ScriptFormat::from_path(output))
+        .with_context(|| {
+            format!(
+                "Can't tell the format of {} from its extension, use --to",
+                output.display()
+            )
+        })?;
+
+    let check = ScriptParser::check_file(&file.path, &file.options)?;
+    let document = match &check.document {
+        Some(document) if check.errors() == 0 => document,
+        _ => {
+            for diagnostic in check.diagnostics.iter().filter(|d| d.is_error()) {
+                observer.diagnostic(&file.path, &check.source, diagnostic);
+            }
+            anyhow::bail!(
+                "Not converting {}: it has {} error(s)",
+                file.path.display(),
+                check.errors()
+            );
+        }
+    };
+
+    let text = format.write(document)?;
+    std::fs::write(output, text)
+        .with_context(|| format!("Failed to write {}", output.display()))?;
+    observer.info(format!(
+        "Converted {} from {} to {}",
+        file.path.display(),
+        check.format,
+        format
+    ));
+    observer.output_written(output);
+
+    Ok(())
+}
+
+/// Upgrade a script to the current version, printing the changes as a diff
+fn run_migrate(
+    file: &ScriptFile,
+    dry_run: bool,
+    force: bool,
+    observer: &SharedObserver,
+) -> Result<()> {
+    let check = ScriptParser::check_file(&file.path, &file.options)?;
+    let Some(mut document) = check.document else {
+        for diagnostic in &check.diagnostics {
+            observer.diagnostic(&file.path, &check.source, diagnostic);
+        }
+        anyhow::bail!("Not migrating {}: it can't be read", file.path.display());
+    };
+    let mut dropped = Vec::new();
+    let version = ScriptParser::migrate(&mut document, &mut dropped)
+        .with_context(|| format!("Not migrating {}", file.path.display()))?;
+    if version == SCRIPT_VERSION {
+        observer.info(format!(
+            "{} is already at version {}",
+            file.path.display(),
+            SCRIPT_VERSION
+        ));
+        return Ok(());
+    }
+
+    let name = file.path.display().to_string();
+    // Edit only what the migration changes; writing the whole document again
+    // would reformat the file and drop its comments
+    let text = match check.format.rewrite(&check.source, &document) {
+        Some(text) => text,
+        None if check.format.has_comments(&check.source) && !force => anyhow::bail!(
+            "Not migrating {}: it can't be edited in place, and rewriting it would lose \
+             its comments (use --force to rewrite it anyway)",
+            name
+        ),
+        None => check.format.write(&document)?,
+    };
+    print!(
+        "{}",
+        TextDiff::from_lines(&check.source, &text)
+            .unified_diff()
+            .header(&name, &name)
+    );
+    // Values the new version has no place for
+    if let Ok((_, source_map)) = check.format.read(&check.source) {
+        for mut diagnostic in dropped {
+            diagnostic.locate(&source_map);
+            observer.diagnostic(&file.path, &check.source, &diagnostic);
+        }
+    }
+    if dry_run {
+        observer.info(format!(
+            "Would migrate {} from version {} to {} (dry run, not written)",
+            name, version, SCRIPT_VERSION
+        ));
+        return Ok(());
+    }
+
+    std::fs::write(&file.path, text).with_context(|| format!("Failed to write {}", name))?;
+    observer.info(format!(
+        "Migrated {} from version {} to {}",
+        name, version, SCRIPT_VERSION
+    ));
+    observer.output_written(&file.path);
     Ok(())
 }
 
 fn run_render(
-    script_path: &str,
-    renderer_engine: &str,
-    output_dir: &Path,
+    file: &ScriptFile,
     export_report: Option<String>,
     fail_on_low_score: Option<u32>,
-    force_cpu: bool,
+    mut options: RenderOptions,
+    config_encoder: &EncoderSettings,
+    renderers: &RendererRegistry,
 ) -> Result<()> {
-    let script_path = Path::new(script_path);
-    println!("🎬 Video Engine - Digital Artisan PoC\n");
-    println!("Parsing script: {}", script_path.display());
+    let script_path = file.path.as_path();
+    let observer = options.renderer_options.observer.clone();
+    observer.info(format!("Parsing script: {}", script_path.display()));
 
-    let script = ScriptParser::parse_json(script_path)?;
+    let mut script = load_script(file, &observer)?;
+    script.metadata.transparent |= options.transparent;
 
-    println!("\n📋 Script Summary:");
-    println!("{}", ScriptParser::summarize(&script));
+    observer.info(format!(
+        "Script Summary:\n{}",
+        ScriptParser::summarize(&script)
+    ));
 
     // Load assets
     let base_path = script_path.parent().unwrap_or_else(|| Path::new("."));
     let mut loader = AssetLoader::new(base_path);
 
     // Pillar 2: Narrative (Engaging)
-    let narrative_report =
-        interstellar_triangulum::context::narrative::NarrativeContext::run(&script);
+    let narrative_report = NarrativeContext::run_with(&script, observer.as_ref());
 
     // Pillar 3: Credibility (Trustworthy)
-    interstellar_triangulum::context::credibility::CredibilityContext::run(&script);
+    CredibilityContext::run_with(&script, observer.as_ref());
 
     // Export Report
     if let Some(path) = export_report {
@@ -218,64 +853,145 @@ fn run_render(
             md
         };
         std::fs::write(path, content)?;
-        println!("\n📄 Report exported to: {}", path.display());
+        observer.output_written(path);
     }
 
     // Fail on low score
     if let Some(threshold) = fail_on_low_score {
         if narrative_report.score < threshold {
-            eprintln!(
-                "\n❌ Narrative score {} is below threshold {}",
+            observer.error(format!(
+                "Narrative score {} is below threshold {}",
                 narrative_report.score, threshold
-            );
+            ));
             std::process::exit(1);
         }
     }
 
     // Pillar 1: Performance (Fast) - Asset Loading & Rendering
-    println!("\n🎨 Loading assets...");
+    observer.stage_started(Stage::Assets);
+    let assets_started = Instant::now();
     // Pre-load assets for statistics and validation
     for scene in &script.scenes {
         for layer in &scene.layers {
             match layer {
                 interstellar_triangulum::script::Layer::Image { source, .. } => {
                     if let Err(e) = loader.load_image(source) {
-                        println!("  ✗ Failed to load image {}: {}", source.display(), e);
+                        observer.warning(format!(
+                            "Failed to load image {}: {}",
+                            source.display(),
+                            e
+                        ));
                     } else {
-                        println!("  ✓ Loaded image: {}", source.display());
+                        observer.info(format!("Loaded image: {}", source.display()));
                     }
                 }
                 interstellar_triangulum::script::Layer::Video { source, .. } => {
                     if let Err(e) = loader.load_video(source) {
-                        println!("  ✗ Failed to load video {}: {}", source.display(), e);
+                        observer.warning(format!(
+                            "Failed to load video {}: {}",
+                            source.display(),
+                            e
+                        ));
                     } else {
-                        println!("  ✓ Loaded video: {}", source.display());
+                        observer.info(format!("Loaded video: {}", source.display()));
                     }
                 }
                 interstellar_triangulum::script::Layer::Text { font, .. } => {
                     if let Err(e) = loader.load_font(font) {
-                        println!("  ✗ Failed to load font {}: {}", font.display(), e);
+                        observer.warning(format!("Failed to load font {}: {}", font.display(), e));
                     } else {
-                        println!("  ✓ Loaded font: {}", font.display());
+                        observer.info(format!("Loaded font: {}", font.display()));
                     }
                 }
             }
         }
     }
 
-    let use_blender = renderer_engine == "blender";
-    let use_gpu = !force_cpu;
+    observer.stage_finished(Stage::Assets, assets_started);
+
+    // Command line settings win over the script's, which win over the config's
+    // and its output path is relative to the script, like its assets
+    let mut script_encoder = script.metadata.encoder.clone().unwrap_or_default();
+    if let Some(output) = &mut script_encoder.output {
+        *output = loader.resolve_path(output);
+    }
+    options.encoder = options.encoder.or(&script_encoder.or(config_encoder));
+
+    PerformanceContext::run(&script, &mut loader, &options, renderers)?;
+
+    observer.info(format!("Asset Statistics: {}", loader.stats()));
+
+    Ok(())
+}
+
+fn run_still(
+    file: &ScriptFile,
+    output: &Path,
+    options: &RenderOptions,
+    renderers: &RendererRegistry,
+) -> Result<()> {
+    let script_path = file.path.as_path();
+    let observer = &options.renderer_options.observer;
+    observer.info(format!("Rendering still from: {}", script_path.display()));
+
+    let mut script = load_script(file, observer)?;
+    script.metadata.transparent |= options.transparent;
+
+    let base_path = script_path.parent().unwrap_or_else(|| Path::new("."));
+    let mut loader = AssetLoader::new(base_path);
+
+    let frame = PerformanceContext::render_still(&script, &mut loader, options, renderers, output)?;
+    observer.info(format!("Rendered frame {}", frame));
+    observer.output_written(output);
+
+    Ok(())
+}
+
+fn run_storyboard(
+    file: &ScriptFile,
+    count: Option<u32>,
+    columns: u32,
+    tile_width: u32,
+    font: Option<&Path>,
+    output: &Path,
+    renderer_options: &RendererOptions,
+) -> Result<()> {
+    let observer = &renderer_options.observer;
+    let script_path = file.path.as_path();
+    observer.info(format!(
+        "Building storyboard from: {}",
+        script_path.display()
+    ));
+
+    let script = load_script(file, observer)?;
+    let base_path = script_path.parent().unwrap_or_else(|| Path::new("."));
+    let mut loader = AssetLoader::new(base_path);
+
+    let caption_font = Storyboard::caption_font(&script, &mut loader, font);
+    if caption_font.is_none() {
+        observer.warning("No usable font found, tiles will have no captions (use --font)");
+    }
 
-    interstellar_triangulum::context::performance::PerformanceContext::run(
-        &script,
+    observer.stage_started(Stage::Render);
+    let started = Instant::now();
+    let mut engine =
+        RenderEngine::with_observer(script.clone(), renderer_options.use_gpu, observer.clone());
+    let frames = Storyboard::select_frames(&script, engine.timeline(), count);
+    let sheet = Storyboard::render(
+        &mut engine,
         &mut loader,
-        output_dir,
-        use_blender,
-        use_gpu,
+        &frames,
+        columns,
+        tile_width,
+        caption_font.as_ref(),
     )?;
 
-    println!("\n📊 Asset Statistics:");
-    println!("  {}", loader.stats());
+    sheet
+        .save(output)
+        .with_context(|| format!("Failed to save storyboard: {}", output.display()))?;
+    observer.stage_finished(Stage::Render, started);
+    observer.info(format!("Storyboard of {} frames", frames.len()));
+    observer.output_written(output);
 
     Ok(())
 }
@@ -1,70 +1,495 @@
-use crate::script::VideoScript;
-use anyhow::{Context, Result};
+use crate::composition::Composition;
+use crate::diagnostics::{escape_pointer, Diagnostic, Severity};
+use crate::error::ParseError;
+use crate::formats::{strip_position, ScriptFormat};
+use crate::schema::{closest, ScriptSchema};
+use crate::script::{Effect, Layer, VideoScript, SCRIPT_VERSION};
+use regex::{Captures, Regex};
+use serde_json::{Map, Value};
 use std::path::Path;
 
-/// Script parser that handles JSON/TOML video scripts
+/// Script parser that handles JSON, TOML and YAML video scripts
 pub struct ScriptParser;
 
+/// Upgrades of script documents, the one at index `i` taking version `i + 1`
+/// to `i + 2`
+const MIGRATIONS: [fn(&mut Value, &mut Vec<Diagnostic>); SCRIPT_VERSION as usize - 1] = [version_2];
+
+/// A script file with everything validation found in it
+#[derive(Debug)]
+pub struct ScriptCheck {
+    pub format: ScriptFormat,
+    /// Text of the file, for showing the lines diagnostics point at
+    pub source: String,
+    /// The document as parsed, before it was read as a script (e.g. for
+    /// converting it to another format)
+    pub document: Option<Value>,
+    /// The script, unless it has errors
+    pub script: Option<VideoScript>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ScriptCheck {
+    pub fn errors(&self) -> usize {
+        self.diagnostics.iter().filter(|d| d.is_error()).count()
+    }
+
+    pub fn warnings(&self) -> usize {
+        self.diagnostics.len() - self.errors()
+    }
+
+    /// The script, or the first error of the file at `path`
+    pub fn into_script(self, path: &Path) -> Result<VideoScript, ParseError> {
+        if let Some(script) = self.script {
+            return Ok(script);
+        }
+        let error = self
+            .diagnostics
+            .into_iter()
+            .find(Diagnostic::is_error)
+            .expect("invalid scripts have an error");
+        if self.document.is_some() && error.code != "invalid-structure" {
+            return Err(error.into());
+        }
+        let message = match error.pointer.as_str() {
+            "" => error.message,
+            pointer => format!("{} (at {})", error.message, pointer),
+        };
+        Err(ParseError::Syntax {
+            path: path.to_path_buf(),
+            format: self.format,
+            line: error.line,
+            column: error.column,
+            message,
+        })
+    }
+}
+
+/// How script files are read
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// Format of the file (by default the one its extension names, JSON if it has none)
+    pub format: Option<ScriptFormat>,
+    /// Report keys that aren't script fields as errors instead of warnings
+    pub strict: bool,
+    /// Values overriding the script's `variables`. Strings are read as the
+    /// type of the script's value, so `"48"` can set a number.
+    pub variables: Map<String, Value>,
+}
+
 impl ScriptParser {
+    /// Parse a script file. Warnings are left out; use `check_file` to get them.
+    pub fn parse_file(path: &Path, options: &ParseOptions) -> Result<VideoScript, ParseError> {
+        Self::check_file(path, options)?.into_script(path)
+    }
+
     /// Parse a JSON script file
-    pub fn parse_json(path: &Path) -> Result<VideoScript> {
-        let content = std::fs::read_to_string(path)
-            .with_context(|| format!("Failed to read script file: {}", path.display()))?;
+    pub fn parse_json(path: &Path) -> Result<VideoScript, ParseError> {
+        let options = ParseOptions {
+            format: Some(ScriptFormat::Json),
+            ..Default::default()
+        };
+        Self::parse_file(path, &options)
+    }
+
+    /// Read, parse and validate a script file, collecting every issue found
+    /// with its location in the file. Only failing to read the file is an error.
+    pub fn check_file(path: &Path, options: &ParseOptions) -> Result<ScriptCheck, ParseError> {
+        let source = std::fs::read_to_string(path).map_err(|source| ParseError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let options = ParseOptions {
+            format: Some(options.format.unwrap_or_else(|| ScriptFormat::detect(path))),
+            ..options.clone()
+        };
+        Ok(Self::check(source, Some(path), &options))
+    }
+
+    /// Parse and validate the text of a script (JSON unless `options` name a format)
+    pub fn check_source(source: String, options: &ParseOptions) -> ScriptCheck {
+        Self::check(source, None, options)
+    }
+
+    /// Check a script, read from the file at `path` (if any) that its includes
+    /// are relative to
+    fn check(source: String, path: Option<&Path>, options: &ParseOptions) -> ScriptCheck {
+        let format = options.format.unwrap_or(ScriptFormat::Json);
+        let (document, source_map) = match format.read(&source) {
+            Ok(read) => read,
+            Err(error) => {
+                return ScriptCheck {
+                    format,
+                    source,
+                    document: None,
+                    script: None,
+                    diagnostics: vec![error.into()],
+                }
+            }
+        };
+
+        // Scripts are read as the current version; the file keeps its own
+        let mut current = document.clone();
+        let mut diagnostics = Vec::new();
+        let script = match Self::migrate(&mut current, &mut diagnostics) {
+            Err(error) => {
+                diagnostics.push(Diagnostic::error(
+                    "unsupported-version",
+                    "/version",
+                    error.to_string(),
+                ));
+                None
+            }
+            Ok(version) => {
+                if version < SCRIPT_VERSION {
+                    diagnostics.push(Diagnostic::warning(
+                        "outdated-version",
+                        "/version",
+                        format!(
+                            "Script is version {}, upgrade it to version {} with `migrate`",
+                            version, SCRIPT_VERSION
+                        ),
+                    ));
+                }
+                let base_dir = path.and_then(Path::parent).unwrap_or(Path::new(""));
+                let mut composition = Composition::resolve(&mut current, path, base_dir);
+                diagnostics.append(&mut composition.diagnostics);
+                // Later issues point into the composed script, so they're
+                // moved back to where their values come from
+                let mut found = Self::interpolate(&mut current, &options.variables);
+                for mut diagnostic in ScriptSchema::unknown_fields(&current) {
+                    if options.strict {
+                        diagnostic.severity = Severity::Error;
+                    }
+                    found.push(diagnostic);
+                }
+                let script = match serde_path_to_error::deserialize(&current) {
+                    Ok(script) => {
+                        found.extend(Self::diagnose(&script));
+                        Some(script)
+                    }
+                    Err(error) => {
+                        let pointer = json_pointer(error.path());
+                        let message = strip_position(&error.into_inner().to_string());
+                        found.push(Diagnostic::error("invalid-structure", pointer, message));
+                        None
+                    }
+                };
+                for mut diagnostic in found {
+                    diagnostic.pointer = composition.original_pointer(&diagnostic.pointer);
+                    diagnostics.push(diagnostic);
+                }
+                script
+            }
+        };
+
+        for diagnostic in &mut diagnostics {
+            diagnostic.locate(&source_map);
+        }
+
+        let script = script.filter(|_| !diagnostics.iter().any(Diagnostic::is_error));
+        ScriptCheck {
+            format,
+            source,
+            document: Some(document),
+            script,
+            diagnostics,
+        }
+    }
 
-        let script: VideoScript = serde_json::from_str(&content)
-            .with_context(|| format!("Failed to parse JSON script: {}", path.display()))?;
+    /// Upgrade a script document (or an included fragment) to `SCRIPT_VERSION`
+    /// and return the version it was written for. Values the upgrade drops are
+    /// added to `diagnostics` as warnings. Documents that aren't objects are
+    /// left as they are.
+    pub fn migrate(
+        document: &mut Value,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Result<u32, ParseError> {
+        let Value::Object(map) = document else {
+            return Ok(SCRIPT_VERSION);
+        };
+        let version = match map.get("version") {
+            None => 1,
+            Some(value) => value
+                .as_u64()
+                .filter(|version| (1..=SCRIPT_VERSION as u64).contains(version))
+                .ok_or_else(|| ParseError::UnsupportedVersion {
+                    version: value.to_string(),
+                })? as u32,
+        };
+        if version == SCRIPT_VERSION {
+            return Ok(version);
+        }
 
-        Self::validate_script(&script)?;
+        for migration in &MIGRATIONS[version as usize - 1..] {
+            migration(document, diagnostics);
+        }
+        if let Value::Object(map) = document {
+            if let Some(field) = map.get_mut("version") {
+                *field = SCRIPT_VERSION.into();
+            } else {
+                map.shift_insert(0, "version".into(), SCRIPT_VERSION.into());
+            }
+        }
+        Ok(version)
+    }
+
+    /// Replace the `{{name}}` placeholders in the strings of `document` with
+    /// its top-level `variables`, or with `overrides`, and remove `variables`.
+    /// A string that is only a placeholder takes the value as it is (e.g. a
+    /// color); elsewhere the value is written as text. Overrides of variables
+    /// the document doesn't declare are warned about.
+    pub fn interpolate(document: &mut Value, overrides: &Map<String, Value>) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        let Value::Object(map) = document else {
+            return diagnostics;
+        };
+        let mut variables = match map.shift_remove("variables") {
+            None => Map::new(),
+            Some(Value::Object(variables)) => variables,
+            Some(_) => {
+                diagnostics.push(Diagnostic::error(
+                    "invalid-variable",
+                    "/variables",
+                    "variables must map names to values",
+                ));
+                Map::new()
+            }
+        };
+        // Still set, the script may use them without a default
+        for name in overrides
+            .keys()
+            .filter(|name| !variables.contains_key(*name))
+        {
+            let message = match closest(name, variables.keys()) {
+                Some(known) => format!("unknown variable `{}`, did you mean `{}`?", name, known),
+                None => format!("unknown variable `{}`, the script doesn't declare it", name),
+            };
+            diagnostics.push(Diagnostic::warning(
+                "unknown-variable",
+                "/variables",
+                message,
+            ));
+        }
+        for (name, value) in overrides {
+            let value = match (variables.get(name), value) {
+                (Some(default), Value::String(text)) if !default.is_string() => {
+                    match serde_json::from_str::<Value>(text) {
+                        Ok(parsed) if kind(&parsed) == kind(default) => parsed,
+                        _ => {
+                            diagnostics.push(Diagnostic::error(
+                                "invalid-variable",
+                                format!("/variables/{}", escape_pointer(name)),
+                                format!(
+                                    "variable `{}` must be {} like its default, got \"{}\"",
+                                    name,
+                                    kind(default),
+                                    text
+                                ),
+                            ));
+                            continue;
+                        }
+                    }
+                }
+                _ => value.clone(),
+            };
+            variables.insert(name.clone(), value);
+        }
 
-        Ok(script)
+        let mut interpolator = Interpolator::new(&variables, true);
+        interpolator.diagnostics = diagnostics;
+        interpolator.value(document, "");
+        interpolator.diagnostics
     }
 
-    /// Validate the script structure
-    fn validate_script(script: &VideoScript) -> Result<()> {
+    /// Every issue of the script structure, in document order. Diagnostics are
+    /// not located in a file; `check_file` does that.
+    pub fn diagnose(script: &VideoScript) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+
         // Validate metadata
         if script.metadata.title.is_empty() {
-            anyhow::bail!("Script title cannot be empty");
+            diagnostics.push(Diagnostic::error(
+                "empty-title",
+                "/metadata/title",
+                "Script title cannot be empty",
+            ));
         }
 
         if script.metadata.fps == 0 {
-            anyhow::bail!("FPS must be greater than 0");
+            diagnostics.push(Diagnostic::error(
+                "invalid-fps",
+                "/metadata/fps",
+                "FPS must be greater than 0",
+            ));
         }
 
         if script.metadata.duration <= 0.0 {
-            anyhow::bail!("Duration must be positive");
+            diagnostics.push(Diagnostic::error(
+                "invalid-duration",
+                "/metadata/duration",
+                "Duration must be positive",
+            ));
         }
 
         // Validate scenes
         if script.scenes.is_empty() {
-            anyhow::bail!("Script must contain at least one scene");
+            diagnostics.push(Diagnostic::error(
+                "no-scenes",
+                "/scenes",
+                "Script must contain at least one scene",
+            ));
         }
 
         for (idx, scene) in script.scenes.iter().enumerate() {
+            let scene_pointer = format!("/scenes/{}", idx);
+            let scene_error = |code, field: &str, message: String| {
+                Diagnostic::error(code, format!("{}{}", scene_pointer, field), message)
+                    .in_scene(&scene.id, None)
+            };
+
             if scene.id.is_empty() {
-                anyhow::bail!("Scene {} has empty ID", idx);
+                diagnostics.push(Diagnostic::error(
+                    "empty-scene-id",
+                    format!("{}/id", scene_pointer),
+                    format!("Scene {} has empty ID", idx),
+                ));
             }
 
             if scene.duration <= 0.0 {
-                anyhow::bail!("Scene '{}' duration must be positive", scene.id);
+                diagnostics.push(scene_error(
+                    "invalid-scene-duration",
+                    "/duration",
+                    "duration must be positive".into(),
+                ));
             }
 
             if scene.layers.is_empty() {
-                anyhow::bail!("Scene '{}' must have at least one layer", scene.id);
+                diagnostics.push(scene_error(
+                    "empty-scene",
+                    "/layers",
+                    "must have at least one layer".into(),
+                ));
+            }
+
+            if let Some(transition) = &scene.transition {
+                let duration = transition.duration();
+                let duration_pointer = format!("/transition/{}/duration", transition.kind());
+                if duration < 0.0 {
+                    diagnostics.push(scene_error(
+                        "invalid-transition",
+                        &duration_pointer,
+                        "transition duration must not be negative".into(),
+                    ));
+                }
+
+                // The transition overlaps the end of this scene with the start of the next
+                if let Some(next) = script.scenes.get(idx + 1) {
+                    if duration > scene.duration || duration > next.duration {
+                        diagnostics.push(scene_error(
+                            "transition-too-long",
+                            &duration_pointer,
+                            format!(
+                                "transition ({:.2}s) is longer than the scenes it overlaps",
+                                duration
+                            ),
+                        ));
+                    }
+                }
+            }
+
+            for (layer_idx, layer) in scene.layers.iter().enumerate() {
+                let layer_pointer = format!("{}/layers/{}", scene_pointer, layer_idx);
+                let layer_error = |code, field: &str, message: String| {
+                    Diagnostic::error(code, format!("{}{}", layer_pointer, field), message)
+                        .in_scene(&scene.id, Some(layer_idx))
+                };
+
+                if let Layer::Video { playback, .. } = layer {
+                    // Point at the first offending option
+                    let field = if playback.trim_in < 0.0 {
+                        Some("/trim_in")
+                    } else if playback.trim_out.is_some_and(|out| out <= playback.trim_in) {
+                        Some("/trim_out")
+                    } else if playback.playback_rate <= 0.0 {
+                        Some("/playback_rate")
+                    } else if playback.volume < 0.0 {
+                        Some("/volume")
+                    } else {
+                        None
+                    };
+                    if let Some(field) = field {
+                        diagnostics.push(layer_error(
+                            "invalid-playback",
+                            field,
+                            "invalid video playback options".into(),
+                        ));
+                    }
+                }
+
+                for (effect_idx, effect) in layer.effects().iter().enumerate() {
+                    let invalid = match effect {
+                        Effect::FadeIn { duration } | Effect::FadeOut { duration } => {
+                            *duration < 0.0
+                        }
+                        Effect::Blur { radius } => *radius < 0.0,
+                        Effect::ColorGrade(grade) => grade.contrast < 0.0 || grade.saturation < 0.0,
+                    };
+                    if invalid {
+                        diagnostics.push(layer_error(
+                            "invalid-effect",
+                            &format!("/effects/{}/{}", effect_idx, effect.kind()),
+                            format!("invalid effect parameters {:?}", effect),
+                        ));
+                    }
+                }
+
+                for (property, times) in layer.animation().tracks() {
+                    let keyframe = |idx: usize| format!("/animation/{}/{}/time", property, idx);
+                    if let Some(idx) = times.iter().position(|t| *t < 0.0) {
+                        diagnostics.push(layer_error(
+                            "negative-keyframe-time",
+                            &keyframe(idx),
+                            format!("{} keyframe times must not be negative", property),
+                        ));
+                    }
+
+                    if let Some(idx) = times.windows(2).position(|pair| pair[1] < pair[0]) {
+                        diagnostics.push(layer_error(
+                            "unsorted-keyframes",
+                            &keyframe(idx + 1),
+                            format!("{} keyframes must be sorted by time", property),
+                        ));
+                    }
+                }
             }
         }
 
-        // Validate total duration matches scenes
-        let total_scene_duration: f32 = script.scenes.iter().map(|s| s.duration).sum();
+        // Validate total duration matches scenes (minus the transition overlaps)
+        let scene_count = script.scenes.len();
+        let total_scene_duration: f32 = script
+            .scenes
+            .iter()
+            .enumerate()
+            .map(|(idx, s)| match &s.transition {
+                Some(transition) if idx + 1 < scene_count => s.duration - transition.duration(),
+                _ => s.duration,
+            })
+            .sum();
         let duration_diff = (total_scene_duration - script.metadata.duration).abs();
 
         if duration_diff > 0.1 {
-            eprintln!(
-                "Warning: Total scene duration ({:.2}s) differs from metadata duration ({:.2}s)",
-                total_scene_duration, script.metadata.duration
-            );
+            diagnostics.push(Diagnostic::warning(
+                "duration-mismatch",
+                "/metadata/duration",
+                format!(
+                    "Total scene duration ({:.2}s) differs from metadata duration ({:.2}s)",
+                    total_scene_duration, script.metadata.duration
+                ),
+            ));
         }
 
-        Ok(())
+        diagnostics
     }
 
     /// Get a summary of the script structure
@@ -98,12 +523,289 @@ impl ScriptParser {
     }
 }
 
+/// JSON pointer of a serde path, e.g. `/scenes/0/layers`
+fn json_pointer(path: &serde_path_to_error::Path) -> String {
+    use serde_path_to_error::Segment;
+    path.iter()
+        .filter_map(|segment| match segment {
+            Segment::Seq { index } => Some(index.to_string()),
+            Segment::Map { key } | Segment::Enum { variant: key } => Some(escape_pointer(key)),
+            Segment::Unknown => None,
+        })
+        .map(|segment| format!("/{}", segment))
+        .collect()
+}
+
+/// Fills in `{{name}}` placeholders, reporting the ones without a value
+struct Interpolator<'a> {
+    variables: &'a Map<String, Value>,
+    placeholder: Regex,
+    /// Whether placeholders without a value are errors, or left for later
+    complete: bool,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Interpolator<'a> {
+    fn new(variables: &'a Map<String, Value>, complete: bool) -> Self {
+        Self {
+            variables,
+            placeholder: Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_-]*)\s*\}\}").unwrap(),
+            complete,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    fn value(&mut self, value: &mut Value, pointer: &str) {
+        match value {
+            Value::Array(items) => {
+                for (index, item) in items.iter_mut().enumerate() {
+                    self.value(item, &format!("{}/{}", pointer, index));
+                }
+            }
+            Value::Object(map) => {
+                for (key, item) in map.iter_mut() {
+                    self.value(item, &format!("{}/{}", pointer, escape_pointer(key)));
+                }
+            }
+            Value::String(text) => {
+                if let Some(whole) = self.whole_placeholder(text) {
+                    if let Some(variable) = self.lookup(&whole, pointer) {
+                        *value = variable.clone();
+                    }
+                    return;
+                }
+                let placeholder = self.placeholder.clone();
+                let replaced = placeholder.replace_all(text, |captures: &Captures| {
+                    match self.lookup(&captures[1], pointer) {
+                        Some(Value::String(variable)) => variable.clone(),
+                        Some(variable @ (Value::Number(_) | Value::Bool(_))) => {
+                            variable.to_string()
+                        }
+                        Some(variable) => {
+                            let message = format!(
+                                "variable `{}` is {} and can't be part of a text",
+                                &captures[1],
+                                kind(variable)
+                            );
+                            self.diagnostics.push(Diagnostic::error(
+                                "invalid-variable",
+                                pointer,
+                                message,
+                            ));
+                            captures[0].to_string()
+                        }
+                        None => captures[0].to_string(),
+                    }
+                });
+                if let std::borrow::Cow::Owned(replaced) = replaced {
+                    *text = replaced;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Name of the variable if `text` is nothing but a placeholder
+    fn whole_placeholder(&self, text: &str) -> Option<String> {
+        self.placeholder
+            .captures(text)
+            .filter(|captures| captures[0].len() == text.len())
+            .map(|captures| captures[1].to_string())
+    }
+
+    fn lookup(&mut self, name: &str, pointer: &str) -> Option<&Value> {
+        let variable = self.variables.get(name);
+        if variable.is_none() && self.complete {
+            let message = match closest(name, self.variables.keys()) {
+                Some(known) => format!("undefined variable `{}`, did you mean `{}`?", name, known),
+                None => format!("undefined variable `{}`", name),
+            };
+            self.diagnostics
+                .push(Diagnostic::error("undefined-variable", pointer, message));
+        }
+        variable
+    }
+}
+
+/// Fill in the placeholders of `value` (found at `pointer`) that `variables`
+/// have a value for, leaving the others for the script's variables
+pub(crate) fn substitute(
+    value: &mut Value,
+    pointer: &str,
+    variables: &Map<String, Value>,
+) -> Vec<Diagnostic> {
+    let mut interpolator = Interpolator::new(variables, false);
+    interpolator.value(value, pointer);
+    interpolator.diagnostics
+}
+
+/// Kind of a JSON value, with its article (`a number`)
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "a list",
+        Value::Object(_) => "a map",
+    }
+}
+
+/// Items of the `key` array of `value`
+fn items_mut<'a>(value: &'a mut Value, key: &str) -> impl Iterator<Item = &'a mut Value> {
+    value
+        .get_mut(key)
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+}
+
+/// Version 2: effects take parameters, and scenes overlap during transitions
+fn version_2(document: &mut Value, diagnostics: &mut Vec<Diagnostic>) {
+    parameterized_effects(document, diagnostics);
+    overlapping_transitions(document);
+}
+
+/// Scenes used to play one after the other, with transitions inside the
+/// outgoing scene. The next scene now starts while a transition plays, so the
+/// script gets shorter by each overlap (at most as long as either scene, as in
+/// the timeline).
+fn overlapping_transitions(document: &mut Value) {
+    let duration = |scene: &Value| scene.get("duration").and_then(Value::as_f64);
+    let scenes = document
+        .get("scenes")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let overlap: f64 = scenes
+        .windows(2)
+        .filter_map(|pair| {
+            let transition = pair[0].get("transition")?.as_object()?.values().next()?;
+            let length = transition.get("duration")?.as_f64()?;
+            Some(length.min(duration(&pair[0])?).min(duration(&pair[1])?))
+        })
+        .sum();
+    if overlap <= 0.0 {
+        return;
+    }
+    if let Some(total) = document.pointer_mut("/metadata/duration") {
+        if let Some(seconds) = total.as_f64() {
+            // Rounded so 10.0 - 0.3 is written as 9.7
+            *total = (((seconds - overlap).max(0.0) * 1e6).round() / 1e6).into();
+        }
+    }
+}
+
+/// Version 2: fades take a duration (`"fade_in"` becomes `{"fade_in": {}}`),
+/// color grades take parameters instead of the never used `adjustment`, and
+/// wipe directions are lowercase names
+fn parameterized_effects(document: &mut Value, diagnostics: &mut Vec<Diagnostic>) {
+    for (pointer, scene) in scenes_mut(document) {
+        if let Some(direction) = scene.pointer_mut("/transition/wipe/direction") {
+            if let Some(name) = direction.as_str() {
+                *direction = name.to_lowercase().into();
+            }
+        }
+        for (index, layer) in items_mut(scene, "layers").enumerate() {
+            let pointer = format!("{}/layers/{}", pointer, index);
+            layer_effects(layer, &pointer, diagnostics);
+        }
+    }
+    for (pointer, layer) in defined_layers_mut(document) {
+        layer_effects(layer, &pointer, diagnostics);
+    }
+}
+
+fn layer_effects(layer: &mut Value, pointer: &str, diagnostics: &mut Vec<Diagnostic>) {
+    for (index, effect) in items_mut(layer, "effects").enumerate() {
+        if let Some(name @ ("fade_in" | "fade_out")) = effect.as_str() {
+            *effect = serde_json::json!({ name: {} });
+        }
+        if let Some(Value::Object(grade)) = effect.get_mut("color_grade") {
+            // It never did anything, but say what's gone in case it was meant to
+            if let Some(adjustment) = grade.shift_remove("adjustment") {
+                diagnostics.push(Diagnostic::warning(
+                    "dropped-adjustment",
+                    format!("{}/effects/{}/color_grade/adjustment", pointer, index),
+                    format!(
+                        "color grade adjustment {} is dropped, set `exposure`, `contrast`, \
+                         `saturation` or `temperature` instead",
+                        adjustment
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+/// The scenes of a document, and its scene definitions, by pointer
+fn scenes_mut(document: &mut Value) -> Vec<(String, &mut Value)> {
+    let Value::Object(map) = document else {
+        return Vec::new();
+    };
+    let mut scenes = Vec::new();
+    for (key, value) in map.iter_mut() {
+        match (key.as_str(), value) {
+            ("scenes", Value::Array(items)) => scenes.extend(
+                items
+                    .iter_mut()
+                    .enumerate()
+                    .map(|(index, scene)| (format!("/scenes/{}", index), scene)),
+            ),
+            ("definitions", definitions) => {
+                if let Some(Value::Object(named)) = definitions.get_mut("scenes") {
+                    scenes.extend(named.iter_mut().map(|(name, scene)| {
+                        let pointer = format!("/definitions/scenes/{}", escape_pointer(name));
+                        (pointer, scene)
+                    }));
+                }
+            }
+            _ => {}
+        }
+    }
+    scenes
+}
+
+/// The layers of the layer definitions of a document, given as a list or on
+/// their own, by pointer
+fn defined_layers_mut(document: &mut Value) -> Vec<(String, &mut Value)> {
+    let Some(Value::Object(named)) = document.pointer_mut("/definitions/layers") else {
+        return Vec::new();
+    };
+    let mut layers = Vec::new();
+    for (name, group) in named.iter_mut() {
+        let pointer = format!("/definitions/layers/{}", escape_pointer(name));
+        match group {
+            Value::Array(items) => layers.extend(
+                items
+                    .iter_mut()
+                    .enumerate()
+                    .map(|(index, layer)| (format!("{}/{}", pointer, index), layer)),
+            ),
+            layer => layers.push((pointer, layer)),
+        }
+    }
+    layers
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::diagnostics::Severity;
     use std::io::Write;
     use tempfile::NamedTempFile;
 
+    /// First validation error of the script
+    fn validate_script(script: &VideoScript) -> Result<(), ParseError> {
+        match ScriptParser::diagnose(script)
+            .into_iter()
+            .find(Diagnostic::is_error)
+        {
+            Some(error) => Err(error.into()),
+            None => Ok(()),
+        }
+    }
+
     #[test]
     fn test_parse_valid_json() {
         let json = r#"
@@ -197,7 +899,7 @@ mod tests {
             "scenes": [{"id": "s1", "duration": 5.0, "layers": []}]
         }"#;
         let script: VideoScript = serde_json::from_str(json).unwrap();
-        let result = ScriptParser::validate_script(&script);
+        let result = validate_script(&script);
         assert!(result.is_err());
         assert!(result
             .unwrap_err()
@@ -205,6 +907,85 @@ mod tests {
             .contains("must have at least one layer"));
     }
 
+    #[test]
+    fn test_validate_script_unsorted_keyframes() {
+        let json = r#"{
+            "metadata": {"title": "Test", "resolution": "1920x1080", "fps": 30, "duration": 5.0},
+            "scenes": [{"id": "s1", "duration": 5.0, "layers": [{
+                "type": "image",
+                "source": "t.png",
+                "animation": {"opacity": [{"time": 1.0, "value": 1.0}, {"time": 0.5, "value": 0.0}]}
+            }]}]
+        }"#;
+        let script: VideoScript = serde_json::from_str(json).unwrap();
+        let result = validate_script(&script);
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("opacity keyframes must be sorted by time"));
+    }
+
+    #[test]
+    fn test_validate_script_negative_blur() {
+        let json = r#"{
+            "metadata": {"title": "Test", "resolution": "1920x1080", "fps": 30, "duration": 5.0},
+            "scenes": [{"id": "s1", "duration": 5.0, "layers": [{
+                "type": "image",
+                "source": "t.png",
+                "effects": [{"blur": {"radius": -2.0}}]
+            }]}]
+        }"#;
+        let script: VideoScript = serde_json::from_str(json).unwrap();
+        let result = validate_script(&script);
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("invalid effect parameters"));
+    }
+
+    #[test]
+    fn test_validate_script_transition_too_long() {
+        let json = r#"{
+            "metadata": {"title": "Test", "resolution": "1920x1080", "fps": 30, "duration": 3.0},
+            "scenes": [
+                {"id": "s1", "duration": 2.0, "layers": [{"type": "image", "source": "t.png"}],
+                 "transition": {"wipe": {"duration": 1.5, "direction": "right"}}},
+                {"id": "s2", "duration": 1.0, "layers": [{"type": "image", "source": "t.png"}]}
+            ]
+        }"#;
+        let script: VideoScript = serde_json::from_str(json).unwrap();
+        let result = validate_script(&script);
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("longer than the scenes it overlaps"));
+    }
+
+    #[test]
+    fn test_validate_script_video_trim() {
+        let json = r#"{
+            "metadata": {"title": "Test", "resolution": "1920x1080", "fps": 30, "duration": 5.0},
+            "scenes": [{"id": "s1", "duration": 5.0, "layers": [{
+                "type": "video",
+                "source": "clip.mp4",
+                "trim_in": 4.0,
+                "trim_out": 2.0
+            }]}]
+        }"#;
+        let script: VideoScript = serde_json::from_str(json).unwrap();
+        let result = validate_script(&script);
+        assert!(result.is_err());
+        let error = result.unwrap_err();
+        assert!(error.to_string().contains("invalid video playback options"));
+        assert!(matches!(
+            error,
+            ParseError::Invalid { scene: Some(ref id), layer: Some(0), .. } if id == "s1"
+        ));
+    }
+
     #[test]
     fn test_validate_script_duration_mismatch() {
         // Test duration warning (should succeed but log warning)
@@ -213,9 +994,408 @@ mod tests {
             "scenes": [{"id": "s1", "duration": 5.0, "layers": [{"type": "image", "source": "t.png"}]}]
         }"#;
         let script: VideoScript = serde_json::from_str(json).unwrap();
-        // Should succeed but print warning to stderr
-        let result = ScriptParser::validate_script(&script);
+        // Should succeed with a warning
+        let result = validate_script(&script);
         assert!(result.is_ok());
+        let diagnostics = ScriptParser::diagnose(&script);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(diagnostics[0].code, "duration-mismatch");
+    }
+
+    #[test]
+    fn test_migrate() {
+        let mut document = serde_json::json!({
+            "metadata": {"title": "Old", "resolution": "1280x720", "fps": 30, "duration": 2.0},
+            "scenes": [{
+                "id": "intro",
+                "duration": 2.0,
+                "layers": [{
+                    "type": "image",
+                    "source": "bg.png",
+                    "effects": ["fade_in", {"color_grade": {"adjustment": "warm"}}]
+                }],
+                "transition": {"wipe": {"duration": 0.5, "direction": "Left"}}
+            }]
+        });
+        let mut dropped = Vec::new();
+        assert_eq!(
+            ScriptParser::migrate(&mut document, &mut dropped).unwrap(),
+            1
+        );
+        let found: Vec<_> = dropped
+            .iter()
+            .map(|d| (d.severity, d.code, d.pointer.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![(
+                Severity::Warning,
+                "dropped-adjustment",
+                "/scenes/0/layers/0/effects/1/color_grade/adjustment"
+            )]
+        );
+        assert!(dropped[0]
+            .message
+            .starts_with("color grade adjustment \"warm\" is dropped"));
+        assert_eq!(
+            document.as_object().unwrap().keys().next().unwrap(),
+            "version"
+        );
+        assert_eq!(document["version"], SCRIPT_VERSION);
+        assert_eq!(
+            document["scenes"][0]["layers"][0]["effects"],
+            serde_json::json!([{"fade_in": {}}, {"color_grade": {}}])
+        );
+        assert_eq!(
+            document.pointer("/scenes/0/transition/wipe/direction"),
+            Some(&Value::from("left"))
+        );
+        let script: VideoScript = serde_json::from_value(document.clone()).unwrap();
+        assert_eq!(
+            script.scenes[0].layers[0].effects()[0],
+            Effect::FadeIn { duration: 0.5 }
+        );
+
+        // Transitions overlap the scenes around them, which shortens the script
+        let mut document = serde_json::json!({
+            "metadata": {"duration": 10.0},
+            "scenes": [
+                {"duration": 3.0, "transition": {"fade": {"duration": 0.5}}},
+                {"duration": 5.0, "transition": "cut"},
+                {"duration": 0.2, "transition": {"dissolve": {"duration": 1.0}}},
+                {"duration": 1.8}
+            ]
+        });
+        ScriptParser::migrate(&mut document, &mut Vec::new()).unwrap();
+        assert_eq!(document["metadata"]["duration"], 9.3);
+
+        // Definitions are upgraded too
+        let mut definitions = serde_json::json!({"definitions": {
+            "scenes": {"outro": {"transition": {"wipe": {"direction": "Right"}}}},
+            "layers": {"logo": {"type": "image", "effects": ["fade_out"]}}
+        }});
+        ScriptParser::migrate(&mut definitions, &mut Vec::new()).unwrap();
+        assert_eq!(
+            definitions["definitions"]["scenes"]["outro"]["transition"]["wipe"]["direction"],
+            "right"
+        );
+        assert_eq!(
+            definitions["definitions"]["layers"]["logo"]["effects"],
+            serde_json::json!([{"fade_out": {}}])
+        );
+
+        // Current documents are left alone, newer ones are refused
+        let migrated = document.clone();
+        assert_eq!(
+            ScriptParser::migrate(&mut document, &mut Vec::new()).unwrap(),
+            SCRIPT_VERSION
+        );
+        assert_eq!(document, migrated);
+        document["version"] = (SCRIPT_VERSION + 1).into();
+        let error = ScriptParser::migrate(&mut document, &mut Vec::new()).unwrap_err();
+        assert!(matches!(error, ParseError::UnsupportedVersion { .. }));
+
+        // Old scripts still load, with a reminder to upgrade them
+        let check = ScriptParser::check_source(
+            r#"{"metadata": {"title": "T", "resolution": "1920x1080", "fps": 30, "duration": 1.0},
+                "scenes": [{"id": "s", "duration": 1.0, "layers": [
+                    {"type": "image", "source": "a.png", "effects": ["fade_out"]}]}]}"#
+                .to_string(),
+            &ParseOptions::default(),
+        );
+        assert!(check.script.is_some());
+        assert_eq!(check.diagnostics[0].code, "outdated-version");
+        assert_eq!(check.diagnostics[0].line, Some(1));
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let json = r#"{
+  "version": 2,
+  "variables": {"product": "Widget", "brand": {"r": 255, "g": 80, "b": 0}, "size": 48},
+  "metadata": {"title": "{{product}} explained", "resolution": "1920x1080", "fps": 30, "duration": 1.0},
+  "scenes": [{"id": "intro", "duration": 1.0, "layers": [
+    {"type": "text", "content": "Meet {{ product }} ({{size}}px)", "font": "assets/{{product}}.ttf",
+     "font_size": "{{size}}", "color": "{{brand}}"}
+  ]}]
+}"#;
+        let check = ScriptParser::check_source(json.to_string(), &ParseOptions::default());
+        let script = check.script.unwrap();
+        assert_eq!(script.metadata.title, "Widget explained");
+        let Layer::Text {
+            content,
+            font,
+            font_size,
+            color,
+            ..
+        } = &script.scenes[0].layers[0]
+        else {
+            panic!("Expected a text layer");
+        };
+        assert_eq!(content, "Meet Widget (48px)");
+        assert_eq!(font, Path::new("assets/Widget.ttf"));
+        assert_eq!(*font_size, 48.0);
+        assert_eq!((color.r, color.g, color.b), (255, 80, 0));
+        // The file is kept as written
+        assert_eq!(check.document.unwrap()["variables"]["size"], 48);
+
+        // Overrides from the command line are read as the type of the default
+        let options = ParseOptions {
+            variables: serde_json::json!({"product": "Gadget", "size": "64"})
+                .as_object()
+                .unwrap()
+                .clone(),
+            ..Default::default()
+        };
+        let script = ScriptParser::check_source(json.to_string(), &options)
+            .script
+            .unwrap();
+        assert_eq!(script.metadata.title, "Gadget explained");
+        let Layer::Text { font_size, .. } = &script.scenes[0].layers[0] else {
+            panic!("Expected a text layer");
+        };
+        assert_eq!(*font_size, 64.0);
+
+        let mut document = serde_json::json!({
+            "variables": {"product": "Widget", "size": 48},
+            "metadata": {"title": "{{prodcut}}"},
+            "scenes": [{"id": "{{brand}}"}]
+        });
+        let overrides = serde_json::json!({"size": "big"});
+        let diagnostics = ScriptParser::interpolate(&mut document, overrides.as_object().unwrap());
+        let found: Vec<_> = diagnostics
+            .iter()
+            .map(|d| (d.code, d.pointer.as_str(), d.message.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "invalid-variable",
+                    "/variables/size",
+                    "variable `size` must be a number like its default, got \"big\""
+                ),
+                (
+                    "undefined-variable",
+                    "/metadata/title",
+                    "undefined variable `prodcut`, did you mean `product`?"
+                ),
+                (
+                    "undefined-variable",
+                    "/scenes/0/id",
+                    "undefined variable `brand`"
+                ),
+            ]
+        );
+        assert!(document.get("variables").is_none());
+
+        // Overrides of undeclared variables are still used, with a warning
+        let mut document = serde_json::json!({
+            "variables": {"product": "Widget"},
+            "metadata": {"title": "{{product}} by {{brand}}"}
+        });
+        let overrides = serde_json::json!({"prodcut": "Gadget", "brand": "Acme"});
+        let diagnostics = ScriptParser::interpolate(&mut document, overrides.as_object().unwrap());
+        let found: Vec<_> = diagnostics
+            .iter()
+            .map(|d| (d.severity, d.code, d.message.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    Severity::Warning,
+                    "unknown-variable",
+                    "unknown variable `prodcut`, did you mean `product`?"
+                ),
+                (
+                    Severity::Warning,
+                    "unknown-variable",
+                    "unknown variable `brand`, the script doesn't declare it"
+                ),
+            ]
+        );
+        assert_eq!(document["metadata"]["title"], "Widget by Acme");
+    }
+
+    #[test]
+    fn test_unknown_fields() {
+        let json = r#"{
+  "version": 2,
+  "metadata": {"title": "T", "resolution": "1920x1080", "fps": 30, "duration": 1.0},
+  "scenes": [{"id": "s", "duration": 1.0, "scene_typ": "hook", "layers": [
+    {"type": "image", "source": "a.png"}
+  ]}]
+}"#;
+        let check = ScriptParser::check_source(json.to_string(), &ParseOptions::default());
+        assert!(check.script.is_some());
+        assert_eq!(check.warnings(), 1);
+
+        let strict = ParseOptions {
+            strict: true,
+            ..Default::default()
+        };
+        let check = ScriptParser::check_source(json.to_string(), &strict);
+        assert!(check.script.is_none());
+        let diagnostic = &check.diagnostics[0];
+        assert_eq!(diagnostic.code, "unknown-field");
+        assert_eq!(diagnostic.pointer, "/scenes/0/scene_typ");
+        assert_eq!(
+            diagnostic.message,
+            "unknown field `scene_typ`, did you mean `scene_type`?"
+        );
+        assert_eq!((diagnostic.line, diagnostic.column), (Some(4), Some(56)));
+    }
+
+    #[test]
+    fn test_check_source_collects_located_diagnostics() {
+        let json = r#"{
+  "version": 2,
+  "metadata": {"title": "", "resolution": "1920x1080", "fps": 30, "duration": 5.0},
+  "scenes": [
+    {"id": "s1", "duration": 5.0, "layers": []},
+    {"id": "s2", "duration": 1.0, "layers": [{
+      "type": "video",
+      "source": "clip.mp4",
+      "trim_in": 4.0,
+      "trim_out": 2.0
+    }]}
+  ]
+}"#;
+        let check = ScriptParser::check_source(json.to_string(), &ParseOptions::default());
+        assert!(check.script.is_none());
+        let found: Vec<_> = check
+            .diagnostics
+            .iter()
+            .map(|d| (d.code, d.pointer.as_str(), d.line, d.column))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("empty-title", "/metadata/title", Some(3), Some(25)),
+                ("empty-scene", "/scenes/0/layers", Some(5), Some(45)),
+                (
+                    "invalid-playback",
+                    "/scenes/1/layers/0/trim_out",
+                    Some(10),
+                    Some(19)
+                ),
+                ("duration-mismatch", "/metadata/duration", Some(3), Some(79)),
+            ]
+        );
+        assert_eq!((check.errors(), check.warnings()), (3, 1));
+        assert_eq!(check.diagnostics[2].layer, Some(0));
+
+        // Scripts that don't deserialize are reported where serde stopped
+        let check = ScriptParser::check_source(
+            r#"{"version": 2, "metadata": {"title": "T", "resolution": "1920x1080", "fps": "30", "duration": 5.0}, "scenes": []}"#
+                .to_string(),
+            &ParseOptions::default(),
+        );
+        let diagnostic = &check.diagnostics[0];
+        assert_eq!(diagnostic.code, "invalid-structure");
+        assert_eq!(diagnostic.pointer, "/metadata/fps");
+        assert_eq!(diagnostic.line, Some(1));
+        assert_eq!(diagnostic.column, Some(77));
+        assert!(!diagnostic.message.contains("at line"));
+    }
+
+    #[test]
+    fn test_check_source_composes_definitions() {
+        let json = r#"{
+  "version": 2,
+  "definitions": {
+    "scenes": {"cta": {"id": "cta", "duration": 2.0, "layers": [{"use": "button"}]}},
+    "layers": {"button": {"type": "text", "content": "{{label}}", "font": "f.ttf", "font_size": 24.0,
+      "color": {"r": 0, "g": 0, "b": 0}, "trnsform": {}}}
+  },
+  "metadata": {"title": "T", "resolution": "640x360", "fps": 30, "duration": 2.0},
+  "scenes": [{"use": "cta", "with": {"label": "Buy"}}]
+}"#;
+        let check = ScriptParser::check_source(json.to_string(), &ParseOptions::default());
+        let script = check.script.as_ref().unwrap();
+        assert_eq!(script.scenes[0].id, "cta");
+        assert!(matches!(
+            &script.scenes[0].layers[0],
+            Layer::Text { content, .. } if content == "Buy"
+        ));
+        // The document keeps the definitions for converting and migrating
+        assert!(check.document.as_ref().unwrap()["definitions"].is_object());
+
+        // Issues are located where the values are written: in the used
+        // definition, or next to `use` for fields it replaces
+        let warning = &check.diagnostics[0];
+        assert_eq!(warning.pointer, "/definitions/layers/button/trnsform");
+        assert_eq!((warning.line, warning.column), (Some(6), Some(54)));
+        let json = json.replace(
+            r#""with": {"label": "Buy"}}"#,
+            r#""with": {"label": "Buy"}, "layers": []}"#,
+        );
+        let check = ScriptParser::check_source(json, &ParseOptions::default());
+        let found: Vec<_> = check
+            .diagnostics
+            .iter()
+            .map(|d| (d.code, d.pointer.as_str(), d.line))
+            .collect();
+        assert_eq!(found, vec![("empty-scene", "/scenes/0/layers", Some(9))]);
+    }
+
+    #[test]
+    fn test_parse_file_formats() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let yaml = dir.path().join("script.yaml");
+        std::fs::write(
+            &yaml,
+            "# Written by hand\n\
+             metadata: {title: Test, resolution: 1920x1080, fps: 30, duration: 5.0}\n\
+             scenes:\n\
+             \x20 - id: s1\n\
+             \x20   duration: 5.0\n\
+             \x20   layers: [{type: image, source: t.png}]\n",
+        )
+        .unwrap();
+        let script = ScriptParser::parse_file(&yaml, &ParseOptions::default()).unwrap();
+        assert_eq!(script.scenes[0].id, "s1");
+
+        // The same text isn't JSON
+        let error = ScriptParser::parse_file(
+            &yaml,
+            &ParseOptions {
+                format: Some(ScriptFormat::Json),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            ParseError::Syntax {
+                format: ScriptFormat::Json,
+                line: Some(1),
+                ..
+            }
+        ));
+
+        let toml = dir.path().join("script.toml");
+        std::fs::write(
+            &toml,
+            r#"
+[metadata]
+title = "Test"
+resolution = "1920x1080"
+fps = "30"
+duration = 5.0
+"#,
+        )
+        .unwrap();
+        let error = ScriptParser::parse_file(&toml, &ParseOptions::default()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Failed to parse TOML script {}:5:7: invalid type: string \"30\", expected u32 (at /metadata/fps)",
+                toml.display()
+            )
+        );
     }
 
     #[test]
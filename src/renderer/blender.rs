@@ -1,9 +1,15 @@
+use crate::assets::AssetLoader;
+use crate::error::{self, BlenderError};
+use crate::events::{ConsoleObserver, FrameProgress, SharedObserver};
+use crate::renderer::manifest::MANIFEST_FILE;
+use crate::renderer::{Capabilities, FrameFormat, RenderManifest, Renderer, SceneCache, Timeline};
 use crate::script::{Layer, VideoScript};
-use anyhow::{Context, Result};
+use anyhow::Result;
 use sha2::{Digest, Sha256};
 use std::fs;
 use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::sync::{Arc, Mutex};
 use std::thread;
@@ -15,9 +21,21 @@ pub struct BlenderRenderer {
     output_dir: PathBuf,
     cache_dir: PathBuf,
     parallel_jobs: usize,
+    /// Reuse frames of unchanged scenes from earlier renders, cached here
+    scene_cache_dir: Option<PathBuf>,
+    observer: SharedObserver,
 }
 
 impl BlenderRenderer {
+    /// Image and text layers with their animation; scenes cut into each other
+    pub const CAPABILITIES: Capabilities = Capabilities {
+        layers: &["image", "text"],
+        effects: &[],
+        transitions: &["cut"],
+        transparency: true,
+        streaming: false,
+    };
+
     pub fn new(script: VideoScript, output_dir: PathBuf) -> Self {
         let cache_dir = PathBuf::from(".cache/blender");
         let parallel_jobs = std::cmp::min(num_cpus::get(), 2).max(1);
@@ -26,9 +44,49 @@ impl BlenderRenderer {
             output_dir,
             cache_dir,
             parallel_jobs,
+            scene_cache_dir: None,
+            observer: Arc::new(ConsoleObserver),
         }
     }
 
+    /// Report progress to `observer` instead of the console
+    pub fn set_observer(&mut self, observer: SharedObserver) {
+        self.observer = observer;
+    }
+
+    /// Reuse frames of unchanged scenes, cached in `dir` (disabled by default)
+    pub fn set_scene_cache_dir(&mut self, dir: Option<PathBuf>) {
+        self.scene_cache_dir = dir;
+    }
+
+    /// Cache of rendered frames per scene. Blender animates the camera over the
+    /// whole timeline, so scenes are also keyed by their position on it.
+    fn scene_cache(&self) -> Result<Option<SceneCache>> {
+        let Some(dir) = &self.scene_cache_dir else {
+            return Ok(None);
+        };
+
+        let metadata = &self.script.metadata;
+        let (width, height) = metadata.resolution.dimensions();
+        let settings = format!(
+            "blender {} {}x{} {}fps {}s transparent={}",
+            env!("CARGO_PKG_VERSION"),
+            width,
+            height,
+            metadata.fps,
+            metadata.duration,
+            metadata.transparent
+        );
+        let cache = SceneCache::new(
+            &self.script,
+            Path::new("."),
+            dir,
+            &[settings.as_bytes()],
+            true,
+        )?;
+        Ok(Some(cache))
+    }
+
     /// Generate the Python script for Blender
     fn generate_python_script(&self, start_frame: u32, end_frame: u32) -> String {
         let mut py = String::new();
@@ -58,15 +116,23 @@ def create_image_material(name, image_path):
     nodes.clear()
 
     shader = nodes.new('ShaderNodeBsdfPrincipled')
+    shader.name = 'Shader'
     shader.inputs['Alpha'].default_value = 1.0
     
     tex = nodes.new('ShaderNodeTexImage')
     tex.image = img
 
+    # Texture alpha multiplied by the layer opacity
+    opacity = nodes.new('ShaderNodeMath')
+    opacity.name = 'Opacity'
+    opacity.operation = 'MULTIPLY'
+    opacity.inputs[1].default_value = 1.0
+
     out = nodes.new('ShaderNodeOutputMaterial')
 
     links.new(tex.outputs['Color'], shader.inputs['Base Color'])
-    links.new(tex.outputs['Alpha'], shader.inputs['Alpha'])
+    links.new(tex.outputs['Alpha'], opacity.inputs[0])
+    links.new(opacity.outputs['Value'], shader.inputs['Alpha'])
     links.new(shader.outputs['BSDF'], out.inputs['Surface'])
     
     mat.blend_method = 'BLEND'
@@ -80,12 +146,21 @@ def create_text_material(name, color):
     nodes.clear()
 
     shader = nodes.new('ShaderNodeBsdfPrincipled')
+    shader.name = 'Shader'
     shader.inputs['Base Color'].default_value = color
     # Add some emission so it pops
     shader.inputs['Emission Color'].default_value = color
     shader.inputs['Emission Strength'].default_value = 0.5
 
+    # Color alpha multiplied by the layer opacity
+    opacity = nodes.new('ShaderNodeMath')
+    opacity.name = 'Opacity'
+    opacity.operation = 'MULTIPLY'
+    opacity.inputs[0].default_value = color[3]
+    opacity.inputs[1].default_value = 1.0
+
     out = nodes.new('ShaderNodeOutputMaterial')
+    links.new(opacity.outputs['Value'], shader.inputs['Alpha'])
     links.new(shader.outputs['BSDF'], out.inputs['Surface'])
     
     mat.blend_method = 'BLEND'
@@ -148,6 +223,30 @@ def keyframe_visibility(obj, start_frame, end_frame):
     obj.keyframe_insert(data_path="hide_render", frame=end_frame)
     obj.keyframe_insert(data_path="hide_viewport", frame=end_frame)
 
+def keyframe_transform(obj, frame, bx, by, sx, sy, rotation):
+    obj.location.x = bx
+    obj.location.y = by
+    obj.scale.x = sx
+    obj.scale.y = sy
+    obj.rotation_euler.z = -math.radians(rotation)  # Clockwise on screen
+    obj.keyframe_insert(data_path="location", frame=frame)
+    obj.keyframe_insert(data_path="scale", frame=frame)
+    obj.keyframe_insert(data_path="rotation_euler", frame=frame)
+
+def keyframe_opacity(mat, frame, opacity):
+    socket = mat.node_tree.nodes['Opacity'].inputs[1]
+    socket.default_value = opacity
+    socket.keyframe_insert(data_path="default_value", frame=frame)
+
+def keyframe_color(mat, frame, color):
+    shader = mat.node_tree.nodes['Shader']
+    for name in ('Base Color', 'Emission Color'):
+        shader.inputs[name].default_value = color
+        shader.inputs[name].keyframe_insert(data_path="default_value", frame=frame)
+    alpha = mat.node_tree.nodes['Opacity'].inputs[0]
+    alpha.default_value = color[3]
+    alpha.keyframe_insert(data_path="default_value", frame=frame)
+
 def to_blender_coords(x, y, res_x, res_y):
     # Map 0,0 (top-left) to -W/2, H/2
     # Scale: 100px = 1 unit
@@ -184,6 +283,10 @@ def to_blender_coords(x, y, res_x, res_y):
 
         py.push_str("scene.render.image_settings.file_format = 'PNG'\n");
         py.push_str("scene.render.image_settings.color_mode = 'RGBA'\n"); // Ensure alpha output
+        if self.script.metadata.transparent {
+            // Render the world background as alpha instead of color
+            py.push_str("scene.render.film_transparent = True\n");
+        }
 
         // Camera setup
         py.push_str("\n# Camera setup\n");
@@ -204,12 +307,11 @@ def to_blender_coords(x, y, res_x, res_y):
         // Setup Compositor
         py.push_str("setup_compositor()\n");
 
-        // Process scenes and layers
-        let mut current_frame = 0;
-        for scene in &self.script.scenes {
-            let scene_duration_frames = (scene.duration * self.script.metadata.fps as f32) as u32;
-            let scene_end_frame = current_frame + scene_duration_frames;
-
+        // Process scenes and layers (scenes overlap while a transition plays)
+        let scene_ranges = Timeline::from_script(&self.script).scene_ranges();
+        for (scene, &(current_frame, scene_end_frame)) in
+            self.script.scenes.iter().zip(&scene_ranges)
+        {
             for (layer_idx, layer) in scene.layers.iter().enumerate() {
                 match layer {
                     Layer::Image {
@@ -249,6 +351,25 @@ def to_blender_coords(x, y, res_x, res_y):
                         py.push_str(&format!("    obj.scale.x *= {}\n", transform.scale));
                         py.push_str(&format!("    obj.scale.y *= {}\n", transform.scale));
 
+                        // Rotation and opacity
+                        py.push_str(&format!(
+                            "    obj.rotation_euler.z = -math.radians({})\n",
+                            transform.rotation
+                        ));
+                        py.push_str(&format!(
+                            "    mat.node_tree.nodes['Opacity'].inputs[1].default_value = {}\n",
+                            transform.opacity
+                        ));
+
+                        // Keyframed properties
+                        self.push_animation_keyframes(
+                            &mut py,
+                            "    ",
+                            layer,
+                            current_frame,
+                            scene_end_frame,
+                        );
+
                         // Visibility Keyframing
                         py.push_str(&format!(
                             "    keyframe_visibility(obj, {}, {})\n",
@@ -261,6 +382,7 @@ def to_blender_coords(x, y, res_x, res_y):
                         font_size,
                         color,
                         position,
+                        transform,
                         ..
                     } => {
                         let name = format!("Text_{}_{}", scene.id, layer_idx);
@@ -295,11 +417,37 @@ def to_blender_coords(x, y, res_x, res_y):
                         // For now, using same mapping.
                         py.push_str(&format!(
                             "bx, by = to_blender_coords({}, {}, {}, {})\n",
-                            position.x, position.y, width, height
+                            position.x + transform.position.x,
+                            position.y + transform.position.y,
+                            width,
+                            height
                         ));
                         py.push_str("obj.location.x = bx\n");
                         py.push_str("obj.location.y = by\n");
 
+                        // Scale, rotation and opacity
+                        py.push_str(&format!(
+                            "obj.scale.x = obj.scale.y = {}\n",
+                            transform.scale
+                        ));
+                        py.push_str(&format!(
+                            "obj.rotation_euler.z = -math.radians({})\n",
+                            transform.rotation
+                        ));
+                        py.push_str(&format!(
+                            "mat.node_tree.nodes['Opacity'].inputs[1].default_value = {}\n",
+                            transform.opacity
+                        ));
+
+                        // Keyframed properties
+                        self.push_animation_keyframes(
+                            &mut py,
+                            "",
+                            layer,
+                            current_frame,
+                            scene_end_frame,
+                        );
+
                         // Visibility Keyframing
                         py.push_str(&format!(
                             "keyframe_visibility(obj, {}, {})\n",
@@ -309,7 +457,6 @@ def to_blender_coords(x, y, res_x, res_y):
                     _ => {}
                 }
             }
-            current_frame += scene_duration_frames;
         }
 
         py.push_str("\n# Render animation\n");
@@ -318,6 +465,92 @@ def to_blender_coords(x, y, res_x, res_y):
         py
     }
 
+    /// Bake the layer's keyframe tracks into one Blender keyframe per frame of its scene.
+    ///
+    /// Expects `obj` and `mat` to be bound in the generated script (and `img_w`/`img_h`
+    /// for image layers).
+    fn push_animation_keyframes(
+        &self,
+        py: &mut String,
+        indent: &str,
+        layer: &Layer,
+        start_frame: u32,
+        end_frame: u32,
+    ) {
+        let animation = layer.animation();
+        if animation.is_empty() {
+            return;
+        }
+
+        let (width, height) = self.script.metadata.resolution.dimensions();
+        let fps = self.script.metadata.fps as f32;
+        let animates_transform = !animation.position.is_empty()
+            || !animation.scale.is_empty()
+            || !animation.rotation.is_empty();
+
+        for frame in start_frame..end_frame {
+            let time = (frame - start_frame) as f32 / fps;
+
+            let (x, y, size, transform, color) = match layer.at_time(time) {
+                Layer::Image { transform, .. } | Layer::Video { transform, .. } => (
+                    transform.position.x,
+                    transform.position.y,
+                    ("img_w / 100.0 * ", "img_h / 100.0 * "),
+                    transform,
+                    None,
+                ),
+                Layer::Text {
+                    position,
+                    transform,
+                    color,
+                    ..
+                } => (
+                    position.x + transform.position.x,
+                    position.y + transform.position.y,
+                    ("", ""),
+                    transform,
+                    Some(color),
+                ),
+            };
+
+            if animates_transform {
+                py.push_str(&format!(
+                    "{}bx, by = to_blender_coords({}, {}, {}, {})\n",
+                    indent, x, y, width, height
+                ));
+                py.push_str(&format!(
+                    "{}keyframe_transform(obj, {}, bx, by, {}{}, {}{}, {})\n",
+                    indent,
+                    frame,
+                    size.0,
+                    transform.scale,
+                    size.1,
+                    transform.scale,
+                    transform.rotation
+                ));
+            }
+
+            if !animation.opacity.is_empty() {
+                py.push_str(&format!(
+                    "{}keyframe_opacity(mat, {}, {})\n",
+                    indent, frame, transform.opacity
+                ));
+            }
+
+            if let (false, Some(color)) = (animation.color.is_empty(), color) {
+                py.push_str(&format!(
+                    "{}keyframe_color(mat, {}, ({}, {}, {}, {}))\n",
+                    indent,
+                    frame,
+                    color.r as f32 / 255.0,
+                    color.g as f32 / 255.0,
+                    color.b as f32 / 255.0,
+                    color.a as f32 / 255.0
+                ));
+            }
+        }
+    }
+
     /// Calculate hash of the generation logic/script
     fn calculate_hash(&self, python_script: &str) -> String {
         let mut hasher = Sha256::new();
@@ -326,43 +559,107 @@ def to_blender_coords(x, y, res_x, res_y):
     }
 
     /// Render the video using Blender
-    pub fn render(&self) -> Result<()> {
-        fs::create_dir_all(&self.cache_dir)?;
-        fs::create_dir_all(&self.output_dir)?;
+    pub fn render(&self) -> Result<(), BlenderError> {
+        let total_frames = (self.script.metadata.duration * self.script.metadata.fps as f32) as u32;
+        self.render_range(0..total_frames)
+    }
+
+    /// Image Blender writes for `frame`
+    pub fn frame_path(&self, frame: u32) -> PathBuf {
+        self.output_dir.join(self.frame_format().file_name(frame))
+    }
+
+    /// Render `frames` (written as `frame_####.png`, numbered by timeline frame).
+    ///
+    /// Frames Blender reports as saved are recorded in the output directory's
+    /// render manifest, so a rerun after a failed job only renders the missing ones.
+    pub fn render_range(&self, frames: Range<u32>) -> Result<(), BlenderError> {
+        for dir in [&self.cache_dir, &self.output_dir] {
+            fs::create_dir_all(dir).map_err(|e| output_error(dir, e.into()))?;
+        }
 
         let total_frames = (self.script.metadata.duration * self.script.metadata.fps as f32) as u32;
         let python_script = self.generate_python_script(0, total_frames);
         let script_hash = self.calculate_hash(&python_script);
-
         let cache_file = self.cache_dir.join(format!("{}.py", script_hash));
-        let hash_file = self.cache_dir.join("last_render.sha256");
-
-        // Check cache
-        if cache_file.exists() && hash_file.exists() {
-            let last_hash = fs::read_to_string(&hash_file).unwrap_or_default();
-            if last_hash.trim() == script_hash {
-                println!("✨ Cache hit! Skipping Blender rendering.");
-                return Ok(());
+
+        let manifest_path = self.output_dir.join(MANIFEST_FILE);
+        let mut manifest = RenderManifest::load(&self.output_dir, &script_hash);
+        if manifest.started_over() {
+            self.observer
+                .info("Script changed since the last render, starting over");
+        }
+
+        // Frames of scenes that didn't change come from the scene cache
+        let scene_cache = self
+            .scene_cache()
+            .map_err(|e| output_error(&self.cache_dir, e))?
+            .map(Arc::new);
+        if let Some(scene_cache) = &scene_cache {
+            let mut reused = 0;
+            let missing = manifest.missing_ranges(frames.clone(), |frame| self.frame_path(frame));
+            for frame in missing.into_iter().flatten() {
+                let path = self.frame_path(frame);
+                if scene_cache.restore_file(frame, &path) {
+                    manifest
+                        .mark_complete(frame, &path)
+                        .map_err(|e| output_error(&manifest_path, e))?;
+                    reused += 1;
+                }
+            }
+            if reused > 0 {
+                self.observer
+                    .info(format!("Reusing {} frames of unchanged scenes", reused));
+                manifest
+                    .save()
+                    .map_err(|e| output_error(&manifest_path, e))?;
             }
         }
 
-        println!("🎨 Starting Blender rendering...");
+        let missing = manifest.missing_ranges(frames.clone(), |frame| self.frame_path(frame));
+        let missing_frames: usize = missing.iter().map(|range| range.len()).sum();
+        if missing_frames == 0 {
+            self.observer.info("Cache hit! Skipping Blender rendering.");
+            return Ok(());
+        }
+        if missing_frames < frames.len() {
+            self.observer.info(format!(
+                "Resuming: {}/{} frames already rendered",
+                frames.len() - missing_frames,
+                frames.len()
+            ));
+        }
 
         // Write script to file
-        fs::write(&cache_file, &python_script)?;
+        fs::write(&cache_file, &python_script).map_err(|e| output_error(&cache_file, e.into()))?;
 
-        println!(
-            "🚀 Launching {} parallel Blender jobs...",
+        self.observer.info(format!(
+            "Launching {} parallel Blender jobs...",
             self.parallel_jobs
-        );
+        ));
 
-        let frames_per_job = (total_frames as f32 / self.parallel_jobs as f32).ceil() as u32;
+        // Split the missing frames into one chunk per job; jobs take chunks from
+        // the queue until it is empty
+        let frames_per_job = (missing_frames as f32 / self.parallel_jobs as f32).ceil() as u32;
+        let queue: Vec<Range<u32>> = missing
+            .into_iter()
+            .flat_map(|range| {
+                range
+                    .clone()
+                    .step_by(frames_per_job as usize)
+                    .map(move |start| start..(start + frames_per_job).min(range.end))
+            })
+            .rev()
+            .collect();
+        let queue = Arc::new(Mutex::new(queue));
+        let manifest = Arc::new(Mutex::new(manifest));
+        let progress = Arc::new(Mutex::new(FrameProgress::new(missing_frames as u32)));
         let mut handles = vec![];
-        let completed_frames = Arc::new(Mutex::new(0));
         let start_time = Instant::now();
 
         // Safety Vault: Memory Monitor
-        let _monitor_handle = thread::spawn(|| {
+        let observer = self.observer.clone();
+        let _monitor_handle = thread::spawn(move || {
             let mut sys = System::new_all();
             loop {
                 sys.refresh_memory();
@@ -371,10 +668,10 @@ def to_blender_coords(x, y, res_x, res_y):
                 let usage_percent = (used_memory as f64 / total_memory as f64) * 100.0;
 
                 if usage_percent > 99.0 {
-                    eprintln!(
-                        "🚨 CRITICAL: Memory usage at {:.1}%! Killing process to prevent crash.",
+                    observer.error(format!(
+                        "CRITICAL: Memory usage at {:.1}%! Killing process to prevent crash.",
                         usage_percent
-                    );
+                    ));
                     std::process::exit(1);
                 }
 
@@ -382,88 +679,167 @@ def to_blender_coords(x, y, res_x, res_y):
             }
         });
 
-        for i in 0..self.parallel_jobs {
-            let start = i as u32 * frames_per_job;
-            let end = ((i + 1) as u32 * frames_per_job).min(total_frames);
-
-            if start >= end {
-                break;
-            }
-
+        for _ in 0..self.parallel_jobs {
             let cache_file = cache_file.clone();
             let output_dir = self.output_dir.clone();
-            let completed = Arc::clone(&completed_frames);
-
-            let handle = thread::spawn(move || -> Result<()> {
-                let mut child = Command::new("blender")
-                    .arg("-b")
-                    .arg("-P")
-                    .arg(&cache_file)
-                    .arg("--")
-                    .arg("--start")
-                    .arg(start.to_string())
-                    .arg("--end")
-                    .arg(end.to_string())
-                    .arg("--output")
-                    .arg(output_dir.join("frame_").to_str().unwrap())
-                    .stdout(Stdio::piped())
-                    .stderr(Stdio::piped())
-                    .spawn()
-                    .context("Failed to spawn Blender process")?;
-
-                // Monitor progress
-                if let Some(stdout) = child.stdout.take() {
-                    let reader = BufReader::new(stdout);
-                    for line in reader.lines().map_while(Result::ok) {
-                        if line.contains("Saved:") {
-                            let mut count = completed.lock().unwrap();
-                            *count += 1;
+            let queue = Arc::clone(&queue);
+            let manifest = Arc::clone(&manifest);
+            let scene_cache = scene_cache.clone();
+            let progress = Arc::clone(&progress);
+            let observer = self.observer.clone();
+            let manifest_path = manifest_path.clone();
+
+            let handle = thread::spawn(move || -> Result<(), BlenderError> {
+                loop {
+                    let Some(range) = queue.lock().unwrap().pop() else {
+                        return Ok(());
+                    };
+
+                    let mut child = Command::new("blender")
+                        .arg("-b")
+                        .arg("-P")
+                        .arg(&cache_file)
+                        .arg("--")
+                        .arg("--start")
+                        .arg(range.start.to_string())
+                        .arg("--end")
+                        .arg((range.end - 1).to_string()) // Blender's frame_end is inclusive
+                        .arg("--output")
+                        .arg(output_dir.join("frame_"))
+                        .stdout(Stdio::piped())
+                        .stderr(Stdio::piped())
+                        .spawn()
+                        .map_err(|e| match e.kind() {
+                            std::io::ErrorKind::NotFound => BlenderError::NotInstalled,
+                            _ => BlenderError::Spawn(e),
+                        })?;
+
+                    // Monitor progress, recording each frame once Blender has saved it
+                    if let Some(stdout) = child.stdout.take() {
+                        let reader = BufReader::new(stdout);
+                        for line in reader.lines().map_while(Result::ok) {
+                            if let Some((frame, path)) = Self::parse_saved_frame(&line) {
+                                manifest
+                                    .lock()
+                                    .unwrap()
+                                    .mark_complete(frame, &path)
+                                    .map_err(|e| output_error(&manifest_path, e))?;
+                                if let Some(scene_cache) = &scene_cache {
+                                    if let Err(e) = scene_cache.store_file(frame, &path) {
+                                        observer.warning(format!(
+                                            "Failed to cache frame {}: {}",
+                                            frame, e
+                                        ));
+                                    }
+                                }
+                                let event = progress.lock().unwrap().frame_done(frame);
+                                observer.on_event(&event);
+                            }
                         }
                     }
-                }
 
-                let status = child.wait()?;
-                if !status.success() {
-                    anyhow::bail!("Blender job failed");
+                    let status = child.wait().map_err(BlenderError::Spawn)?;
+                    if !status.success() {
+                        return Err(BlenderError::JobFailed {
+                            frames: range,
+                            code: status.code(),
+                        });
+                    }
                 }
-                Ok(())
             });
             handles.push(handle);
         }
 
         // Wait for all jobs
-        let mut success = true;
+        let mut errors = Vec::new();
         for handle in handles {
             if let Err(e) = handle.join().unwrap() {
-                println!("❌ Job failed: {}", e);
-                success = false;
+                self.observer.error(format!("Job failed: {}", e));
+                errors.push(e);
+            }
+        }
+
+        // Keep the frames of failed jobs too, so a rerun can resume
+        manifest
+            .lock()
+            .unwrap()
+            .save()
+            .map_err(|e| output_error(&manifest_path, e))?;
+
+        match errors.len() {
+            0 => {
+                self.observer.info(format!(
+                    "Blender rendering complete in {:.2}s",
+                    start_time.elapsed().as_secs_f32()
+                ));
+                Ok(())
             }
+            1 => Err(errors.remove(0)),
+            failed => Err(BlenderError::JobsFailed { failed }),
         }
+    }
+
+    /// Frame number and path of a Blender `Saved: '/out/frame_0042.png'` log line
+    fn parse_saved_frame(line: &str) -> Option<(u32, PathBuf)> {
+        let path = line
+            .trim()
+            .strip_prefix("Saved:")?
+            .trim()
+            .trim_matches('\'');
+        let path = PathBuf::from(path);
+        let frame = path
+            .file_stem()?
+            .to_str()?
+            .strip_prefix("frame_")?
+            .parse()
+            .ok()?;
+        Some((frame, path))
+    }
+}
+
+impl Renderer for BlenderRenderer {
+    fn name(&self) -> &str {
+        "blender"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Self::CAPABILITIES
+    }
 
-        if success {
-            // Update cache
-            fs::write(&hash_file, &script_hash)?;
-            let duration = start_time.elapsed();
-            println!(
-                "✅ Blender rendering complete in {:.2}s",
-                duration.as_secs_f32()
-            );
-        } else {
-            anyhow::bail!("One or more Blender jobs failed");
+    fn frame_format(&self) -> FrameFormat {
+        FrameFormat {
+            digits: 4,
+            extension: "png",
         }
+    }
+
+    fn render_range(
+        &mut self,
+        frames: Range<u32>,
+        output_dir: &Path,
+        _asset_loader: &mut AssetLoader,
+    ) -> error::Result<()> {
+        self.output_dir = output_dir.to_path_buf();
+        Ok(BlenderRenderer::render_range(self, frames)?)
+    }
+}
 
-        Ok(())
+fn output_error(path: &Path, source: anyhow::Error) -> BlenderError {
+    BlenderError::Output {
+        path: path.to_path_buf(),
+        source,
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::script::{Metadata, Resolution, Scene};
+    use crate::script::{Metadata, Resolution, Scene, SCRIPT_VERSION};
 
     #[test]
     fn test_generate_python_script() {
         let script = VideoScript {
+            version: SCRIPT_VERSION,
             metadata: Metadata {
                 title: "Test".into(),
                 resolution: Resolution::Named("1920x1080".into()),
@@ -471,6 +847,8 @@ mod tests {
                 duration: 10.0,
                 description: None,
                 citations: vec![],
+                encoder: None,
+                transparent: false,
             },
             scenes: vec![Scene {
                 id: "test".into(),
@@ -480,18 +858,142 @@ mod tests {
                     source: "test.png".into(),
                     effects: vec![],
                     transform: Default::default(),
+                    animation: Default::default(),
                 }],
                 transition: None,
             }],
             audio: None,
         };
 
-        let renderer = BlenderRenderer::new(script, PathBuf::from("output"));
+        let renderer = BlenderRenderer::new(script.clone(), PathBuf::from("output"));
         let py_script = renderer.generate_python_script(0, 150);
 
         assert!(py_script.contains("import bpy"));
         assert!(py_script.contains("scene.render.resolution_x = 1920"));
         assert!(py_script.contains("scene.render.resolution_y = 1080"));
         assert!(py_script.contains("scene.frame_end = 150"));
+        assert!(!py_script.contains("film_transparent"));
+
+        let mut transparent = script;
+        transparent.metadata.transparent = true;
+        let renderer = BlenderRenderer::new(transparent, PathBuf::from("output"));
+        let py_script = renderer.generate_python_script(0, 150);
+        assert!(py_script.contains("scene.render.film_transparent = True"));
+    }
+
+    #[test]
+    fn test_generate_python_script_bakes_animation() {
+        let layer: Layer = serde_json::from_value(serde_json::json!({
+            "type": "text",
+            "content": "Title",
+            "font": "font.ttf",
+            "font_size": 48.0,
+            "color": {"r": 255, "g": 255, "b": 255},
+            "position": {"x": 100, "y": 100},
+            "animation": {
+                "position": [
+                    {"time": 0.0, "value": {"x": -200, "y": 0}, "easing": "ease_out"},
+                    {"time": 0.5, "value": {"x": 0, "y": 0}}
+                ],
+                "opacity": [{"time": 0.0, "value": 0.0}, {"time": 0.25, "value": 1.0}]
+            }
+        }))
+        .unwrap();
+
+        let script = VideoScript {
+            version: SCRIPT_VERSION,
+            metadata: Metadata {
+                title: "Test".into(),
+                resolution: Resolution::Named("1920x1080".into()),
+                fps: 10,
+                duration: 1.0,
+                description: None,
+                citations: vec![],
+                encoder: None,
+                transparent: false,
+            },
+            scenes: vec![Scene {
+                id: "intro".into(),
+                duration: 1.0,
+                scene_type: Default::default(),
+                layers: vec![layer],
+                transition: None,
+            }],
+            audio: None,
+        };
+
+        let renderer = BlenderRenderer::new(script, PathBuf::from("output"));
+        let py_script = renderer.generate_python_script(0, 10);
+
+        // One keyframe per frame of the scene for each animated property
+        assert_eq!(py_script.matches("\nkeyframe_transform(obj, ").count(), 10);
+        assert_eq!(py_script.matches("\nkeyframe_opacity(mat, ").count(), 10);
+        assert!(!py_script.contains("\nkeyframe_color(mat, "));
+
+        // Starts off-screen left (text position + animated offset) and lands in place
+        assert!(py_script.contains("to_blender_coords(-100, 100, 1920, 1080)"));
+        assert!(py_script.contains("keyframe_opacity(mat, 9, 1)"));
+    }
+
+    #[test]
+    fn test_parse_saved_frame() {
+        assert_eq!(
+            BlenderRenderer::parse_saved_frame("Saved: 'output/frame_0042.png'"),
+            Some((42, PathBuf::from("output/frame_0042.png")))
+        );
+        assert_eq!(
+            BlenderRenderer::parse_saved_frame(" Time: 00:01.20 (Saving: 00:00.01)"),
+            None
+        );
+        assert_eq!(
+            BlenderRenderer::parse_saved_frame("Saved: 'output/audio.wav'"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_render_range_reuses_cached_scenes() {
+        let script = VideoScript {
+            version: SCRIPT_VERSION,
+            metadata: Metadata {
+                title: "Test".into(),
+                resolution: Resolution::Named("8x8".into()),
+                fps: 10,
+                duration: 0.5,
+                description: None,
+                citations: vec![],
+                encoder: None,
+                transparent: false,
+            },
+            scenes: vec![Scene {
+                id: "test".into(),
+                duration: 0.5,
+                scene_type: Default::default(),
+                layers: vec![],
+                transition: None,
+            }],
+            audio: None,
+        };
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let renderer = BlenderRenderer {
+            cache_dir: temp_dir.path().join("cache"),
+            scene_cache_dir: Some(temp_dir.path().join("cache/scenes")),
+            ..BlenderRenderer::new(script, temp_dir.path().join("output"))
+        };
+
+        // Frames cached by an earlier render of the same scene
+        let scene_cache = renderer.scene_cache().unwrap().unwrap();
+        let rendered = temp_dir.path().join("rendered.png");
+        crate::renderer::FrameBuffer::new(8, 8)
+            .save_png(&rendered)
+            .unwrap();
+        for frame in 0..5 {
+            scene_cache.store_file(frame, &rendered).unwrap();
+        }
+
+        // Every frame is restored, so Blender never has to run
+        renderer.render_range(0..5).unwrap();
+        assert!((0..5).all(|frame| renderer.frame_path(frame).is_file()));
     }
 }
@@ -1,5 +1,7 @@
-use crate::renderer::FrameBuffer;
+use crate::renderer::{FrameBuffer, TextRasterizer};
 use crate::script::Transform;
+use ab_glyph::FontArc;
+use image::RgbaImage;
 
 /// Layer compositor
 pub struct Compositor;
@@ -28,7 +30,191 @@ impl Compositor {
         }
     }
 
-    /// Draw text (placeholder - simple rectangle for now)
+    /// Alpha blend an RGBA image onto the buffer with its top-left corner at (x, y)
+    pub fn draw_image(buffer: &mut FrameBuffer, image: &RgbaImage, x: i32, y: i32) {
+        let (buf_width, buf_height) = buffer.dimensions();
+
+        for (dx, dy, pixel) in image.enumerate_pixels() {
+            if pixel[3] == 0 {
+                continue;
+            }
+
+            let px = x + dx as i32;
+            let py = y + dy as i32;
+
+            if px >= 0 && py >= 0 && (px as u32) < buf_width && (py as u32) < buf_height {
+                buffer.blend_pixel(px as u32, py as u32, pixel.0);
+            }
+        }
+    }
+
+    /// Alpha blend an RGBA image scaled to `width`x`height` at (x, y), with an opacity multiplier.
+    pub fn draw_image_scaled(
+        buffer: &mut FrameBuffer,
+        image: &RgbaImage,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        opacity: f32,
+    ) {
+        if image.width() == 0 || image.height() == 0 {
+            return;
+        }
+
+        let matrix = Affine2::scaling(
+            width as f32 / image.width() as f32,
+            height as f32 / image.height() as f32,
+        )
+        .then(&Affine2::translation(x as f32, y as f32));
+
+        Self::draw_image_transformed(buffer, image, &matrix, opacity);
+    }
+
+    /// Alpha blend an RGBA image mapped into the frame by `matrix` (image pixel space
+    /// to frame pixel space), with an opacity multiplier.
+    ///
+    /// Every frame pixel whose center falls inside the transformed image is inverse-mapped
+    /// and sampled with bilinear filtering and clamp-to-edge addressing, matching the GPU
+    /// rasterizer and sampler.
+    pub fn draw_image_transformed(
+        buffer: &mut FrameBuffer,
+        image: &RgbaImage,
+        matrix: &Affine2,
+        opacity: f32,
+    ) {
+        let opacity = opacity.clamp(0.0, 1.0);
+        if image.width() == 0 || image.height() == 0 || opacity == 0.0 {
+            return;
+        }
+
+        let (width, height) = (image.width() as f32, image.height() as f32);
+        Self::for_each_covered_pixel(buffer, matrix, width, height, |buffer, px, py, u, v| {
+            let [r, g, b, a] = Self::sample_bilinear(image, u - 0.5, v - 0.5);
+
+            let alpha = a * opacity;
+            if alpha > 0.0 {
+                buffer.blend_pixel(
+                    px,
+                    py,
+                    [
+                        r.round() as u8,
+                        g.round() as u8,
+                        b.round() as u8,
+                        alpha.round() as u8,
+                    ],
+                );
+            }
+        });
+    }
+
+    /// Blend a solid `width`x`height` rectangle mapped into the frame by `matrix`
+    pub fn fill_rect_transformed(
+        buffer: &mut FrameBuffer,
+        matrix: &Affine2,
+        width: u32,
+        height: u32,
+        color: [u8; 4],
+    ) {
+        if color[3] == 0 {
+            return;
+        }
+
+        Self::for_each_covered_pixel(
+            buffer,
+            matrix,
+            width as f32,
+            height as f32,
+            |buffer, px, py, _, _| buffer.blend_pixel(px, py, color),
+        );
+    }
+
+    /// Visit every frame pixel whose center lies inside the `width`x`height` source
+    /// rectangle mapped by `matrix`, passing the inverse-mapped source coordinates
+    fn for_each_covered_pixel<F>(
+        buffer: &mut FrameBuffer,
+        matrix: &Affine2,
+        width: f32,
+        height: f32,
+        mut visit: F,
+    ) where
+        F: FnMut(&mut FrameBuffer, u32, u32, f32, f32),
+    {
+        let Some(inverse) = matrix.inverse() else {
+            return; // Degenerate transform (e.g. zero scale) covers nothing
+        };
+
+        let (buf_width, buf_height) = buffer.dimensions();
+        let corners = matrix.corners(width, height);
+
+        // Bounding box of the transformed rectangle, clipped to the buffer
+        let (mut min_x, mut min_y) = (f32::MAX, f32::MAX);
+        let (mut max_x, mut max_y) = (f32::MIN, f32::MIN);
+        for [cx, cy] in corners {
+            min_x = min_x.min(cx);
+            min_y = min_y.min(cy);
+            max_x = max_x.max(cx);
+            max_y = max_y.max(cy);
+        }
+
+        let start_x = min_x.floor().max(0.0) as u32;
+        let start_y = min_y.floor().max(0.0) as u32;
+        let end_x = (max_x.ceil().max(0.0) as u32).min(buf_width);
+        let end_y = (max_y.ceil().max(0.0) as u32).min(buf_height);
+
+        for py in start_y..end_y {
+            for px in start_x..end_x {
+                let (u, v) = inverse.transform_point(px as f32 + 0.5, py as f32 + 0.5);
+                if u >= 0.0 && v >= 0.0 && u < width && v < height {
+                    visit(buffer, px, py, u, v);
+                }
+            }
+        }
+    }
+
+    /// Sample an image at fractional texel coordinates (clamped to the edges)
+    fn sample_bilinear(image: &RgbaImage, u: f32, v: f32) -> [f32; 4] {
+        let max_x = image.width() as f32 - 1.0;
+        let max_y = image.height() as f32 - 1.0;
+        let u = u.clamp(0.0, max_x);
+        let v = v.clamp(0.0, max_y);
+
+        let x0 = u.floor() as u32;
+        let y0 = v.floor() as u32;
+        let x1 = (x0 + 1).min(image.width() - 1);
+        let y1 = (y0 + 1).min(image.height() - 1);
+        let fx = u - x0 as f32;
+        let fy = v - y0 as f32;
+
+        let p00 = image.get_pixel(x0, y0);
+        let p10 = image.get_pixel(x1, y0);
+        let p01 = image.get_pixel(x0, y1);
+        let p11 = image.get_pixel(x1, y1);
+
+        let mut out = [0.0; 4];
+        for (c, value) in out.iter_mut().enumerate() {
+            let top = p00[c] as f32 * (1.0 - fx) + p10[c] as f32 * fx;
+            let bottom = p01[c] as f32 * (1.0 - fx) + p11[c] as f32 * fx;
+            *value = top * (1.0 - fy) + bottom * fy;
+        }
+        out
+    }
+
+    /// Draw antialiased text with its top-left corner at (x, y)
+    pub fn draw_text(
+        buffer: &mut FrameBuffer,
+        font: &FontArc,
+        text: &str,
+        x: i32,
+        y: i32,
+        font_size: f32,
+        color: [u8; 4],
+    ) {
+        let image = TextRasterizer::rasterize(font, text, font_size, color);
+        Self::draw_image(buffer, &image, x, y);
+    }
+
+    /// Draw text (placeholder - simple rectangle, used when no font is available)
     pub fn draw_text_placeholder(
         buffer: &mut FrameBuffer,
         text: &str,
@@ -42,23 +228,135 @@ impl Compositor {
         Self::fill_rect(buffer, x, y, width, height, color);
     }
 
-    /// Apply transform to coordinates
+    /// Apply transform to a layer-local point given relative to the anchor point
     pub fn apply_transform(x: i32, y: i32, transform: &Transform) -> (i32, i32) {
-        // Apply position offset
-        let tx = x + transform.position.x;
-        let ty = y + transform.position.y;
+        let matrix = Self::layer_matrix(transform, 0.0, 0.0);
+        let (tx, ty) = matrix.transform_point(x as f32, y as f32);
+        (tx.round() as i32, ty.round() as i32)
+    }
+
+    /// Build the matrix mapping a `width`x`height` layer into the frame.
+    ///
+    /// The layer is scaled and rotated around its anchor point, and the anchor
+    /// is placed at the transform position.
+    pub fn layer_matrix(transform: &Transform, width: f32, height: f32) -> Affine2 {
+        Affine2::translation(-transform.anchor.x * width, -transform.anchor.y * height)
+            .then(&Affine2::scaling(transform.scale, transform.scale))
+            .then(&Affine2::rotation(transform.rotation))
+            .then(&Affine2::translation(
+                transform.position.x as f32,
+                transform.position.y as f32,
+            ))
+    }
+}
+
+/// 2D affine transform in pixel space:
+/// `x' = a*x + c*y + tx`, `y' = b*x + d*y + ty`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Affine2 {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub tx: f32,
+    pub ty: f32,
+}
+
+impl Affine2 {
+    pub fn identity() -> Self {
+        Self::scaling(1.0, 1.0)
+    }
+
+    pub fn translation(tx: f32, ty: f32) -> Self {
+        Self {
+            a: 1.0,
+            b: 0.0,
+            c: 0.0,
+            d: 1.0,
+            tx,
+            ty,
+        }
+    }
+
+    pub fn scaling(sx: f32, sy: f32) -> Self {
+        Self {
+            a: sx,
+            b: 0.0,
+            c: 0.0,
+            d: sy,
+            tx: 0.0,
+            ty: 0.0,
+        }
+    }
+
+    /// Clockwise rotation on screen (y axis points down), in degrees
+    pub fn rotation(degrees: f32) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Self {
+            a: cos,
+            b: sin,
+            c: -sin,
+            d: cos,
+            tx: 0.0,
+            ty: 0.0,
+        }
+    }
+
+    /// Compose: apply `self` first, then `next`
+    pub fn then(&self, next: &Affine2) -> Affine2 {
+        Affine2 {
+            a: next.a * self.a + next.c * self.b,
+            b: next.b * self.a + next.d * self.b,
+            c: next.a * self.c + next.c * self.d,
+            d: next.b * self.c + next.d * self.d,
+            tx: next.a * self.tx + next.c * self.ty + next.tx,
+            ty: next.b * self.tx + next.d * self.ty + next.ty,
+        }
+    }
 
-        // TODO: Apply scale and rotation
-        // For now, just position offset
+    pub fn transform_point(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            self.a * x + self.c * y + self.tx,
+            self.b * x + self.d * y + self.ty,
+        )
+    }
+
+    /// Inverse transform, or `None` if the matrix is singular
+    pub fn inverse(&self) -> Option<Affine2> {
+        let det = self.a * self.d - self.b * self.c;
+        if det.abs() < f32::EPSILON {
+            return None;
+        }
 
-        (tx, ty)
+        let inv_det = 1.0 / det;
+        let a = self.d * inv_det;
+        let b = -self.b * inv_det;
+        let c = -self.c * inv_det;
+        let d = self.a * inv_det;
+        Some(Affine2 {
+            a,
+            b,
+            c,
+            d,
+            tx: -(a * self.tx + c * self.ty),
+            ty: -(b * self.tx + d * self.ty),
+        })
+    }
+
+    /// Frame positions of a `width`x`height` rectangle's corners
+    /// (top-left, top-right, bottom-right, bottom-left)
+    pub fn corners(&self, width: f32, height: f32) -> [[f32; 2]; 4] {
+        [(0.0, 0.0), (width, 0.0), (width, height), (0.0, height)].map(|(x, y)| {
+            let (fx, fy) = self.transform_point(x, y);
+            [fx, fy]
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::script::Position;
+    use crate::script::{Anchor, Position};
 
     #[test]
     fn test_fill_rect() {
@@ -81,9 +379,205 @@ mod tests {
             scale: 1.0,
             rotation: 0.0,
             opacity: 1.0,
+            anchor: Default::default(),
         };
 
         let (tx, ty) = Compositor::apply_transform(10, 20, &transform);
         assert_eq!((tx, ty), (110, 70));
     }
+
+    #[test]
+    fn test_apply_transform_scale_and_rotation() {
+        let transform = Transform {
+            position: Position { x: 100, y: 50 },
+            scale: 2.0,
+            rotation: 90.0,
+            ..Default::default()
+        };
+
+        // (10, 0) scaled to (20, 0), then rotated clockwise to (0, 20)
+        let (tx, ty) = Compositor::apply_transform(10, 0, &transform);
+        assert_eq!((tx, ty), (100, 70));
+    }
+
+    #[test]
+    fn test_layer_matrix_anchor() {
+        let transform = Transform {
+            position: Position { x: 50, y: 50 },
+            scale: 2.0,
+            anchor: Anchor { x: 0.5, y: 0.5 },
+            ..Default::default()
+        };
+
+        // A 10x10 layer centered on (50, 50) and scaled around its center
+        let matrix = Compositor::layer_matrix(&transform, 10.0, 10.0);
+        assert_eq!(matrix.transform_point(5.0, 5.0), (50.0, 50.0));
+        assert_eq!(matrix.transform_point(0.0, 0.0), (40.0, 40.0));
+        assert_eq!(matrix.transform_point(10.0, 10.0), (60.0, 60.0));
+    }
+
+    #[test]
+    fn test_affine_inverse_roundtrip() {
+        let matrix = Affine2::scaling(2.0, 3.0)
+            .then(&Affine2::rotation(30.0))
+            .then(&Affine2::translation(7.0, -4.0));
+        let inverse = matrix.inverse().unwrap();
+
+        let (x, y) = matrix.transform_point(3.0, 5.0);
+        let (rx, ry) = inverse.transform_point(x, y);
+        assert!((rx - 3.0).abs() < 1e-4);
+        assert!((ry - 5.0).abs() < 1e-4);
+
+        assert!(Affine2::scaling(0.0, 1.0).inverse().is_none());
+        assert_eq!(Affine2::identity().transform_point(1.5, 2.5), (1.5, 2.5));
+    }
+
+    #[test]
+    fn test_draw_image_transformed_rotation() {
+        let mut fb = FrameBuffer::new(40, 40);
+        fb.clear([0, 0, 0, 255]);
+
+        // A 20x4 horizontal bar rotated 90 degrees around its center becomes vertical
+        let mut image = RgbaImage::new(20, 4);
+        for pixel in image.pixels_mut() {
+            *pixel = image::Rgba([255, 255, 0, 255]);
+        }
+        let transform = Transform {
+            position: Position { x: 20, y: 20 },
+            rotation: 90.0,
+            anchor: Anchor { x: 0.5, y: 0.5 },
+            ..Default::default()
+        };
+        let matrix = Compositor::layer_matrix(&transform, 20.0, 4.0);
+
+        Compositor::draw_image_transformed(&mut fb, &image, &matrix, 1.0);
+
+        assert_eq!(fb.get_pixel(20, 12), Some([255, 255, 0, 255]));
+        assert_eq!(fb.get_pixel(20, 27), Some([255, 255, 0, 255]));
+        assert_eq!(fb.get_pixel(12, 20), Some([0, 0, 0, 255]));
+        assert_eq!(fb.get_pixel(27, 20), Some([0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_draw_image_transformed_bilinear_rotation() {
+        let mut fb = FrameBuffer::new(40, 40);
+        fb.clear([0, 0, 0, 255]);
+
+        // Black/white checkerboard: a 45 degree rotation must produce filtered gray levels
+        let image = RgbaImage::from_fn(8, 8, |x, y| {
+            if (x + y) % 2 == 0 {
+                image::Rgba([255, 255, 255, 255])
+            } else {
+                image::Rgba([0, 0, 0, 255])
+            }
+        });
+        let transform = Transform {
+            position: Position { x: 20, y: 20 },
+            scale: 2.0,
+            rotation: 45.0,
+            anchor: Anchor { x: 0.5, y: 0.5 },
+            ..Default::default()
+        };
+        let matrix = Compositor::layer_matrix(&transform, 8.0, 8.0);
+
+        Compositor::draw_image_transformed(&mut fb, &image, &matrix, 1.0);
+
+        let center = fb.get_pixel(20, 20).unwrap();
+        assert!(center[0] > 0 && center[0] < 255);
+    }
+
+    #[test]
+    fn test_fill_rect_transformed_opacity() {
+        let mut fb = FrameBuffer::new(10, 10);
+        fb.clear([0, 0, 0, 255]);
+
+        let matrix = Affine2::translation(2.0, 2.0);
+        Compositor::fill_rect_transformed(&mut fb, &matrix, 4, 4, [255, 0, 0, 128]);
+
+        let pixel = fb.get_pixel(3, 3).unwrap();
+        assert!(pixel[0] > 120 && pixel[0] < 135);
+        assert_eq!(fb.get_pixel(6, 6), Some([0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_draw_image_blends_alpha() {
+        let mut fb = FrameBuffer::new(10, 10);
+        fb.clear([0, 0, 0, 255]);
+
+        let mut image = RgbaImage::new(2, 2);
+        image.put_pixel(0, 0, image::Rgba([255, 255, 255, 255]));
+        image.put_pixel(1, 0, image::Rgba([255, 255, 255, 128]));
+
+        Compositor::draw_image(&mut fb, &image, 4, 4);
+
+        assert_eq!(fb.get_pixel(4, 4), Some([255, 255, 255, 255]));
+        let half = fb.get_pixel(5, 4).unwrap();
+        assert!(half[0] > 120 && half[0] < 135);
+        // Fully transparent pixels leave the background untouched
+        assert_eq!(fb.get_pixel(4, 5), Some([0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_draw_image_clips_to_buffer() {
+        let mut fb = FrameBuffer::new(4, 4);
+        fb.clear([0, 0, 0, 255]);
+
+        let mut image = RgbaImage::new(4, 4);
+        for pixel in image.pixels_mut() {
+            *pixel = image::Rgba([255, 0, 0, 255]);
+        }
+
+        // Partially off-screen on both sides must not panic
+        Compositor::draw_image(&mut fb, &image, -2, 2);
+
+        assert_eq!(fb.get_pixel(0, 3), Some([255, 0, 0, 255]));
+        assert_eq!(fb.get_pixel(2, 3), Some([0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_draw_image_scaled() {
+        let mut fb = FrameBuffer::new(20, 20);
+        fb.clear([0, 0, 0, 255]);
+
+        let mut image = RgbaImage::new(2, 2);
+        for pixel in image.pixels_mut() {
+            *pixel = image::Rgba([0, 255, 0, 255]);
+        }
+
+        // 2x2 image scaled up to 8x8 at (5, 5)
+        Compositor::draw_image_scaled(&mut fb, &image, 5, 5, 8, 8, 1.0);
+
+        assert_eq!(fb.get_pixel(5, 5), Some([0, 255, 0, 255]));
+        assert_eq!(fb.get_pixel(12, 12), Some([0, 255, 0, 255]));
+        assert_eq!(fb.get_pixel(13, 13), Some([0, 0, 0, 255]));
+        assert_eq!(fb.get_pixel(4, 4), Some([0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_draw_image_scaled_opacity() {
+        let mut fb = FrameBuffer::new(4, 4);
+        fb.clear([0, 0, 0, 255]);
+
+        let mut image = RgbaImage::new(1, 1);
+        image.put_pixel(0, 0, image::Rgba([255, 255, 255, 255]));
+
+        Compositor::draw_image_scaled(&mut fb, &image, 0, 0, 4, 4, 0.5);
+
+        let pixel = fb.get_pixel(2, 2).unwrap();
+        assert!(pixel[0] > 120 && pixel[0] < 135);
+    }
+
+    #[test]
+    fn test_sample_bilinear_interpolates() {
+        let mut image = RgbaImage::new(2, 1);
+        image.put_pixel(0, 0, image::Rgba([0, 0, 0, 255]));
+        image.put_pixel(1, 0, image::Rgba([200, 200, 200, 255]));
+
+        let mid = Compositor::sample_bilinear(&image, 0.5, 0.0);
+        assert_eq!(mid[0], 100.0);
+
+        // Clamped outside the image
+        let edge = Compositor::sample_bilinear(&image, 5.0, -3.0);
+        assert_eq!(edge[0], 200.0);
+    }
 }
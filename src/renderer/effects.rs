@@ -0,0 +1,455 @@
+use crate::script::{ColorGrade, Effect};
+use anyhow::{Context, Result};
+use image::RgbaImage;
+use rayon::prelude::*;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Per-layer effect operators for the native renderer
+pub struct Effects;
+
+impl Effects {
+    /// Opacity multiplier of the fade effects at `time` seconds into a scene
+    pub fn fade_opacity(effects: &[Effect], time: f32, scene_duration: f32) -> f32 {
+        let ramp = |elapsed: f32, duration: f32| {
+            if duration <= 0.0 {
+                1.0
+            } else {
+                (elapsed / duration).clamp(0.0, 1.0)
+            }
+        };
+
+        effects
+            .iter()
+            .map(|effect| match effect {
+                Effect::FadeIn { duration } => ramp(time, *duration),
+                Effect::FadeOut { duration } => ramp(scene_duration - time, *duration),
+                _ => 1.0,
+            })
+            .product()
+    }
+
+    /// Whether any effect changes the layer pixels (rather than only its opacity)
+    pub fn has_pixel_effects(effects: &[Effect]) -> bool {
+        effects
+            .iter()
+            .any(|effect| matches!(effect, Effect::Blur { .. } | Effect::ColorGrade(_)))
+    }
+
+    /// Margin in pixels that the blur effects add on each side of a layer
+    pub fn padding(effects: &[Effect]) -> u32 {
+        effects
+            .iter()
+            .map(|effect| match effect {
+                Effect::Blur { radius } => Self::kernel_radius(*radius),
+                _ => 0,
+            })
+            .sum()
+    }
+
+    /// Apply the blur and color grade effects in order.
+    ///
+    /// The result is `padding(effects)` pixels larger on each side than the input.
+    /// `load_lut` resolves the LUT files referenced by color grades.
+    pub fn apply(
+        image: &RgbaImage,
+        effects: &[Effect],
+        load_lut: impl Fn(&Path) -> Option<Arc<CubeLut>>,
+    ) -> RgbaImage {
+        let mut result = image.clone();
+
+        for effect in effects {
+            match effect {
+                Effect::Blur { radius } => result = Self::blur(&result, *radius),
+                Effect::ColorGrade(grade) => {
+                    let lut = grade.lut.as_deref().and_then(&load_lut);
+                    Self::color_grade(&mut result, grade, lut.as_deref());
+                }
+                Effect::FadeIn { .. } | Effect::FadeOut { .. } => {}
+            }
+        }
+
+        result
+    }
+
+    /// Separable Gaussian blur; `radius` is the kernel extent (3 standard deviations).
+    ///
+    /// The image is padded by the kernel radius so the blur can spread past its
+    /// edges, and blurred with premultiplied alpha to avoid dark fringes.
+    pub fn blur(image: &RgbaImage, radius: f32) -> RgbaImage {
+        let pad = Self::kernel_radius(radius) as usize;
+        if pad == 0 {
+            return image.clone();
+        }
+
+        let sigma = radius / 3.0;
+        let mut kernel: Vec<f32> = (-(pad as i32)..=pad as i32)
+            .map(|i| (-((i * i) as f32) / (2.0 * sigma * sigma)).exp())
+            .collect();
+        let sum: f32 = kernel.iter().sum();
+        kernel.iter_mut().for_each(|k| *k /= sum);
+
+        let width = image.width() as usize + 2 * pad;
+        let height = image.height() as usize + 2 * pad;
+
+        let mut source = vec![[0.0f32; 4]; width * height];
+        for (x, y, pixel) in image.enumerate_pixels() {
+            let alpha = pixel[3] as f32 / 255.0;
+            source[(y as usize + pad) * width + x as usize + pad] = [
+                pixel[0] as f32 * alpha,
+                pixel[1] as f32 * alpha,
+                pixel[2] as f32 * alpha,
+                pixel[3] as f32,
+            ];
+        }
+
+        // Horizontal pass
+        let mut horizontal = vec![[0.0f32; 4]; width * height];
+        horizontal
+            .par_chunks_mut(width)
+            .enumerate()
+            .for_each(|(y, row)| {
+                let src = &source[y * width..(y + 1) * width];
+                for (x, out) in row.iter_mut().enumerate() {
+                    for (k, weight) in kernel.iter().enumerate() {
+                        let sx = x as i32 + k as i32 - pad as i32;
+                        if sx >= 0 && (sx as usize) < width {
+                            let p = src[sx as usize];
+                            for c in 0..4 {
+                                out[c] += p[c] * weight;
+                            }
+                        }
+                    }
+                }
+            });
+
+        // Vertical pass
+        let mut vertical = vec![[0.0f32; 4]; width * height];
+        vertical
+            .par_chunks_mut(width)
+            .enumerate()
+            .for_each(|(y, row)| {
+                for (k, weight) in kernel.iter().enumerate() {
+                    let sy = y as i32 + k as i32 - pad as i32;
+                    if sy < 0 || sy as usize >= height {
+                        continue;
+                    }
+                    let src = &horizontal[sy as usize * width..(sy as usize + 1) * width];
+                    for (out, p) in row.iter_mut().zip(src) {
+                        for c in 0..4 {
+                            out[c] += p[c] * weight;
+                        }
+                    }
+                }
+            });
+
+        let mut result = RgbaImage::new(width as u32, height as u32);
+        for (pixel, value) in result.pixels_mut().zip(&vertical) {
+            let alpha = value[3];
+            if alpha <= 0.0 {
+                continue;
+            }
+            let unpremultiply = 255.0 / alpha;
+            let channel = |v: f32| (v * unpremultiply).round().clamp(0.0, 255.0) as u8;
+            *pixel = image::Rgba([
+                channel(value[0]),
+                channel(value[1]),
+                channel(value[2]),
+                alpha.round().clamp(0.0, 255.0) as u8,
+            ]);
+        }
+
+        result
+    }
+
+    /// Apply a color grade in place.
+    ///
+    /// Exposure and temperature are applied in linear light; contrast, saturation
+    /// and the LUT operate on the display (sRGB) values. Alpha is left unchanged.
+    pub fn color_grade(image: &mut RgbaImage, grade: &ColorGrade, lut: Option<&CubeLut>) {
+        let to_linear: Vec<f32> = (0..256).map(|v| srgb_to_linear(v as f32 / 255.0)).collect();
+
+        let gain = 2f32.powf(grade.exposure);
+        let temperature = grade.temperature.clamp(-1.0, 1.0) * 0.2;
+        let gains = [gain * (1.0 + temperature), gain, gain * (1.0 - temperature)];
+
+        image.par_chunks_mut(4).for_each(|pixel| {
+            let mut rgb = [0.0f32; 3];
+            for c in 0..3 {
+                let linear = to_linear[pixel[c] as usize] * gains[c];
+                rgb[c] = linear_to_srgb(linear.clamp(0.0, 1.0));
+                rgb[c] = (rgb[c] - 0.5) * grade.contrast + 0.5;
+            }
+
+            let luma = 0.2126 * rgb[0] + 0.7152 * rgb[1] + 0.0722 * rgb[2];
+            for value in &mut rgb {
+                *value = (luma + (*value - luma) * grade.saturation).clamp(0.0, 1.0);
+            }
+
+            if let Some(lut) = lut {
+                rgb = lut.sample(rgb);
+            }
+
+            for c in 0..3 {
+                pixel[c] = (rgb[c] * 255.0).round().clamp(0.0, 255.0) as u8;
+            }
+        });
+    }
+
+    fn kernel_radius(radius: f32) -> u32 {
+        radius.max(0.0).ceil() as u32
+    }
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// 3D color lookup table parsed from an Adobe `.cube` file
+#[derive(Debug, Clone)]
+pub struct CubeLut {
+    size: usize,
+    domain_min: [f32; 3],
+    domain_max: [f32; 3],
+    /// Output colors with red varying fastest, then green, then blue
+    table: Vec<[f32; 3]>,
+}
+
+impl CubeLut {
+    /// Parse the contents of a `.cube` file (only 3D LUTs are supported)
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut size = None;
+        let mut domain_min = [0.0; 3];
+        let mut domain_max = [1.0; 3];
+        let mut table = Vec::new();
+
+        let triple = |parts: &[&str]| -> Result<[f32; 3]> {
+            if parts.len() != 3 {
+                anyhow::bail!("Expected 3 values, found {}", parts.len());
+            }
+            let mut values = [0.0; 3];
+            for (value, part) in values.iter_mut().zip(parts) {
+                *value = part
+                    .parse()
+                    .with_context(|| format!("Invalid number: {}", part))?;
+            }
+            Ok(values)
+        };
+
+        for (idx, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let context = || format!("Invalid LUT at line {}", idx + 1);
+            match parts[0] {
+                "TITLE" => {}
+                "LUT_3D_SIZE" => {
+                    let n: usize = parts
+                        .get(1)
+                        .and_then(|n| n.parse().ok())
+                        .with_context(context)?;
+                    if n < 2 {
+                        anyhow::bail!("LUT_3D_SIZE must be at least 2");
+                    }
+                    size = Some(n);
+                }
+                "LUT_1D_SIZE" => anyhow::bail!("1D LUTs are not supported"),
+                "DOMAIN_MIN" => domain_min = triple(&parts[1..]).with_context(context)?,
+                "DOMAIN_MAX" => domain_max = triple(&parts[1..]).with_context(context)?,
+                _ => table.push(triple(&parts).with_context(context)?),
+            }
+        }
+
+        let size = size.context("Missing LUT_3D_SIZE")?;
+        if table.len() != size * size * size {
+            anyhow::bail!(
+                "Expected {} LUT entries, found {}",
+                size * size * size,
+                table.len()
+            );
+        }
+
+        Ok(Self {
+            size,
+            domain_min,
+            domain_max,
+            table,
+        })
+    }
+
+    /// Look up a color with trilinear interpolation
+    pub fn sample(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let max = (self.size - 1) as f32;
+        let mut base = [0usize; 3];
+        let mut frac = [0.0f32; 3];
+        for c in 0..3 {
+            let range = self.domain_max[c] - self.domain_min[c];
+            let normalized = if range > 0.0 {
+                ((rgb[c] - self.domain_min[c]) / range).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let position = normalized * max;
+            base[c] = (position.floor() as usize).min(self.size - 2);
+            frac[c] = position - base[c] as f32;
+        }
+
+        let entry = |r: usize, g: usize, b: usize| {
+            self.table
+                [(base[0] + r) + (base[1] + g) * self.size + (base[2] + b) * self.size * self.size]
+        };
+        let lerp = |a: [f32; 3], b: [f32; 3], t: f32| {
+            [
+                a[0] + (b[0] - a[0]) * t,
+                a[1] + (b[1] - a[1]) * t,
+                a[2] + (b[2] - a[2]) * t,
+            ]
+        };
+
+        let c00 = lerp(entry(0, 0, 0), entry(1, 0, 0), frac[0]);
+        let c10 = lerp(entry(0, 1, 0), entry(1, 1, 0), frac[0]);
+        let c01 = lerp(entry(0, 0, 1), entry(1, 0, 1), frac[0]);
+        let c11 = lerp(entry(0, 1, 1), entry(1, 1, 1), frac[0]);
+        let c0 = lerp(c00, c10, frac[1]);
+        let c1 = lerp(c01, c11, frac[1]);
+        let result = lerp(c0, c1, frac[2]);
+
+        [
+            result[0].clamp(0.0, 1.0),
+            result[1].clamp(0.0, 1.0),
+            result[2].clamp(0.0, 1.0),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    /// 2x2x2 LUT that inverts every channel
+    const INVERT_LUT: &str = "TITLE \"Invert\"\nLUT_3D_SIZE 2\n\
+        1 1 1\n0 1 1\n1 0 1\n0 0 1\n1 1 0\n0 1 0\n1 0 0\n0 0 0\n";
+
+    #[test]
+    fn test_fade_opacity_ramps() {
+        let effects = vec![
+            Effect::FadeIn { duration: 1.0 },
+            Effect::FadeOut { duration: 2.0 },
+        ];
+
+        assert_eq!(Effects::fade_opacity(&effects, 0.0, 10.0), 0.0);
+        assert_eq!(Effects::fade_opacity(&effects, 0.5, 10.0), 0.5);
+        assert_eq!(Effects::fade_opacity(&effects, 5.0, 10.0), 1.0);
+        assert_eq!(Effects::fade_opacity(&effects, 9.0, 10.0), 0.5);
+        assert_eq!(Effects::fade_opacity(&effects, 10.0, 10.0), 0.0);
+        assert_eq!(Effects::fade_opacity(&[], 0.0, 10.0), 1.0);
+    }
+
+    #[test]
+    fn test_blur_pads_and_spreads() {
+        let mut image = RgbaImage::new(5, 5);
+        image.put_pixel(2, 2, Rgba([255, 0, 0, 255]));
+
+        let blurred = Effects::blur(&image, 3.0);
+        assert_eq!(blurred.dimensions(), (11, 11));
+
+        // The dot spreads symmetrically, keeping its color and fading outwards
+        let center = blurred.get_pixel(5, 5);
+        let near = blurred.get_pixel(6, 5);
+        let far = blurred.get_pixel(7, 5);
+        assert!(center[3] > near[3] && near[3] > far[3] && far[3] > 0);
+        assert_eq!(blurred.get_pixel(4, 5), near);
+        assert_eq!(near[0], 255);
+        assert_eq!(near[1], 0);
+
+        // Total coverage is preserved
+        let total: u32 = blurred.pixels().map(|p| p[3] as u32).sum();
+        assert!((total as i32 - 255).abs() < 16);
+    }
+
+    #[test]
+    fn test_blur_zero_radius_is_identity() {
+        let image = RgbaImage::from_pixel(3, 3, Rgba([10, 20, 30, 255]));
+        assert_eq!(Effects::blur(&image, 0.0), image);
+        assert_eq!(Effects::padding(&[Effect::Blur { radius: 2.5 }]), 3);
+    }
+
+    #[test]
+    fn test_color_grade_neutral_is_identity() {
+        let mut image = RgbaImage::from_pixel(2, 2, Rgba([10, 128, 250, 200]));
+        let original = image.clone();
+        Effects::color_grade(&mut image, &ColorGrade::default(), None);
+        assert_eq!(image, original);
+    }
+
+    #[test]
+    fn test_color_grade_adjustments() {
+        let pixel = Rgba([200, 100, 50, 255]);
+
+        let mut gray = RgbaImage::from_pixel(1, 1, pixel);
+        let desaturate = ColorGrade {
+            saturation: 0.0,
+            ..Default::default()
+        };
+        Effects::color_grade(&mut gray, &desaturate, None);
+        let p = gray.get_pixel(0, 0);
+        assert!(p[0] == p[1] && p[1] == p[2]);
+
+        let mut bright = RgbaImage::from_pixel(1, 1, pixel);
+        let exposure = ColorGrade {
+            exposure: 1.0,
+            ..Default::default()
+        };
+        Effects::color_grade(&mut bright, &exposure, None);
+        assert!(bright.get_pixel(0, 0)[1] > 100);
+
+        let mut warm = RgbaImage::from_pixel(1, 1, Rgba([128, 128, 128, 255]));
+        let temperature = ColorGrade {
+            temperature: 1.0,
+            ..Default::default()
+        };
+        Effects::color_grade(&mut warm, &temperature, None);
+        let p = warm.get_pixel(0, 0);
+        assert!(p[0] > 128 && p[1] == 128 && p[2] < 128);
+    }
+
+    #[test]
+    fn test_cube_lut_sample() {
+        let lut = CubeLut::parse(INVERT_LUT).unwrap();
+        assert_eq!(lut.sample([0.0, 0.0, 0.0]), [1.0, 1.0, 1.0]);
+        assert_eq!(lut.sample([1.0, 0.0, 1.0]), [0.0, 1.0, 0.0]);
+
+        let mid = lut.sample([0.25, 0.5, 0.75]);
+        assert!((mid[0] - 0.75).abs() < 1e-5);
+        assert!((mid[1] - 0.5).abs() < 1e-5);
+        assert!((mid[2] - 0.25).abs() < 1e-5);
+
+        let mut image = RgbaImage::from_pixel(1, 1, Rgba([255, 0, 0, 255]));
+        Effects::color_grade(&mut image, &ColorGrade::default(), Some(&lut));
+        assert_eq!(image.get_pixel(0, 0), &Rgba([0, 255, 255, 255]));
+    }
+
+    #[test]
+    fn test_cube_lut_parse_errors() {
+        assert!(CubeLut::parse("0 0 0\n").is_err());
+        assert!(CubeLut::parse("LUT_3D_SIZE 2\n0 0 0\n").is_err());
+        assert!(CubeLut::parse("LUT_1D_SIZE 4\n").is_err());
+        assert!(CubeLut::parse("LUT_3D_SIZE 2\n0 0 zero\n").is_err());
+    }
+}
@@ -1,10 +1,26 @@
-use anyhow::{Context, Result};
-use std::path::Path;
-use std::process::Command;
+use crate::error::EncoderError;
+use crate::renderer::FrameBuffer;
+use crate::script::{Container, EncoderSettings, VideoCodec};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
 
 /// Handles video encoding using external FFmpeg process
 pub struct VideoEncoder;
 
+/// Format of the frames being encoded and the audio muxed with them
+#[derive(Debug, Clone, Default)]
+pub struct EncodeOptions<'a> {
+    pub fps: u32,
+    pub width: u32,
+    pub height: u32,
+    /// Keep the alpha channel of the frames
+    pub transparent: bool,
+    pub audio_path: Option<&'a Path>,
+    /// Seconds into the audio at which the first frame starts (for partial renders)
+    pub audio_offset: f32,
+}
+
 impl VideoEncoder {
     /// Check if FFmpeg is available
     pub fn is_available() -> bool {
@@ -15,59 +31,482 @@ impl VideoEncoder {
     ///
     /// # Arguments
     /// * `frame_pattern` - Pattern for input frames (e.g., "output/frame_%d.ppm")
-    /// * `output_path` - Path for the output video (e.g., "output.mp4")
-    /// * `fps` - Frames per second
-    /// * `width` - Video width
-    /// * `height` - Video height
+    /// * `start_number` - Number of the first frame file
+    /// * `settings` - Output path and codec options
+    /// * `options` - Frame format and audio
     pub fn encode(
-        frame_pattern: &str,
-        output_path: &Path,
-        fps: u32,
-        width: u32,
-        height: u32,
-        audio_path: Option<&Path>,
-    ) -> Result<()> {
+        frame_pattern: &Path,
+        start_number: u32,
+        settings: &EncoderSettings,
+        options: &EncodeOptions,
+    ) -> Result<(), EncoderError> {
         if !Self::is_available() {
-            anyhow::bail!("FFmpeg not found. Please install ffmpeg to enable video encoding.");
+            return Err(EncoderError::NotInstalled);
         }
 
-        println!("🎥 Encoding video to {}...", output_path.display());
+        let output_path = Self::output_path(settings, options.transparent);
+        let output_args =
+            Self::output_args(settings, options.audio_path.is_some(), options.transparent)?;
 
         let mut cmd = Command::new("ffmpeg");
         cmd.arg("-y") // Overwrite output
             .arg("-f")
             .arg("image2") // Input format
             .arg("-framerate")
-            .arg(fps.to_string())
+            .arg(options.fps.to_string())
+            .arg("-start_number")
+            .arg(start_number.to_string())
             .arg("-i")
             .arg(frame_pattern);
 
-        if let Some(audio) = audio_path {
-            cmd.arg("-i").arg(audio);
+        Self::add_audio_input(&mut cmd, options);
+        cmd.arg("-s")
+            .arg(format!("{}x{}", options.width, options.height));
+
+        let status = cmd
+            .args(&output_args)
+            .arg(&output_path)
+            .status()
+            .map_err(EncoderError::Spawn)?;
+
+        if !status.success() {
+            return Err(EncoderError::Failed {
+                code: status.code(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Start an FFmpeg process that encodes raw RGBA frames written to its stdin
+    ///
+    /// Frames are passed to the returned stream as they are rendered, so no
+    /// intermediate files are written.
+    pub fn start_stream(
+        settings: &EncoderSettings,
+        options: &EncodeOptions,
+    ) -> Result<EncoderStream, EncoderError> {
+        if !Self::is_available() {
+            return Err(EncoderError::NotInstalled);
         }
 
-        cmd.arg("-c:v")
-            .arg("libx264") // Video codec
+        let output_path = Self::output_path(settings, options.transparent);
+        let output_args =
+            Self::output_args(settings, options.audio_path.is_some(), options.transparent)?;
+
+        let mut cmd = Command::new("ffmpeg");
+        cmd.arg("-y") // Overwrite output
+            .arg("-f")
+            .arg("rawvideo") // Input format
             .arg("-pix_fmt")
-            .arg("yuv420p") // Pixel format for compatibility
+            .arg("rgba")
             .arg("-s")
-            .arg(format!("{}x{}", width, height));
+            .arg(format!("{}x{}", options.width, options.height))
+            .arg("-framerate")
+            .arg(options.fps.to_string())
+            .arg("-i")
+            .arg("-"); // Frames are read from stdin
+
+        Self::add_audio_input(&mut cmd, options);
 
-        if audio_path.is_some() {
-            cmd.arg("-c:a")
-                .arg("aac") // Audio codec
-                .arg("-shortest"); // Finish when shortest stream ends (video)
+        let mut child = cmd
+            .args(&output_args)
+            .arg(&output_path)
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(EncoderError::Spawn)?;
+
+        let stdin = child.stdin.take().ok_or(EncoderError::InputClosed)?;
+
+        Ok(EncoderStream {
+            child,
+            stdin: Some(stdin),
+            frame_size: (options.width * options.height * 4) as usize,
+        })
+    }
+
+    /// Add the audio track as a second input, starting where the video starts
+    fn add_audio_input(cmd: &mut Command, options: &EncodeOptions) {
+        if let Some(audio) = options.audio_path {
+            if options.audio_offset > 0.0 {
+                cmd.arg("-ss").arg(format!("{:.6}", options.audio_offset));
+            }
+            cmd.arg("-i").arg(audio);
         }
+    }
 
-        let status = cmd
-            .arg(output_path)
-            .status()
-            .context("Failed to execute ffmpeg")?;
+    /// Path of the video (or PNG sequence pattern) written for `settings`
+    pub fn output_path(settings: &EncoderSettings, transparent: bool) -> PathBuf {
+        settings
+            .output
+            .clone()
+            .unwrap_or_else(|| match Self::container(settings, transparent) {
+                Container::Png => PathBuf::from("frames/frame_%04d.png"),
+                container => PathBuf::from(format!("output.{}", container.extension())),
+            })
+    }
+
+    /// Container for `settings`, inferred from the output extension if unset
+    /// (ProRes in a mov by default for transparent renders, otherwise mp4)
+    fn container(settings: &EncoderSettings, transparent: bool) -> Container {
+        settings
+            .container
+            .or_else(|| {
+                let extension = settings.output.as_ref()?.extension()?.to_str()?;
+                Container::from_extension(extension)
+            })
+            .unwrap_or(if transparent {
+                Container::Mov
+            } else {
+                Container::Mp4
+            })
+    }
+
+    /// FFmpeg output options (codec, quality, pixel format, audio and container)
+    fn output_args(
+        settings: &EncoderSettings,
+        has_audio: bool,
+        transparent: bool,
+    ) -> Result<Vec<String>, EncoderError> {
+        let container = Self::container(settings, transparent);
+        if container == Container::Png {
+            return Self::png_sequence_args(settings, transparent);
+        }
+
+        let codec = settings.codec.unwrap_or(match container {
+            Container::Webm => VideoCodec::Vp9,
+            Container::Mov | Container::Mkv if transparent => VideoCodec::Prores,
+            _ => VideoCodec::H264,
+        });
+        if transparent && !matches!(codec, VideoCodec::Vp9 | VideoCodec::Prores) {
+            return Err(EncoderError::InvalidSettings(format!(
+                "{:?} video has no alpha channel; use ProRes (.mov), VP9 (.webm) or a PNG sequence for transparent output",
+                codec
+            )));
+        }
+
+        let supported = match container {
+            Container::Webm => matches!(codec, VideoCodec::Vp9 | VideoCodec::Av1),
+            Container::Mp4 => codec != VideoCodec::Prores,
+            Container::Mov => codec != VideoCodec::Vp9,
+            Container::Mkv => true,
+            Container::Png => unreachable!("PNG sequences have no video codec"),
+        };
+        if !supported {
+            return Err(EncoderError::InvalidSettings(format!(
+                "{:?} video cannot be stored in a {} container",
+                codec,
+                container.extension()
+            )));
+        }
+
+        let (encoder, pixel_format, max_crf) = match codec {
+            VideoCodec::H264 => ("libx264", "yuv420p", 51),
+            VideoCodec::H265 => ("libx265", "yuv420p", 51),
+            VideoCodec::Vp9 => ("libvpx-vp9", "yuv420p", 63),
+            VideoCodec::Av1 => ("libsvtav1", "yuv420p", 63),
+            VideoCodec::Prores => ("prores_ks", "yuv422p10le", 0),
+        };
+        let pixel_format = match codec {
+            VideoCodec::Vp9 if transparent => "yuva420p",
+            VideoCodec::Prores if transparent => "yuva444p10le",
+            _ => pixel_format,
+        };
+        let pixel_format = settings.pixel_format.as_deref().unwrap_or(pixel_format);
+
+        let mut args = vec![
+            "-c:v".to_string(),
+            encoder.to_string(),
+            "-pix_fmt".to_string(),
+            pixel_format.to_string(),
+        ];
+
+        if codec == VideoCodec::Prores && (settings.crf.is_some() || settings.bitrate.is_some()) {
+            return Err(EncoderError::InvalidSettings(
+                "ProRes quality is set by its profile (preset), not by CRF or bitrate".to_string(),
+            ));
+        }
+        if let Some(crf) = settings.crf {
+            if crf > max_crf {
+                return Err(EncoderError::InvalidSettings(format!(
+                    "CRF {} is out of range for {:?} (0-{})",
+                    crf, codec, max_crf
+                )));
+            }
+            args.extend(["-crf".to_string(), crf.to_string()]);
+        }
+        match &settings.bitrate {
+            Some(bitrate) => args.extend(["-b:v".to_string(), bitrate.clone()]),
+            // libvpx only uses constant quality mode when the bitrate is zero
+            None if codec == VideoCodec::Vp9 && settings.crf.is_some() => {
+                args.extend(["-b:v".to_string(), "0".to_string()])
+            }
+            None => {}
+        }
+
+        // Only the 4444 ProRes profiles carry alpha
+        let preset = match (&settings.preset, codec) {
+            (None, VideoCodec::Prores) if transparent => Some("4444".to_string()),
+            (preset, _) => preset.clone(),
+        };
+        if let Some(preset) = preset {
+            let option = match codec {
+                VideoCodec::Prores => "-profile:v",
+                VideoCodec::Vp9 => "-deadline",
+                _ => "-preset",
+            };
+            args.extend([option.to_string(), preset]);
+        }
+
+        // libvpx drops the alpha plane when alternate reference frames are on
+        if codec == VideoCodec::Vp9 && transparent {
+            args.extend(["-auto-alt-ref".to_string(), "0".to_string()]);
+        }
+
+        // Apple players only recognize H.265 tagged as hvc1
+        if codec == VideoCodec::H265 && matches!(container, Container::Mp4 | Container::Mov) {
+            args.extend(["-tag:v".to_string(), "hvc1".to_string()]);
+        }
+
+        if has_audio {
+            let audio_codec = match (container, codec) {
+                (Container::Webm, _) => "libopus",
+                (_, VideoCodec::Prores) => "pcm_s16le",
+                _ => "aac",
+            };
+            args.extend(["-c:a".to_string(), audio_codec.to_string()]);
+            if let Some(bitrate) = &settings.audio_bitrate {
+                if audio_codec != "pcm_s16le" {
+                    args.extend(["-b:a".to_string(), bitrate.clone()]);
+                }
+            }
+            // Finish when shortest stream ends (video)
+            args.push("-shortest".to_string());
+        }
+
+        let format = match container {
+            Container::Mkv => "matroska",
+            other => other.extension(),
+        };
+        args.extend(["-f".to_string(), format.to_string()]);
+
+        Ok(args)
+    }
+
+    /// FFmpeg output options for a numbered PNG sequence (codec and quality
+    /// settings don't apply, and audio is dropped)
+    fn png_sequence_args(
+        settings: &EncoderSettings,
+        transparent: bool,
+    ) -> Result<Vec<String>, EncoderError> {
+        let output = Self::output_path(settings, transparent);
+        if !output.to_string_lossy().contains('%') {
+            return Err(EncoderError::InvalidSettings(format!(
+                "PNG sequence output {} needs a frame number pattern such as frame_%04d.png",
+                output.display()
+            )));
+        }
+
+        let pixel_format = if transparent { "rgba" } else { "rgb24" };
+        let pixel_format = settings.pixel_format.as_deref().unwrap_or(pixel_format);
+        Ok([
+            "-c:v",
+            "png",
+            "-pix_fmt",
+            pixel_format,
+            "-an",
+            "-f",
+            "image2",
+        ]
+        .map(String::from)
+        .to_vec())
+    }
+}
+
+/// A running FFmpeg process fed with raw frames (see `VideoEncoder::start_stream`)
+pub struct EncoderStream {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    frame_size: usize,
+}
+
+impl EncoderStream {
+    /// Send the next frame to the encoder
+    pub fn write_frame(&mut self, frame: &FrameBuffer) -> Result<(), EncoderError> {
+        let data = frame.as_bytes();
+        if data.len() != self.frame_size {
+            return Err(EncoderError::FrameSize {
+                expected: self.frame_size,
+                actual: data.len(),
+            });
+        }
+
+        self.stdin
+            .as_mut()
+            .ok_or(EncoderError::InputClosed)?
+            .write_all(data)
+            .map_err(EncoderError::Write)
+    }
+
+    /// Close the input and wait for FFmpeg to finish writing the video
+    pub fn finish(mut self) -> Result<(), EncoderError> {
+        // Dropping stdin signals the end of the stream
+        drop(self.stdin.take());
 
+        let status = self.child.wait().map_err(EncoderError::Spawn)?;
         if !status.success() {
-            anyhow::bail!("FFmpeg encoding failed");
+            return Err(EncoderError::Failed {
+                code: status.code(),
+            });
         }
 
         Ok(())
     }
 }
+
+impl Drop for EncoderStream {
+    fn drop(&mut self) {
+        // Abandoned without `finish` (e.g. a render error): stop FFmpeg
+        if self.stdin.take().is_some() {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_output_args() {
+        let settings = EncoderSettings::default();
+        assert_eq!(
+            VideoEncoder::output_path(&settings, false),
+            PathBuf::from("output.mp4")
+        );
+        assert_eq!(
+            VideoEncoder::output_args(&settings, true, false).unwrap(),
+            [
+                "-c:v",
+                "libx264",
+                "-pix_fmt",
+                "yuv420p",
+                "-c:a",
+                "aac",
+                "-shortest",
+                "-f",
+                "mp4"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_output_args_from_settings() {
+        // The container and default codec follow the output extension
+        let settings = EncoderSettings {
+            output: Some(PathBuf::from("renders/final.webm")),
+            crf: Some(30),
+            audio_bitrate: Some("128k".into()),
+            ..Default::default()
+        };
+        let args = VideoEncoder::output_args(&settings, true, false)
+            .unwrap()
+            .join(" ");
+        assert_eq!(
+            args,
+            "-c:v libvpx-vp9 -pix_fmt yuv420p -crf 30 -b:v 0 -c:a libopus -b:a 128k -shortest -f webm"
+        );
+
+        let settings = EncoderSettings {
+            codec: Some(VideoCodec::Prores),
+            container: Some(Container::Mov),
+            preset: Some("hq".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            VideoEncoder::output_path(&settings, false),
+            PathBuf::from("output.mov")
+        );
+        let args = VideoEncoder::output_args(&settings, false, false)
+            .unwrap()
+            .join(" ");
+        assert_eq!(
+            args,
+            "-c:v prores_ks -pix_fmt yuv422p10le -profile:v hq -f mov"
+        );
+    }
+
+    #[test]
+    fn test_invalid_output_args() {
+        let prores_in_webm = EncoderSettings {
+            container: Some(Container::Webm),
+            codec: Some(VideoCodec::Prores),
+            ..Default::default()
+        };
+        assert!(matches!(
+            VideoEncoder::output_args(&prores_in_webm, false, false),
+            Err(EncoderError::InvalidSettings(_))
+        ));
+
+        let crf_out_of_range = EncoderSettings {
+            crf: Some(60),
+            ..Default::default()
+        };
+        assert!(VideoEncoder::output_args(&crf_out_of_range, false, false).is_err());
+
+        let transparent_mp4 = EncoderSettings {
+            output: Some(PathBuf::from("overlay.mp4")),
+            ..Default::default()
+        };
+        assert!(VideoEncoder::output_args(&transparent_mp4, false, true).is_err());
+
+        let png_without_pattern = EncoderSettings {
+            output: Some(PathBuf::from("overlay.png")),
+            ..Default::default()
+        };
+        assert!(VideoEncoder::output_args(&png_without_pattern, false, true).is_err());
+    }
+
+    #[test]
+    fn test_transparent_output_args() {
+        // ProRes 4444 in a mov unless told otherwise
+        let settings = EncoderSettings::default();
+        assert_eq!(
+            VideoEncoder::output_path(&settings, true),
+            PathBuf::from("output.mov")
+        );
+        let args = VideoEncoder::output_args(&settings, true, true)
+            .unwrap()
+            .join(" ");
+        assert_eq!(
+            args,
+            "-c:v prores_ks -pix_fmt yuva444p10le -profile:v 4444 -c:a pcm_s16le -shortest -f mov"
+        );
+
+        let webm = EncoderSettings {
+            container: Some(Container::Webm),
+            ..Default::default()
+        };
+        let args = VideoEncoder::output_args(&webm, false, true)
+            .unwrap()
+            .join(" ");
+        assert_eq!(
+            args,
+            "-c:v libvpx-vp9 -pix_fmt yuva420p -auto-alt-ref 0 -f webm"
+        );
+
+        let png = EncoderSettings {
+            container: Some(Container::Png),
+            ..Default::default()
+        };
+        assert_eq!(
+            VideoEncoder::output_path(&png, true),
+            PathBuf::from("frames/frame_%04d.png")
+        );
+        let args = VideoEncoder::output_args(&png, true, true)
+            .unwrap()
+            .join(" ");
+        assert_eq!(args, "-c:v png -pix_fmt rgba -an -f image2");
+    }
+}
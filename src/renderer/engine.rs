@@ -1,11 +1,23 @@
 use crate::assets::AssetLoader;
-use crate::renderer::{Compositor, FrameBuffer, GpuRenderer, Timeline};
-use crate::script::{Layer, VideoScript};
-use anyhow::Result;
+use crate::error::{self, RenderError};
+use crate::events::{ConsoleObserver, FrameProgress, SharedObserver};
+use crate::renderer::manifest::MANIFEST_FILE;
+use crate::renderer::{
+    Affine2, Capabilities, Compositor, CubeLut, Effects, FrameBuffer, FrameFormat, GpuRenderer,
+    RenderManifest, Renderer, SceneCache, TextRasterizer, Timeline, Transitions,
+};
+use crate::script::{Effect, Layer, Position, Transform, VideoPlayback, VideoScript};
+use crate::video::VideoDecoder;
+use ab_glyph::FontArc;
+use anyhow::{Context, Result};
 use dashmap::DashMap;
-use image::GenericImageView;
+use image::{GenericImageView, RgbaImage};
 use rayon::prelude::*;
-use std::sync::Arc;
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
 
 /// Cached texture entry: (BindGroup, width, height)
 type TextureCacheEntry = (Arc<wgpu::BindGroup>, u32, u32);
@@ -18,101 +30,336 @@ pub struct RenderEngine {
     #[allow(dead_code)]
     gpu_renderer: Option<GpuRenderer>,
     texture_cache: Arc<DashMap<std::path::PathBuf, TextureCacheEntry>>,
+    /// Decoded images for the CPU path, keyed by script path
+    image_cache: Arc<DashMap<std::path::PathBuf, Arc<RgbaImage>>>,
+    /// Parsed fonts keyed by script path (`None` if the font failed to load)
+    font_cache: Arc<DashMap<std::path::PathBuf, Option<FontArc>>>,
+    /// Image layers with blur/color grade applied, keyed by source and effects
+    effect_cache: Arc<DashMap<String, Arc<RgbaImage>>>,
+    /// Parsed `.cube` LUTs keyed by script path (`None` if the LUT failed to load)
+    lut_cache: Arc<DashMap<std::path::PathBuf, Option<Arc<CubeLut>>>>,
+    /// The outgoing scene's frame while a transition is rendered
+    transition_buffer: FrameBuffer,
+    /// Video decoders keyed by script path (`None` if the video failed to open),
+    /// shared by parallel workers so each video is decoded by one ffmpeg process
+    video_decoders: Arc<DashMap<std::path::PathBuf, Option<Arc<Mutex<VideoDecoder>>>>>,
+    /// Frames rendered or waiting to be consumed at once on the CPU path
+    /// (1 renders sequentially)
+    max_in_flight_frames: usize,
+    /// Frames produced by `render` and `render_frames` (the whole timeline by default)
+    frames: Range<u32>,
+    /// Previously rendered frames of unchanged scenes (disabled by default)
+    scene_cache: Option<Arc<SceneCache>>,
+    /// Receives rendering progress and asset warnings
+    observer: SharedObserver,
 }
 
 impl RenderEngine {
-    /// Create new render engine from script
+    /// Everything in the script format
+    pub const CAPABILITIES: Capabilities = Capabilities {
+        layers: &["video", "image", "text"],
+        effects: &["fade_in", "fade_out", "blur", "color_grade"],
+        transitions: &["cut", "fade", "dissolve", "wipe"],
+        transparency: true,
+        streaming: true,
+    };
+
+    /// Create new render engine from script, reporting to the console
     pub fn new(script: VideoScript, use_gpu: bool) -> Self {
+        Self::with_observer(script, use_gpu, Arc::new(ConsoleObserver))
+    }
+
+    /// Create a render engine that reports its progress to `observer`
+    pub fn with_observer(script: VideoScript, use_gpu: bool, observer: SharedObserver) -> Self {
         let (width, height) = script.metadata.resolution.dimensions();
         let timeline = Timeline::from_script(&script);
         let frame_buffer = FrameBuffer::new(width, height);
+        let transition_buffer = FrameBuffer::new(width, height);
 
         // Try to initialize GPU renderer (optional - falls back to CPU if fails)
-        let gpu_renderer = if use_gpu {
+        let mut gpu_renderer = if use_gpu {
             pollster::block_on(async { GpuRenderer::new(width, height).await.ok() })
         } else {
             None
         };
+        if let Some(gpu) = &mut gpu_renderer {
+            gpu.set_transparent(script.metadata.transparent);
+        }
 
         if gpu_renderer.is_some() {
-            println!("✨ GPU renderer initialized successfully");
+            observer.info("GPU renderer initialized successfully");
         } else {
-            println!(
-                "ℹ️  Using CPU rendering (GPU unavailable, initialization failed, or disabled)"
-            );
+            observer
+                .info("Using CPU rendering (GPU unavailable, initialization failed, or disabled)");
         }
 
+        let frames = 0..timeline.total_frames();
+
         Self {
             script,
             timeline,
             frame_buffer,
             gpu_renderer,
             texture_cache: Arc::new(DashMap::new()),
+            image_cache: Arc::new(DashMap::new()),
+            font_cache: Arc::new(DashMap::new()),
+            effect_cache: Arc::new(DashMap::new()),
+            lut_cache: Arc::new(DashMap::new()),
+            transition_buffer,
+            video_decoders: Arc::new(DashMap::new()),
+            max_in_flight_frames: 1,
+            frames,
+            scene_cache: None,
+            observer,
         }
     }
 
-    /// Render a single frame
+    /// Only render `frames` (e.g. from `Timeline::frame_range`)
+    pub fn set_frame_range(&mut self, frames: Range<u32>) {
+        self.frames = frames;
+    }
+
+    /// Render independent frames in parallel when running on the CPU, keeping at
+    /// most `frames` frames in memory at once (0 picks twice the number of threads)
+    pub fn set_max_in_flight_frames(&mut self, frames: usize) {
+        self.max_in_flight_frames = if frames == 0 {
+            rayon::current_num_threads() * 2
+        } else {
+            frames
+        };
+    }
+
+    /// Reuse frames of unchanged scenes from the cache in `dir`, and store the
+    /// frames rendered from now on there (assets are resolved against `base_path`)
+    pub fn enable_scene_cache(
+        &mut self,
+        dir: &std::path::Path,
+        base_path: &std::path::Path,
+    ) -> Result<(), RenderError> {
+        let (width, height) = self.frame_buffer.dimensions();
+        let settings = format!(
+            "native {} {}x{} {}fps transparent={} gpu={}",
+            env!("CARGO_PKG_VERSION"),
+            width,
+            height,
+            self.script.metadata.fps,
+            self.script.metadata.transparent,
+            self.gpu_renderer.is_some()
+        );
+        let cache = SceneCache::new(&self.script, base_path, dir, &[settings.as_bytes()], false)
+            .map_err(|source| RenderError::Output {
+                path: dir.to_path_buf(),
+                source,
+            })?;
+        self.scene_cache = Some(Arc::new(cache));
+        Ok(())
+    }
+
+    /// Create a CPU engine for the same script with its own frame buffers,
+    /// sharing the asset caches and video decoders
+    fn fork(&self) -> Self {
+        let (width, height) = self.frame_buffer.dimensions();
+        Self {
+            script: self.script.clone(),
+            timeline: self.timeline.clone(),
+            frame_buffer: FrameBuffer::new(width, height),
+            gpu_renderer: None,
+            texture_cache: self.texture_cache.clone(),
+            image_cache: self.image_cache.clone(),
+            font_cache: self.font_cache.clone(),
+            effect_cache: self.effect_cache.clone(),
+            lut_cache: self.lut_cache.clone(),
+            transition_buffer: FrameBuffer::new(width, height),
+            video_decoders: self.video_decoders.clone(),
+            // Workers render single frames, but open decoders for the whole pool
+            max_in_flight_frames: self.max_in_flight_frames,
+            frames: self.frames.clone(),
+            scene_cache: self.scene_cache.clone(),
+            observer: self.observer.clone(),
+        }
+    }
+
+    /// Where the engine reports its progress
+    pub fn observer(&self) -> &SharedObserver {
+        &self.observer
+    }
+
+    /// Render a single frame (or load it from the scene cache, if enabled)
     pub fn render_frame(
         &mut self,
         frame_number: u32,
-        _asset_loader: &mut AssetLoader,
+        asset_loader: &mut AssetLoader,
+    ) -> Result<(), RenderError> {
+        let cache = self.scene_cache.clone();
+        if let Some(cache) = &cache {
+            if cache.load(frame_number, &mut self.frame_buffer) {
+                return Ok(());
+            }
+        }
+
+        self.render_timeline_frame(frame_number, asset_loader)
+            .map_err(|source| self.frame_error(frame_number, source))?;
+        let Some(cache) = cache else {
+            return Ok(());
+        };
+        if let Err(e) = cache.store(frame_number, &self.frame_buffer) {
+            self.observer
+                .warning(format!("Failed to cache frame {}: {}", frame_number, e));
+        }
+        Ok(())
+    }
+
+    /// Render a frame on a parallel worker and return a copy of it. A panic
+    /// becomes the frame's error, so the frame is always reported and the
+    /// render waiting on it stops instead of hanging.
+    fn render_frame_unwinding(
+        &mut self,
+        frame_number: u32,
+        asset_loader: &mut AssetLoader,
+    ) -> Result<FrameBuffer, RenderError> {
+        let render = AssertUnwindSafe(|| {
+            self.render_frame(frame_number, asset_loader)
+                .map(|()| self.frame_buffer.clone())
+        });
+        panic::catch_unwind(render).unwrap_or_else(|payload| {
+            let reason = payload
+                .downcast_ref::<&str>()
+                .map(|reason| reason.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown reason".to_string());
+            Err(self.frame_error(
+                frame_number,
+                anyhow::anyhow!("Render worker panicked: {}", reason),
+            ))
+        })
+    }
+
+    /// Failure to render `frame`, attributed to the scene playing at it
+    fn frame_error(&self, frame: u32, source: anyhow::Error) -> RenderError {
+        RenderError::Frame {
+            frame,
+            scene: self.timeline.get_scene_at_frame(frame).map(String::from),
+            source,
+        }
+    }
+
+    /// Render the scene (or transition) playing at `frame_number`
+    fn render_timeline_frame(
+        &mut self,
+        frame_number: u32,
+        asset_loader: &mut AssetLoader,
     ) -> Result<()> {
-        // Clear frame
-        self.frame_buffer.clear([0, 0, 0, 255]);
+        // Over a transition, render both scenes and blend them
+        if let Some(frame) = self.timeline.transition_at_frame(frame_number) {
+            let transition = frame.transition.clone();
+            let progress = frame.progress;
+            let (from_scene, from_time) = (frame.from_scene.to_string(), frame.from_time);
+            let (to_scene, to_time) = (frame.to_scene.to_string(), frame.to_time);
+
+            self.render_scene(&from_scene, from_time, asset_loader)?;
+            std::mem::swap(&mut self.frame_buffer, &mut self.transition_buffer);
+            self.render_scene(&to_scene, to_time, asset_loader)?;
+
+            Transitions::blend(
+                &self.transition_buffer,
+                &mut self.frame_buffer,
+                &transition,
+                progress,
+            );
+            return Ok(());
+        }
 
         // Get current scene ID
         if let Some(scene_id) = self.timeline.get_scene_at_frame(frame_number) {
             // Clone the scene ID to avoid borrow checker issues
             let scene_id = scene_id.to_string();
+            let scene_time = self
+                .timeline
+                .scene_time_at_frame(frame_number)
+                .unwrap_or(0.0);
+
+            self.render_scene(&scene_id, scene_time, asset_loader)?;
+        } else {
+            self.frame_buffer.clear(self.background());
+        }
+
+        Ok(())
+    }
+
+    /// Render a scene at `scene_time` seconds into it
+    fn render_scene(
+        &mut self,
+        scene_id: &str,
+        scene_time: f32,
+        asset_loader: &mut AssetLoader,
+    ) -> Result<()> {
+        // Clear frame
+        self.frame_buffer.clear(self.background());
+
+        // Find and render the scene
+        if let Some(scene) = self.script.scenes.iter().find(|s| s.id == scene_id) {
+            // Evaluate keyframed properties and fades at the current time within
+            // the scene (this also clones the layers to avoid borrowing issues)
+            let layers: Vec<_> = scene
+                .layers
+                .iter()
+                .map(|layer| {
+                    let mut layer = layer.at_time(scene_time);
+                    let fade = Effects::fade_opacity(layer.effects(), scene_time, scene.duration);
+                    layer.transform_mut().opacity *= fade;
+                    layer
+                })
+                .collect();
+
+            // Parallel: Load all images from disk concurrently
+            // This is the IO-bound bottleneck, so parallelizing it helps significantly
+            let use_gpu = self.gpu_renderer.is_some();
+            let loaded_images: Vec<_> = layers
+                .par_iter()
+                .filter_map(|layer| {
+                    if let Layer::Image { source, .. } = layer {
+                        let cached = if use_gpu {
+                            self.texture_cache.contains_key(source)
+                        } else {
+                            self.image_cache.contains_key(source)
+                        };
 
-            // Find and render the scene
-            if let Some(scene) = self.script.scenes.iter().find(|s| s.id == scene_id) {
-                // Collect layers to avoid borrowing issues
-                let layers: Vec<_> = scene.layers.clone();
-
-                // Parallel: Load all images from disk concurrently
-                // This is the IO-bound bottleneck, so parallelizing it helps significantly
-                let loaded_images: Vec<_> = layers
-                    .par_iter()
-                    .filter_map(|layer| {
-                        if let Layer::Image { source, .. } = layer {
-                            if !self.texture_cache.contains_key(source) {
-                                let full_path = if source.is_absolute() {
-                                    source.clone()
-                                } else {
-                                    _asset_loader.base_path().join(source)
-                                };
-
-                                if full_path.exists() {
-                                    if let Ok(img) = image::open(&full_path) {
-                                        return Some((source.clone(), img));
-                                    }
+                        if !cached {
+                            let full_path = asset_loader.resolve_path(source);
+
+                            if full_path.exists() {
+                                if let Ok(img) = image::open(&full_path) {
+                                    return Some((source.clone(), img));
                                 }
                             }
                         }
-                        None
-                    })
-                    .collect();
-
-                // Sequential: Create GPU textures from loaded images
-                // GPURenderer is not Sync, so this must be done sequentially
-                if let Some(gpu) = &self.gpu_renderer {
-                    for (source, img) in loaded_images {
-                        let dims = img.dimensions();
-                        let bind_group = gpu.create_texture(&img);
-                        self.texture_cache
-                            .insert(source, (bind_group, dims.0, dims.1));
                     }
-                }
+                    None
+                })
+                .collect();
 
-                // Sequential: Render each layer (GPU command submission) (GPU command submission)
-                for layer in &layers {
-                    self.render_layer(layer, _asset_loader)?;
+            // Sequential: Create GPU textures from loaded images
+            // GPURenderer is not Sync, so this must be done sequentially
+            if let Some(gpu) = &self.gpu_renderer {
+                for (source, img) in loaded_images {
+                    let dims = img.dimensions();
+                    let bind_group = gpu.create_texture(&img);
+                    self.texture_cache
+                        .insert(source, (bind_group, dims.0, dims.1));
                 }
+            } else {
+                for (source, img) in loaded_images {
+                    self.image_cache.insert(source, Arc::new(img.to_rgba8()));
+                }
+            }
 
-                // Flush GPU commands after rendering all layers
-                self.flush_gpu()?;
+            // Sequential: Render each layer (GPU command submission) (GPU command submission)
+            for layer in &layers {
+                self.render_layer(layer, scene_time, asset_loader)?;
             }
+
+            // Flush GPU commands after rendering all layers
+            self.flush_gpu()?;
         }
 
         Ok(())
@@ -126,25 +373,47 @@ impl RenderEngine {
         Ok(())
     }
 
-    /// Render a single layer
-    fn render_layer(&mut self, layer: &Layer, asset_loader: &AssetLoader) -> Result<()> {
+    /// Render a single layer `scene_time` seconds into its scene
+    fn render_layer(
+        &mut self,
+        layer: &Layer,
+        scene_time: f32,
+        asset_loader: &mut AssetLoader,
+    ) -> Result<()> {
         match layer {
             Layer::Image {
-                source, transform, ..
+                source,
+                transform,
+                effects,
+                ..
             } => {
-                let (x, y) = Compositor::apply_transform(0, 0, transform);
-                let color = [255, 255, 255, 255];
+                let opacity = transform.opacity.clamp(0.0, 1.0);
+                let color = [255, 255, 255, (opacity * 255.0).round() as u8];
 
-                if let Some(gpu) = &mut self.gpu_renderer {
+                if Effects::has_pixel_effects(effects) {
+                    if let Some(image) = self.get_image(source, asset_loader) {
+                        let key = format!("{}|{:?}", source.display(), effects);
+                        let processed = match self.effect_cache.get(&key) {
+                            Some(entry) => entry.value().clone(),
+                            None => {
+                                let processed =
+                                    Arc::new(self.apply_effects(&image, effects, asset_loader));
+                                self.effect_cache.insert(key, processed.clone());
+                                processed
+                            }
+                        };
+
+                        return self.draw_layer_image(
+                            &processed,
+                            transform,
+                            (image.width() as f32, image.height() as f32),
+                            Effects::padding(effects),
+                        );
+                    }
+                } else if let Some(gpu) = &mut self.gpu_renderer {
                     // Load texture if not in cache
                     if !self.texture_cache.contains_key(source) {
-                        // Resolve path using AssetLoader (hacky access to private method or just join)
-                        // AssetLoader::resolve_path is private. But we can use base_path.
-                        let full_path = if source.is_absolute() {
-                            source.clone()
-                        } else {
-                            asset_loader.base_path().join(source)
-                        };
+                        let full_path = asset_loader.resolve_path(source);
 
                         if full_path.exists() {
                             if let Ok(img) = image::open(&full_path) {
@@ -153,112 +422,573 @@ impl RenderEngine {
                                 self.texture_cache
                                     .insert(source.clone(), (bind_group, dims.0, dims.1));
                             } else {
-                                println!(
+                                self.observer.warning(format!(
                                     "Failed to load image for texture: {}",
                                     full_path.display()
-                                );
+                                ));
                             }
                         }
                     }
 
                     if let Some(entry) = self.texture_cache.get(source) {
                         let (bind_group, w, h) = entry.value();
-                        // Apply scale from transform
-                        let scale = transform.scale;
-                        let draw_w = (*w as f32 * scale) as u32;
-                        let draw_h = (*h as f32 * scale) as u32;
+                        let (w, h) = (*w as f32, *h as f32);
+                        let matrix = Compositor::layer_matrix(transform, w, h);
 
-                        gpu.draw_texture(bind_group.clone(), x, y, draw_w, draw_h, color)?;
-                    } else {
-                        // Fallback to colored rect if texture failed
-                        gpu.fill_rect(
-                            &mut self.frame_buffer,
-                            x,
-                            y,
-                            100,
-                            100,
-                            [100, 100, 200, 255],
-                        )?;
+                        gpu.draw_texture_quad(bind_group.clone(), matrix.corners(w, h), color)?;
+                        return Ok(());
                     }
-                } else {
-                    Compositor::fill_rect(
+                } else if let Some(image) = self.get_image(source, asset_loader) {
+                    let matrix = Compositor::layer_matrix(
+                        transform,
+                        image.width() as f32,
+                        image.height() as f32,
+                    );
+
+                    Compositor::draw_image_transformed(
                         &mut self.frame_buffer,
-                        x,
-                        y,
-                        100,
-                        100,
-                        [100, 100, 200, 255],
+                        &image,
+                        &matrix,
+                        opacity,
                     );
+                    return Ok(());
                 }
-            }
-            Layer::Video { transform, .. } => {
-                // Placeholder: draw colored rectangle for video
-                let (x, y) = Compositor::apply_transform(0, 0, transform);
-                let color = [200, 100, 100, 255];
 
-                if let Some(gpu) = &self.gpu_renderer {
-                    gpu.fill_rect(&mut self.frame_buffer, x, y, 100, 100, color)?;
-                } else {
-                    Compositor::fill_rect(&mut self.frame_buffer, x, y, 100, 100, color);
-                }
+                // Fallback to colored rect if the image failed to load
+                self.draw_placeholder(transform, 100, 100, [100, 100, 200, 255])?;
             }
+            Layer::Video {
+                source,
+                transform,
+                effects,
+                playback,
+                ..
+            } => match self.get_video_frame(source, playback, scene_time, asset_loader) {
+                Ok(Some(frame)) => {
+                    let size = (frame.width() as f32, frame.height() as f32);
+                    if Effects::has_pixel_effects(effects) {
+                        let processed = self.apply_effects(&frame, effects, asset_loader);
+                        self.draw_layer_image(
+                            &processed,
+                            transform,
+                            size,
+                            Effects::padding(effects),
+                        )?;
+                    } else {
+                        self.draw_layer_image(&frame, transform, size, 0)?;
+                    }
+                }
+                // The clip has ended
+                Ok(None) => {}
+                Err(_) => {
+                    // Placeholder: draw colored rectangle for video that can't be decoded
+                    self.draw_placeholder(transform, 100, 100, [200, 100, 100, 255])?;
+                }
+            },
             Layer::Text {
                 content,
-                position,
+                font,
+                font_size,
                 color,
+                position,
+                transform,
+                effects,
                 ..
             } => {
                 let rgba = [color.r, color.g, color.b, color.a];
-                Compositor::draw_text_placeholder(
-                    &mut self.frame_buffer,
-                    content,
-                    position.x,
-                    position.y,
-                    rgba,
-                );
+
+                // The text position is offset by the transform position
+                let transform = Transform {
+                    position: Position {
+                        x: position.x + transform.position.x,
+                        y: position.y + transform.position.y,
+                    },
+                    ..transform.clone()
+                };
+
+                let Some(font) = self.get_font(font, asset_loader) else {
+                    let width = (content.len() as u32 * 8).min(200);
+                    return self.draw_placeholder(&transform, width, 16, rgba);
+                };
+
+                // Rasterize at the final size so scaled text stays sharp; the
+                // matrix then only rotates around the anchor
+                let image =
+                    TextRasterizer::rasterize(&font, content, font_size * transform.scale, rgba);
+                let size = (image.width() as f32, image.height() as f32);
+                let transform = Transform {
+                    scale: 1.0,
+                    ..transform
+                };
+
+                if Effects::has_pixel_effects(effects) {
+                    let processed = self.apply_effects(&image, effects, asset_loader);
+                    self.draw_layer_image(&processed, &transform, size, Effects::padding(effects))?;
+                } else {
+                    self.draw_layer_image(&image, &transform, size, 0)?;
+                }
             }
         }
 
         Ok(())
     }
 
+    /// Draw a per-frame layer image (rasterized text or an effect result).
+    ///
+    /// `size` is the layer size the transform applies to; the image may extend
+    /// `padding` pixels beyond it on each side (e.g. a blur).
+    fn draw_layer_image(
+        &mut self,
+        image: &RgbaImage,
+        transform: &Transform,
+        size: (f32, f32),
+        padding: u32,
+    ) -> Result<()> {
+        let opacity = transform.opacity.clamp(0.0, 1.0);
+        let padding = padding as f32;
+        let matrix = Affine2::translation(-padding, -padding)
+            .then(&Compositor::layer_matrix(transform, size.0, size.1));
+
+        if let Some(gpu) = &self.gpu_renderer {
+            // Upload as a texture so the image is composited in order
+            // with the other GPU-batched layers
+            let bind_group = gpu.create_texture(&image::DynamicImage::ImageRgba8(image.clone()));
+            let tint = [255, 255, 255, (opacity * 255.0).round() as u8];
+            let (w, h) = (image.width() as f32, image.height() as f32);
+            gpu.draw_texture_quad(bind_group, matrix.corners(w, h), tint)?;
+        } else {
+            Compositor::draw_image_transformed(&mut self.frame_buffer, image, &matrix, opacity);
+        }
+
+        Ok(())
+    }
+
+    /// Apply the layer's blur and color grade effects to its image
+    fn apply_effects(
+        &self,
+        image: &RgbaImage,
+        effects: &[Effect],
+        asset_loader: &AssetLoader,
+    ) -> RgbaImage {
+        Effects::apply(image, effects, |path| self.get_lut(path, asset_loader))
+    }
+
+    /// Draw a transformed solid rectangle standing in for content that could not be loaded
+    fn draw_placeholder(
+        &mut self,
+        transform: &Transform,
+        width: u32,
+        height: u32,
+        color: [u8; 4],
+    ) -> Result<()> {
+        let matrix = Compositor::layer_matrix(transform, width as f32, height as f32);
+        let alpha = color[3] as f32 * transform.opacity.clamp(0.0, 1.0);
+        let color = [color[0], color[1], color[2], alpha.round() as u8];
+
+        if let Some(gpu) = &self.gpu_renderer {
+            gpu.fill_quad(matrix.corners(width as f32, height as f32), color)?;
+        } else {
+            Compositor::fill_rect_transformed(
+                &mut self.frame_buffer,
+                &matrix,
+                width,
+                height,
+                color,
+            );
+        }
+        Ok(())
+    }
+
+    /// Get a decoded image from the CPU cache, loading it from disk on first use
+    fn get_image(
+        &self,
+        source: &std::path::Path,
+        asset_loader: &AssetLoader,
+    ) -> Option<Arc<RgbaImage>> {
+        if let Some(entry) = self.image_cache.get(source) {
+            return Some(entry.value().clone());
+        }
+
+        let full_path = asset_loader.resolve_path(source);
+        if !full_path.exists() {
+            return None;
+        }
+
+        match image::open(&full_path) {
+            Ok(img) => {
+                let image = Arc::new(img.to_rgba8());
+                self.image_cache.insert(source.to_path_buf(), image.clone());
+                Some(image)
+            }
+            Err(_) => {
+                self.observer
+                    .warning(format!("Failed to load image: {}", full_path.display()));
+                None
+            }
+        }
+    }
+
+    /// Get a parsed font from the cache, loading it through the asset loader on first use
+    fn get_font(&self, path: &std::path::Path, asset_loader: &mut AssetLoader) -> Option<FontArc> {
+        if let Some(entry) = self.font_cache.get(path) {
+            return entry.value().clone();
+        }
+
+        let font = asset_loader
+            .load_font(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|asset| TextRasterizer::load_font(asset.data.clone()));
+
+        let font = match font {
+            Ok(font) => Some(font),
+            Err(e) => {
+                self.observer.warning(format!(
+                    "Failed to load font {}, falling back to placeholder: {}",
+                    path.display(),
+                    e
+                ));
+                None
+            }
+        };
+
+        self.font_cache.insert(path.to_path_buf(), font.clone());
+        font
+    }
+
+    /// Decode the frame of a video layer shown `scene_time` seconds into its scene,
+    /// opening the decoder on first use.
+    ///
+    /// Returns `Ok(None)` once a non-looping clip has ended.
+    fn get_video_frame(
+        &mut self,
+        source: &std::path::Path,
+        playback: &VideoPlayback,
+        scene_time: f32,
+        asset_loader: &AssetLoader,
+    ) -> Result<Option<Arc<RgbaImage>>> {
+        let observer = &self.observer;
+        let decoder = self
+            .video_decoders
+            .entry(source.to_path_buf())
+            .or_insert_with(|| {
+                let full_path = asset_loader.resolve_path(source);
+                match VideoDecoder::open(&full_path) {
+                    Ok(mut decoder) => {
+                        // Parallel workers ask for frames up to the in-flight
+                        // window out of order
+                        decoder.set_history(self.max_in_flight_frames);
+                        Some(Arc::new(Mutex::new(decoder)))
+                    }
+                    Err(e) => {
+                        observer.warning(format!(
+                            "Failed to open video {}, falling back to placeholder: {}",
+                            full_path.display(),
+                            e
+                        ));
+                        None
+                    }
+                }
+            })
+            .clone()
+            .context("Video unavailable")?;
+        let mut decoder = decoder
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Video decoder failed on another frame"))?;
+
+        let Some(time) = playback.source_time(scene_time, decoder.info().duration) else {
+            return Ok(None);
+        };
+
+        let index = decoder.frame_index(time);
+        decoder.frame(index).map(Some).map_err(|e| {
+            observer.warning(format!("Failed to decode video frame {}: {}", index, e));
+            e
+        })
+    }
+
+    /// Get a parsed LUT from the cache, loading it from disk on first use
+    fn get_lut(&self, path: &std::path::Path, asset_loader: &AssetLoader) -> Option<Arc<CubeLut>> {
+        if let Some(entry) = self.lut_cache.get(path) {
+            return entry.value().clone();
+        }
+
+        let full_path = asset_loader.resolve_path(path);
+        let lut = std::fs::read_to_string(&full_path)
+            .map_err(anyhow::Error::from)
+            .and_then(|source| CubeLut::parse(&source));
+
+        let lut = match lut {
+            Ok(lut) => Some(Arc::new(lut)),
+            Err(e) => {
+                self.observer.warning(format!(
+                    "Failed to load LUT {}, skipping it: {}",
+                    full_path.display(),
+                    e
+                ));
+                None
+            }
+        };
+
+        self.lut_cache.insert(path.to_path_buf(), lut.clone());
+        lut
+    }
+
+    /// Color each frame is cleared to: transparent if the script asks for it, else black
+    fn background(&self) -> [u8; 4] {
+        if self.script.metadata.transparent {
+            [0, 0, 0, 0]
+        } else {
+            [0, 0, 0, 255]
+        }
+    }
+
+    /// File extension for saved frames: PNG keeps the alpha of transparent renders
+    pub fn frame_extension(&self) -> &'static str {
+        if self.script.metadata.transparent {
+            "png"
+        } else {
+            "ppm"
+        }
+    }
+
     /// Save current frame as PPM
-    pub fn save_frame(&self, path: &str) -> Result<()> {
-        self.frame_buffer.save_ppm(path)
+    pub fn save_frame(&self, path: &std::path::Path) -> Result<(), RenderError> {
+        self.frame_buffer
+            .save_ppm(path)
+            .map_err(|source| RenderError::Output {
+                path: path.into(),
+                source,
+            })
     }
 
-    /// Render all frames to the output directory
+    /// Render all frames to the output directory as PPM files (RGBA PNG files
+    /// for transparent scripts).
+    ///
+    /// Completed frames are recorded in the directory's render manifest, so a
+    /// rerun of an interrupted render only renders the missing frames.
     pub fn render(
         &mut self,
         output_dir: &std::path::Path,
         asset_loader: &mut AssetLoader,
-    ) -> Result<()> {
-        let total_frames = self.timeline.total_frames();
+    ) -> error::Result<()> {
+        let format = self.frame_format();
+        let frame_path = |frame: u32| output_dir.join(format.file_name(frame));
+        let manifest_error = |source| RenderError::Output {
+            path: output_dir.join(MANIFEST_FILE),
+            source,
+        };
 
-        for frame in 0..total_frames {
-            if frame % 30 == 0 {
-                println!("  Rendering frame {}/{}", frame, total_frames);
-            }
+        let render_hash = self.render_hash().map_err(manifest_error)?;
+        let mut manifest = RenderManifest::load(output_dir, &render_hash);
+        if manifest.started_over() {
+            self.observer
+                .info("Script changed since the last render, starting over");
+        }
+        let frames = self.frames.clone();
+        let missing = manifest.missing_ranges(frames.clone(), frame_path);
+        let missing_frames: usize = missing.iter().map(|range| range.len()).sum();
+        if missing_frames < frames.len() {
+            self.observer.info(format!(
+                "Resuming: {}/{} frames already rendered",
+                frames.len() - missing_frames,
+                frames.len()
+            ));
+        }
 
-            self.render_frame(frame, asset_loader)?;
+        let mut progress = FrameProgress::new(missing_frames as u32);
+        let result = missing.into_iter().try_for_each(|range| {
+            self.frames = range;
+            self.render_frames_with(asset_loader, &mut progress, |frame, frame_buffer| {
+                let path = frame_path(frame);
+                frame_buffer
+                    .save(&path)
+                    .and_then(|()| manifest.mark_complete(frame, &path))
+                    .map_err(|source| RenderError::Output { path, source }.into())
+            })
+        });
+        self.frames = frames;
+
+        // Record the progress of a failed render too, so it can be resumed
+        manifest.save().map_err(manifest_error)?;
+        result
+    }
+
+    /// Hash of the script and the frame format, identifying the frames it renders
+    fn render_hash(&self) -> Result<String> {
+        let script = serde_json::to_vec(&self.script)?;
+        Ok(RenderManifest::hash(&[
+            b"native",
+            &script,
+            self.frame_extension().as_bytes(),
+        ]))
+    }
+
+    /// Render all frames in order, handing each finished frame to `on_frame`
+    /// (e.g. to stream it into an encoder)
+    pub fn render_frames(
+        &mut self,
+        asset_loader: &mut AssetLoader,
+        on_frame: impl FnMut(u32, &FrameBuffer) -> error::Result<()>,
+    ) -> error::Result<()> {
+        let mut progress = FrameProgress::new(self.frames.len() as u32);
+        self.render_frames_with(asset_loader, &mut progress, on_frame)
+    }
+
+    /// `render_frames`, counting the finished frames in `progress`
+    fn render_frames_with(
+        &mut self,
+        asset_loader: &mut AssetLoader,
+        progress: &mut FrameProgress,
+        mut on_frame: impl FnMut(u32, &FrameBuffer) -> error::Result<()>,
+    ) -> error::Result<()> {
+        // The GPU renderer owns a single device queue, so only CPU frames run in parallel
+        if self.gpu_renderer.is_none() && self.max_in_flight_frames > 1 {
+            return self.render_frames_parallel(asset_loader, progress, on_frame);
+        }
 
-            let filename = format!("frame_{}.ppm", frame);
-            let path = output_dir.join(filename);
-            self.save_frame(path.to_str().unwrap())?;
+        for frame in self.frames.clone() {
+            self.render_frame(frame, asset_loader)?;
+            on_frame(frame, &self.frame_buffer)?;
+            self.observer.on_event(&progress.frame_done(frame));
         }
 
         Ok(())
     }
 
+    /// Render frames across the rayon pool, each worker with its own frame
+    /// buffers, and hand them to `on_frame` in order. The assets the workers
+    /// load are added to `asset_loader`.
+    fn render_frames_parallel(
+        &mut self,
+        asset_loader: &mut AssetLoader,
+        progress: &mut FrameProgress,
+        mut on_frame: impl FnMut(u32, &FrameBuffer) -> error::Result<()>,
+    ) -> error::Result<()> {
+        let frames = self.frames.clone();
+        let max_in_flight = self.max_in_flight_frames as u32;
+
+        // One worker per in-flight frame, so a task never waits for an engine
+        let workers: Vec<_> = (0..max_in_flight.min(frames.len() as u32))
+            .map(|_| (self.fork(), AssetLoader::new(asset_loader.base_path())))
+            .collect();
+        let workers = Mutex::new(workers);
+        let cancelled = AtomicBool::new(false);
+        let (sender, receiver) = mpsc::channel();
+
+        let result = rayon::in_place_scope(|scope| {
+            // Frames that finished ahead of the next one to hand out
+            let mut completed = BTreeMap::new();
+            let mut spawned = frames.start;
+
+            for next_frame in frames.clone() {
+                // Keep the pool busy without holding more than `max_in_flight` frames
+                while spawned < frames.end && spawned - next_frame < max_in_flight {
+                    let frame = spawned;
+                    let sender = sender.clone();
+                    let (workers, cancelled) = (&workers, &cancelled);
+                    scope.spawn(move |_| {
+                        if cancelled.load(Ordering::Relaxed) {
+                            return;
+                        }
+                        let (mut engine, mut loader) = workers
+                            .lock()
+                            .unwrap()
+                            .pop()
+                            .expect("one worker per in-flight frame");
+                        let result = engine.render_frame_unwinding(frame, &mut loader);
+                        workers.lock().unwrap().push((engine, loader));
+                        let _ = sender.send((frame, result));
+                    });
+                    spawned += 1;
+                }
+
+                let frame_buffer = loop {
+                    if let Some(frame_buffer) = completed.remove(&next_frame) {
+                        break frame_buffer;
+                    }
+                    let (frame, result) = receiver.recv().map_err(|_| {
+                        self.frame_error(next_frame, anyhow::anyhow!("Render worker stopped"))
+                    })?;
+                    match result {
+                        Ok(frame_buffer) => {
+                            completed.insert(frame, frame_buffer);
+                        }
+                        Err(e) => {
+                            cancelled.store(true, Ordering::Relaxed);
+                            return Err(e.into());
+                        }
+                    }
+                };
+
+                if let Err(e) = on_frame(next_frame, &frame_buffer) {
+                    cancelled.store(true, Ordering::Relaxed);
+                    return Err(e);
+                }
+                self.observer.on_event(&progress.frame_done(next_frame));
+            }
+
+            Ok(())
+        });
+
+        for (_, loader) in workers.into_inner().unwrap() {
+            asset_loader.merge(loader);
+        }
+        result
+    }
+
+    /// The most recently rendered frame
+    pub fn frame_buffer(&self) -> &FrameBuffer {
+        &self.frame_buffer
+    }
+
     /// Get timeline
     pub fn timeline(&self) -> &Timeline {
         &self.timeline
     }
 }
 
+impl Renderer for RenderEngine {
+    fn name(&self) -> &str {
+        "native"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Self::CAPABILITIES
+    }
+
+    fn frame_format(&self) -> FrameFormat {
+        FrameFormat {
+            digits: 0,
+            extension: self.frame_extension(),
+        }
+    }
+
+    fn render_range(
+        &mut self,
+        frames: Range<u32>,
+        output_dir: &std::path::Path,
+        asset_loader: &mut AssetLoader,
+    ) -> error::Result<()> {
+        self.set_frame_range(frames);
+        self.render(output_dir, asset_loader)
+    }
+
+    fn stream_range(
+        &mut self,
+        frames: Range<u32>,
+        asset_loader: &mut AssetLoader,
+        on_frame: &mut dyn FnMut(u32, &FrameBuffer) -> error::Result<()>,
+    ) -> error::Result<()> {
+        self.set_frame_range(frames);
+        self.render_frames(asset_loader, on_frame)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::EncoderError;
+    use crate::events::{Event, Level};
     use crate::script::*;
     use std::path::PathBuf;
 
@@ -271,6 +1001,7 @@ mod tests {
 
     fn create_test_script() -> VideoScript {
         VideoScript {
+            version: SCRIPT_VERSION,
             metadata: Metadata {
                 title: "Test".into(),
                 resolution: Resolution::Named("1920x1080".into()),
@@ -278,6 +1009,8 @@ mod tests {
                 duration: 10.0,
                 description: None,
                 citations: vec![],
+                encoder: None,
+                transparent: false,
             },
             scenes: vec![Scene {
                 id: "test".into(),
@@ -287,6 +1020,7 @@ mod tests {
                     source: PathBuf::from("test.png"),
                     effects: vec![],
                     transform: Default::default(),
+                    animation: Default::default(),
                 }],
                 transition: None,
             }],
@@ -316,4 +1050,573 @@ mod tests {
         // If GPU is available, it exercises the flush() logic
         engine.render_frame(0, &mut asset_loader).unwrap();
     }
+
+    #[test]
+    fn test_render_text_with_font() {
+        let font_path =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fonts/DejaVuSansMono.ttf");
+
+        let mut script = create_test_script();
+        script.scenes[0].layers = vec![Layer::Text {
+            content: "Title".into(),
+            font: font_path,
+            font_size: 64.0,
+            color: Color {
+                r: 255,
+                g: 255,
+                b: 255,
+                a: 255,
+            },
+            position: Position { x: 10, y: 10 },
+            effects: vec![],
+            transform: Default::default(),
+            animation: Default::default(),
+        }];
+
+        let mut engine = RenderEngine::new(script, false);
+        let mut asset_loader = AssetLoader::new(".");
+        engine.render_frame(0, &mut asset_loader).unwrap();
+
+        // Antialiased glyph edges produce intermediate gray levels
+        let pixels = engine.frame_buffer.as_bytes();
+        assert!(pixels.chunks_exact(4).any(|p| p[0] == 255));
+        assert!(pixels.chunks_exact(4).any(|p| p[0] > 0 && p[0] < 255));
+    }
+
+    #[test]
+    fn test_render_image_on_cpu() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut img = RgbaImage::new(4, 4);
+        for pixel in img.pixels_mut() {
+            *pixel = image::Rgba([0, 0, 255, 255]);
+        }
+        img.save(temp_dir.path().join("blue.png")).unwrap();
+
+        let mut script = create_test_script();
+        script.scenes[0].layers = vec![Layer::Image {
+            source: PathBuf::from("blue.png"),
+            effects: vec![],
+            transform: Transform {
+                position: Position { x: 10, y: 20 },
+                scale: 2.0,
+                rotation: 0.0,
+                opacity: 0.5,
+                anchor: Default::default(),
+            },
+            animation: Default::default(),
+        }];
+
+        let mut engine = RenderEngine::new(script, false);
+        let mut asset_loader = AssetLoader::new(temp_dir.path());
+        engine.render_frame(0, &mut asset_loader).unwrap();
+
+        // Scaled to 8x8 and blended at half opacity over black
+        let inside = engine.frame_buffer.get_pixel(17, 27).unwrap();
+        assert_eq!(inside[0], 0);
+        assert!(inside[2] > 120 && inside[2] < 135);
+        assert_eq!(engine.frame_buffer.get_pixel(18, 28), Some([0, 0, 0, 255]));
+        assert_eq!(engine.frame_buffer.get_pixel(9, 19), Some([0, 0, 0, 255]));
+
+        // Decoded image is cached for the next frame
+        assert!(engine.image_cache.contains_key(&PathBuf::from("blue.png")));
+    }
+
+    #[test]
+    fn test_render_video_placeholder_transform() {
+        let mut script = create_test_script();
+        script.scenes[0].layers = vec![Layer::Video {
+            source: PathBuf::from("missing.mp4"),
+            effects: vec![],
+            transform: Transform {
+                position: Position { x: 200, y: 200 },
+                rotation: 45.0,
+                opacity: 0.5,
+                anchor: Anchor { x: 0.5, y: 0.5 },
+                ..Default::default()
+            },
+            animation: Default::default(),
+            playback: Default::default(),
+        }];
+
+        let mut engine = RenderEngine::new(script, false);
+        let mut asset_loader = AssetLoader::new(".");
+        engine.render_frame(0, &mut asset_loader).unwrap();
+
+        // Centered on (200, 200) at half opacity
+        let center = engine.frame_buffer.get_pixel(200, 200).unwrap();
+        assert!(center[0] > 95 && center[0] < 105);
+
+        // A 45 degree rotation moves the corners off the axis-aligned box
+        assert_eq!(
+            engine.frame_buffer.get_pixel(152, 152),
+            Some([0, 0, 0, 255])
+        );
+        assert_ne!(
+            engine.frame_buffer.get_pixel(200, 265),
+            Some([0, 0, 0, 255])
+        );
+    }
+
+    #[test]
+    fn test_render_animated_opacity() {
+        use crate::animation::{Animation, Easing, Keyframe};
+
+        let mut script = create_test_script();
+        script.scenes[0].layers = vec![Layer::Video {
+            source: PathBuf::from("missing.mp4"),
+            effects: vec![],
+            transform: Default::default(),
+            animation: Animation {
+                opacity: vec![
+                    Keyframe {
+                        time: 0.0,
+                        value: 0.0,
+                        easing: Easing::Linear,
+                    },
+                    Keyframe {
+                        time: 1.0,
+                        value: 1.0,
+                        easing: Easing::Linear,
+                    },
+                ],
+                ..Default::default()
+            },
+            playback: Default::default(),
+        }];
+
+        let mut engine = RenderEngine::new(script, false);
+        let mut asset_loader = AssetLoader::new(".");
+
+        // Fully transparent on the first frame of the scene
+        engine.render_frame(0, &mut asset_loader).unwrap();
+        assert_eq!(engine.frame_buffer.get_pixel(50, 50), Some([0, 0, 0, 255]));
+
+        // Half way through the fade at 0.5s (frame 30 at 60fps)
+        engine.render_frame(30, &mut asset_loader).unwrap();
+        let pixel = engine.frame_buffer.get_pixel(50, 50).unwrap();
+        assert!(pixel[0] > 95 && pixel[0] < 105);
+
+        // Held at the last keyframe value
+        engine.render_frame(120, &mut asset_loader).unwrap();
+        assert_eq!(
+            engine.frame_buffer.get_pixel(50, 50),
+            Some([200, 100, 100, 255])
+        );
+    }
+
+    #[test]
+    fn test_render_fade_effects() {
+        let mut script = create_test_script();
+        script.scenes[0].layers = vec![Layer::Video {
+            source: PathBuf::from("missing.mp4"),
+            effects: vec![
+                Effect::FadeIn { duration: 1.0 },
+                Effect::FadeOut { duration: 1.0 },
+            ],
+            transform: Default::default(),
+            animation: Default::default(),
+            playback: Default::default(),
+        }];
+
+        let mut engine = RenderEngine::new(script, false);
+        let mut asset_loader = AssetLoader::new(".");
+
+        // The scene is 5s at 60fps: transparent at the start, full in the middle,
+        // and fading out over the last second
+        engine.render_frame(0, &mut asset_loader).unwrap();
+        assert_eq!(engine.frame_buffer.get_pixel(50, 50), Some([0, 0, 0, 255]));
+
+        engine.render_frame(150, &mut asset_loader).unwrap();
+        assert_eq!(
+            engine.frame_buffer.get_pixel(50, 50),
+            Some([200, 100, 100, 255])
+        );
+
+        engine.render_frame(270, &mut asset_loader).unwrap();
+        let pixel = engine.frame_buffer.get_pixel(50, 50).unwrap();
+        assert!(pixel[0] > 95 && pixel[0] < 105);
+    }
+
+    #[test]
+    fn test_render_image_effects() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        RgbaImage::from_pixel(8, 8, image::Rgba([0, 0, 255, 255]))
+            .save(temp_dir.path().join("blue.png"))
+            .unwrap();
+
+        let mut script = create_test_script();
+        script.scenes[0].layers = vec![Layer::Image {
+            source: PathBuf::from("blue.png"),
+            effects: vec![
+                Effect::Blur { radius: 3.0 },
+                Effect::ColorGrade(ColorGrade {
+                    saturation: 0.0,
+                    ..Default::default()
+                }),
+            ],
+            transform: Transform {
+                position: Position { x: 20, y: 20 },
+                ..Default::default()
+            },
+            animation: Default::default(),
+        }];
+
+        let mut engine = RenderEngine::new(script, false);
+        let mut asset_loader = AssetLoader::new(temp_dir.path());
+        engine.render_frame(0, &mut asset_loader).unwrap();
+
+        // Desaturated to gray in the middle of the layer
+        let inside = engine.frame_buffer.get_pixel(24, 24).unwrap();
+        assert!(inside[0] > 0 && inside[0] == inside[1] && inside[1] == inside[2]);
+
+        // The blur spreads past the layer bounds and softens its edge
+        let outside = engine.frame_buffer.get_pixel(18, 24).unwrap();
+        assert!(outside[0] > 0 && outside[0] < inside[0]);
+        assert_eq!(engine.frame_buffer.get_pixel(16, 24), Some([0, 0, 0, 255]));
+
+        // The processed image is cached for the next frame
+        assert_eq!(engine.effect_cache.len(), 1);
+    }
+
+    #[test]
+    fn test_render_dissolve_transition() {
+        let mut script = create_test_script();
+        script.scenes[0].layers = vec![Layer::Video {
+            source: PathBuf::from("missing.mp4"),
+            effects: vec![],
+            transform: Default::default(),
+            animation: Default::default(),
+            playback: Default::default(),
+        }];
+        script.scenes[0].transition = Some(Transition::Dissolve { duration: 1.0 });
+
+        let mut second = script.scenes[0].clone();
+        second.id = "second".into();
+        second.transition = None;
+        second.layers = vec![Layer::Image {
+            source: PathBuf::from("missing.png"),
+            effects: vec![],
+            transform: Default::default(),
+            animation: Default::default(),
+        }];
+        script.scenes.push(second);
+
+        let mut engine = RenderEngine::new(script, false);
+        let mut asset_loader = AssetLoader::new(".");
+
+        // Before the overlap only the first scene is visible
+        engine.render_frame(230, &mut asset_loader).unwrap();
+        assert_eq!(
+            engine.frame_buffer.get_pixel(50, 50),
+            Some([200, 100, 100, 255])
+        );
+
+        // Half way through the 1s overlap (frames 240..300) both scenes are mixed
+        engine.render_frame(270, &mut asset_loader).unwrap();
+        let pixel = engine.frame_buffer.get_pixel(50, 50).unwrap();
+        assert!(pixel[0] > 145 && pixel[0] < 155);
+        assert!(pixel[2] > 145 && pixel[2] < 155);
+
+        // After the overlap only the second scene is visible
+        engine.render_frame(300, &mut asset_loader).unwrap();
+        assert_eq!(
+            engine.frame_buffer.get_pixel(50, 50),
+            Some([100, 100, 200, 255])
+        );
+    }
+
+    #[test]
+    fn test_render_transparent_background() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut img = RgbaImage::new(4, 4);
+        for pixel in img.pixels_mut() {
+            *pixel = image::Rgba([0, 0, 255, 255]);
+        }
+        img.save(temp_dir.path().join("blue.png")).unwrap();
+
+        let mut script = create_test_script();
+        script.metadata.transparent = true;
+        script.scenes[0].layers = vec![Layer::Image {
+            source: PathBuf::from("blue.png"),
+            effects: vec![],
+            transform: Transform {
+                position: Position { x: 10, y: 20 },
+                opacity: 0.5,
+                ..Default::default()
+            },
+            animation: Default::default(),
+        }];
+
+        let mut engine = RenderEngine::new(script, false);
+        let mut asset_loader = AssetLoader::new(temp_dir.path());
+        engine.render_frame(0, &mut asset_loader).unwrap();
+        assert_eq!(engine.frame_extension(), "png");
+
+        // The layer keeps its color and carries its opacity as coverage
+        assert_eq!(
+            engine.frame_buffer.get_pixel(11, 21),
+            Some([0, 0, 255, 128])
+        );
+        assert_eq!(engine.frame_buffer.get_pixel(0, 0), Some([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn test_render_frames_in_order() {
+        let mut script = create_test_script();
+        script.metadata.resolution = Resolution::Dimensions {
+            width: 32,
+            height: 18,
+        };
+        script.metadata.fps = 10;
+        script.metadata.duration = 0.5;
+        script.scenes[0].duration = 0.5;
+
+        let mut engine = RenderEngine::new(script, false);
+        let mut asset_loader = AssetLoader::new(".");
+
+        let mut frames = Vec::new();
+        engine
+            .render_frames(&mut asset_loader, |frame, frame_buffer| {
+                assert_eq!(frame_buffer.as_bytes().len(), 32 * 18 * 4);
+                frames.push(frame);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(frames, vec![0, 1, 2, 3, 4]);
+
+        // A frame range renders only those frames, in parallel too
+        engine.set_frame_range(2..4);
+        engine.set_max_in_flight_frames(2);
+        let mut frames = Vec::new();
+        engine
+            .render_frames(&mut asset_loader, |frame, _| {
+                frames.push(frame);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(frames, vec![2, 3]);
+
+        // Errors from the frame consumer stop the render
+        let result = engine.render_frames(&mut asset_loader, |_, _| {
+            Err(EncoderError::InputClosed.into())
+        });
+        assert!(matches!(
+            result,
+            Err(error::Error::Encoder(EncoderError::InputClosed))
+        ));
+    }
+
+    #[test]
+    fn test_render_frames_parallel_worker_panic() {
+        let mut script = create_test_script();
+        script.metadata.fps = 10;
+        script.scenes[0].layers = vec![Layer::Video {
+            source: PathBuf::from("missing.mp4"),
+            effects: vec![],
+            transform: Default::default(),
+            animation: Default::default(),
+            playback: Default::default(),
+        }];
+
+        // The missing video's warning makes the worker rendering it panic
+        let observer: SharedObserver = Arc::new(|event: &Event| {
+            if let Event::Message {
+                level: Level::Warning,
+                message,
+            } = event
+            {
+                panic!("{}", message);
+            }
+        });
+        let mut engine = RenderEngine::with_observer(script, false, observer);
+        engine.set_max_in_flight_frames(4);
+        let mut rendered = 0;
+        let result = engine.render_frames(&mut AssetLoader::new("."), |_, _| {
+            rendered += 1;
+            Ok(())
+        });
+
+        assert_eq!(rendered, 0);
+        let Err(error::Error::Render(RenderError::Frame { frame, source, .. })) = result else {
+            panic!("expected a frame error, got {:?}", result.err());
+        };
+        assert!(frame < 4);
+        assert!(source
+            .to_string()
+            .starts_with("Render worker panicked: Failed to open video"));
+    }
+
+    #[test]
+    fn test_scene_cache_reuses_frames() {
+        let mut script = create_test_script();
+        script.metadata.resolution = Resolution::Dimensions {
+            width: 8,
+            height: 8,
+        };
+        script.metadata.fps = 10;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let cache_dir = temp_dir.path().join("cache");
+        let mut asset_loader = AssetLoader::new(temp_dir.path());
+        let cached_engine = |script: &VideoScript| {
+            let mut engine = RenderEngine::new(script.clone(), false);
+            engine
+                .enable_scene_cache(&cache_dir, temp_dir.path())
+                .unwrap();
+            engine
+        };
+
+        let mut engine = cached_engine(&script);
+        engine.render_frame(3, &mut asset_loader).unwrap();
+        let rendered = engine.frame_buffer().get_pixel(0, 0);
+
+        // Mark the cached frame, so a reuse is visible
+        let cache = engine.scene_cache.clone().unwrap();
+        let mut marked = FrameBuffer::new(8, 8);
+        marked.clear([255, 0, 0, 255]);
+        cache.store(3, &marked).unwrap();
+
+        let mut engine = cached_engine(&script);
+        engine.render_frame(3, &mut asset_loader).unwrap();
+        assert_eq!(
+            engine.frame_buffer().get_pixel(0, 0),
+            Some([255, 0, 0, 255])
+        );
+
+        // An edited scene renders again
+        script.scenes[0].duration = 4.0;
+        let mut engine = cached_engine(&script);
+        engine.render_frame(3, &mut asset_loader).unwrap();
+        assert_eq!(engine.frame_buffer().get_pixel(0, 0), rendered);
+    }
+
+    #[test]
+    fn test_render_resumes_from_manifest() {
+        let mut script = create_test_script();
+        script.metadata.resolution = Resolution::Dimensions {
+            width: 8,
+            height: 8,
+        };
+        script.metadata.fps = 10;
+        script.metadata.duration = 0.5;
+        script.scenes[0].duration = 0.5;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let output_dir = temp_dir.path();
+        let mut engine = RenderEngine::new(script.clone(), false);
+        let mut asset_loader = AssetLoader::new(".");
+
+        // An interrupted run: frames 0..2 done, frame 2 left half written
+        engine.set_frame_range(0..2);
+        engine.render(output_dir, &mut asset_loader).unwrap();
+        std::fs::write(output_dir.join("frame_2.ppm"), b"P6").unwrap();
+        let written = |frame: u32| {
+            std::fs::metadata(output_dir.join(format!("frame_{}.ppm", frame)))
+                .and_then(|metadata| metadata.modified())
+                .unwrap()
+        };
+        let first_frame = written(0);
+
+        let mut engine = RenderEngine::new(script.clone(), false);
+        engine.render(output_dir, &mut asset_loader).unwrap();
+        assert_eq!(written(0), first_frame);
+        let size = |frame: u32| {
+            std::fs::metadata(output_dir.join(format!("frame_{}.ppm", frame)))
+                .unwrap()
+                .len()
+        };
+        assert!((0..5).all(|frame| size(frame) == size(0)));
+
+        // A changed script renders everything again
+        script.scenes[0].id = "changed".into();
+        let manifest = RenderManifest::load(
+            output_dir,
+            &RenderEngine::new(script, false).render_hash().unwrap(),
+        );
+        assert_eq!(
+            manifest.missing_ranges(0..5, |frame| output_dir
+                .join(format!("frame_{}.ppm", frame))),
+            vec![0..5]
+        );
+    }
+
+    #[test]
+    fn test_render_frames_parallel_matches_sequential() {
+        let mut script = create_test_script();
+        script.metadata.resolution = Resolution::Dimensions {
+            width: 32,
+            height: 18,
+        };
+        script.metadata.fps = 10;
+        script.metadata.duration = 2.0;
+        script.scenes[0].duration = 2.0;
+        script.scenes[0].layers = vec![Layer::Video {
+            source: PathBuf::from("missing.mp4"),
+            effects: vec![Effect::FadeIn { duration: 1.0 }],
+            transform: Default::default(),
+            animation: Default::default(),
+            playback: Default::default(),
+        }];
+
+        let mut asset_loader = AssetLoader::new(".");
+        let mut render = |max_in_flight_frames| {
+            let mut engine = RenderEngine::new(script.clone(), false);
+            engine.set_max_in_flight_frames(max_in_flight_frames);
+            let mut frames = Vec::new();
+            engine
+                .render_frames(&mut asset_loader, |frame, frame_buffer| {
+                    frames.push((frame, frame_buffer.as_bytes().to_vec()));
+                    Ok(())
+                })
+                .unwrap();
+            frames
+        };
+
+        let sequential = render(1);
+        let parallel = render(3);
+        assert_eq!(sequential.len(), 20);
+        assert!(sequential == parallel);
+
+        // Consumer errors stop the parallel render too
+        let mut engine = RenderEngine::new(script.clone(), false);
+        engine.set_max_in_flight_frames(4);
+        let result = engine.render_frames(&mut asset_loader, |frame, _| {
+            if frame < 5 {
+                Ok(())
+            } else {
+                Err(EncoderError::InputClosed.into())
+            }
+        });
+        assert!(matches!(
+            result,
+            Err(error::Error::Encoder(EncoderError::InputClosed))
+        ));
+    }
+
+    #[test]
+    fn test_render_text_missing_font_falls_back() {
+        let mut script = create_test_script();
+        script.scenes[0].layers = vec![Layer::Text {
+            content: "Title".into(),
+            font: PathBuf::from("missing.ttf"),
+            font_size: 64.0,
+            color: Color {
+                r: 255,
+                g: 0,
+                b: 0,
+                a: 255,
+            },
+            position: Position { x: 0, y: 0 },
+            effects: vec![],
+            transform: Default::default(),
+            animation: Default::default(),
+        }];
+
+        let mut engine = RenderEngine::new(script, false);
+        let mut asset_loader = AssetLoader::new(".");
+        engine.render_frame(0, &mut asset_loader).unwrap();
+
+        // Placeholder rectangle is drawn instead
+        assert_eq!(engine.frame_buffer.get_pixel(0, 0), Some([255, 0, 0, 255]));
+    }
 }
@@ -1,6 +1,7 @@
 use anyhow::Result;
 use std::fs::File;
 use std::io::Write;
+use std::path::Path;
 
 /// RGBA frame buffer for rendering
 #[derive(Debug, Clone)]
@@ -48,18 +49,21 @@ impl FrameBuffer {
         }
     }
 
-    /// Alpha blend a color onto the buffer at position
+    /// Alpha blend a color onto the buffer at position ("over" with straight alpha,
+    /// so transparent backgrounds keep their coverage)
     pub fn blend_pixel(&mut self, x: u32, y: u32, color: [u8; 4]) {
         if let Some(bg) = self.get_pixel(x, y) {
             let alpha = color[3] as f32 / 255.0;
-            let inv_alpha = 1.0 - alpha;
+            let bg_alpha = bg[3] as f32 / 255.0 * (1.0 - alpha);
+            let out_alpha = alpha + bg_alpha;
+            if out_alpha <= 0.0 {
+                return;
+            }
 
-            let blended = [
-                (color[0] as f32 * alpha + bg[0] as f32 * inv_alpha) as u8,
-                (color[1] as f32 * alpha + bg[1] as f32 * inv_alpha) as u8,
-                (color[2] as f32 * alpha + bg[2] as f32 * inv_alpha) as u8,
-                255, // Output alpha is always opaque
-            ];
+            let mix = |c: usize| {
+                ((color[c] as f32 * alpha + bg[c] as f32 * bg_alpha) / out_alpha).round() as u8
+            };
+            let blended = [mix(0), mix(1), mix(2), (out_alpha * 255.0).round() as u8];
 
             self.set_pixel(x, y, blended);
         }
@@ -75,13 +79,18 @@ impl FrameBuffer {
         &self.pixels
     }
 
+    /// Get mutable raw pixel data
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        &mut self.pixels
+    }
+
     /// Copy data from a slice into the buffer
     pub fn copy_from_slice(&mut self, data: &[u8]) {
         self.pixels.copy_from_slice(data);
     }
 
     /// Save as PPM (simple image format)
-    pub fn save_ppm(&self, path: &str) -> Result<()> {
+    pub fn save_ppm(&self, path: &Path) -> Result<()> {
         let file = File::create(path)?;
         let mut writer = std::io::BufWriter::new(file);
 
@@ -98,6 +107,27 @@ impl FrameBuffer {
         writer.flush()?;
         Ok(())
     }
+
+    /// Save as PNG if `path` ends in `.png`, otherwise as PPM
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if path.extension().is_some_and(|extension| extension == "png") {
+            self.save_png(path)
+        } else {
+            self.save_ppm(path)
+        }
+    }
+
+    /// Save as an RGBA PNG, keeping the alpha channel
+    pub fn save_png(&self, path: &Path) -> Result<()> {
+        image::save_buffer(
+            path,
+            &self.pixels,
+            self.width,
+            self.height,
+            image::ExtendedColorType::Rgba8,
+        )?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -143,4 +173,51 @@ mod tests {
         assert!(pixel[1] > 120 && pixel[1] < 135);
         assert!(pixel[2] > 120 && pixel[2] < 135);
     }
+
+    #[test]
+    fn test_alpha_blending_transparent_background() {
+        let mut fb = FrameBuffer::new(4, 4);
+        fb.clear([0, 0, 0, 0]);
+
+        // Blending onto nothing keeps the color and its coverage
+        fb.blend_pixel(1, 1, [255, 0, 0, 128]);
+        assert_eq!(fb.get_pixel(1, 1), Some([255, 0, 0, 128]));
+
+        // A second half-transparent layer accumulates coverage
+        fb.blend_pixel(1, 1, [0, 0, 255, 128]);
+        let pixel = fb.get_pixel(1, 1).unwrap();
+        assert_eq!(pixel[3], 192);
+        assert!(pixel[2] > pixel[0]);
+
+        // Fully transparent colors leave the buffer untouched
+        fb.blend_pixel(2, 2, [255, 255, 255, 0]);
+        assert_eq!(fb.get_pixel(2, 2), Some([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn test_save_png_keeps_alpha() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("frame.png");
+
+        let mut fb = FrameBuffer::new(2, 2);
+        fb.set_pixel(0, 0, [10, 20, 30, 40]);
+        fb.save_png(&path).unwrap();
+
+        let image = image::open(&path).unwrap().to_rgba8();
+        assert_eq!(image.get_pixel(0, 0).0, [10, 20, 30, 40]);
+        assert_eq!(image.get_pixel(1, 1).0, [0, 0, 0, 0]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_save_non_utf8_path() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let name = std::ffi::OsStr::from_bytes(b"frame_\xff.png");
+        let path = temp_dir.path().join(name);
+
+        FrameBuffer::new(2, 2).save(&path).unwrap();
+        assert_eq!(image::open(&path).unwrap().width(), 2);
+    }
 }
@@ -65,6 +65,8 @@ pub struct GpuRenderer {
     texture_bind_group_layout: wgpu::BindGroupLayout,
     output_texture: Option<wgpu::Texture>,
     staging_buffer: Option<wgpu::Buffer>,
+    /// Clear to transparent instead of black (read back as straight alpha)
+    transparent: bool,
 }
 
 impl GpuRenderer {
@@ -227,9 +229,15 @@ impl GpuRenderer {
             texture_bind_group_layout,
             output_texture: None,
             staging_buffer: None,
+            transparent: false,
         })
     }
 
+    /// Render onto a transparent background instead of black
+    pub fn set_transparent(&mut self, transparent: bool) {
+        self.transparent = transparent;
+    }
+
     /// Create a texture from an image
     pub fn create_texture(&self, image: &image::DynamicImage) -> std::sync::Arc<wgpu::BindGroup> {
         let rgba = image.to_rgba8();
@@ -314,12 +322,28 @@ impl GpuRenderer {
         width: u32,
         height: u32,
         color: [u8; 4],
+    ) -> Result<()> {
+        let (x1, y1) = (x as f32, y as f32);
+        let (x2, y2) = ((x + width as i32) as f32, (y + height as i32) as f32);
+
+        self.draw_texture_quad(bind_group, [[x1, y1], [x2, y1], [x2, y2], [x1, y2]], color)
+    }
+
+    /// Draw a textured quad given its corners in frame pixel coordinates
+    /// (top-left, top-right, bottom-right, bottom-left of the texture)
+    pub fn draw_texture_quad(
+        &self,
+        bind_group: std::sync::Arc<wgpu::BindGroup>,
+        corners: [[f32; 2]; 4],
+        color: [u8; 4],
     ) -> Result<()> {
         // Convert pixel coords to normalized device coordinates (-1 to 1)
-        let x1 = (x as f32 / self.width as f32) * 2.0 - 1.0;
-        let y1 = -((y as f32 / self.height as f32) * 2.0 - 1.0); // Flip Y
-        let x2 = ((x + width as i32) as f32 / self.width as f32) * 2.0 - 1.0;
-        let y2 = -(((y + height as i32) as f32 / self.height as f32) * 2.0 - 1.0);
+        let [tl, tr, br, bl] = corners.map(|[px, py]| {
+            [
+                (px / self.width as f32) * 2.0 - 1.0,
+                -((py / self.height as f32) * 2.0 - 1.0), // Flip Y
+            ]
+        });
 
         let color_norm = [
             color[0] as f32 / 255.0,
@@ -328,35 +352,35 @@ impl GpuRenderer {
             color[3] as f32 / 255.0,
         ];
 
-        // Two triangles to make a rectangle
+        // Two triangles to make a quad
         let new_vertices = vec![
             Vertex {
-                position: [x1, y1],
+                position: tl,
                 color: color_norm,
                 uv: [0.0, 0.0],
             },
             Vertex {
-                position: [x2, y1],
+                position: tr,
                 color: color_norm,
                 uv: [1.0, 0.0],
             },
             Vertex {
-                position: [x2, y2],
+                position: br,
                 color: color_norm,
                 uv: [1.0, 1.0],
             },
             Vertex {
-                position: [x1, y1],
+                position: tl,
                 color: color_norm,
                 uv: [0.0, 0.0],
             },
             Vertex {
-                position: [x2, y2],
+                position: br,
                 color: color_norm,
                 uv: [1.0, 1.0],
             },
             Vertex {
-                position: [x1, y2],
+                position: bl,
                 color: color_norm,
                 uv: [0.0, 1.0],
             },
@@ -377,6 +401,11 @@ impl GpuRenderer {
         Ok(())
     }
 
+    /// Fill a quad given its corners in frame pixel coordinates
+    pub fn fill_quad(&self, corners: [[f32; 2]; 4], color: [u8; 4]) -> Result<()> {
+        self.draw_texture_quad(self.white_texture_bind_group.clone(), corners, color)
+    }
+
     /// Fill a rectangle with a color using GPU batching
     pub fn fill_rect(
         &self,
@@ -399,7 +428,6 @@ impl GpuRenderer {
 
     /// Flush accumulated vertices to GPU and render to frame buffer
     pub fn flush(&mut self, frame_buffer: &mut FrameBuffer) -> Result<()> {
-        let start_time = std::time::Instant::now();
         let mut batches = self.batches.borrow_mut();
         if batches.is_empty() {
             return Ok(());
@@ -478,7 +506,11 @@ impl GpuRenderer {
                     view: &view,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        load: wgpu::LoadOp::Clear(if self.transparent {
+                            wgpu::Color::TRANSPARENT
+                        } else {
+                            wgpu::Color::BLACK
+                        }),
                         store: wgpu::StoreOp::Store,
                     },
                     depth_slice: None,
@@ -572,15 +604,25 @@ impl GpuRenderer {
                 frame_buffer.copy_from_slice(&data);
             }
 
+            // Alpha blending over a transparent clear leaves premultiplied color;
+            // the frame buffer holds straight alpha
+            if self.transparent {
+                for pixel in frame_buffer.as_bytes_mut().chunks_exact_mut(4) {
+                    let alpha = pixel[3];
+                    if alpha > 0 && alpha < 255 {
+                        for c in &mut pixel[..3] {
+                            *c = (*c as u32 * 255 / alpha as u32).min(255) as u8;
+                        }
+                    }
+                }
+            }
+
             staging_buffer.unmap();
         });
 
         // Clear batches for next frame
         batches.clear();
 
-        let duration = start_time.elapsed();
-        println!("GPU Flush: {:.3}ms", duration.as_secs_f64() * 1000.0);
-
         Ok(())
     }
 
@@ -0,0 +1,192 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Name of the manifest file in the output directory
+pub const MANIFEST_FILE: &str = "render_manifest.json";
+/// Completed frames recorded between two writes of the manifest
+const SAVE_INTERVAL: usize = 30;
+
+/// Record of the frames of a render that are already on disk, so an interrupted
+/// render can pick up where it stopped.
+///
+/// Frames are only trusted while the render hash matches and the file still has
+/// the content it was written with.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RenderManifest {
+    /// Hash of everything that affects the rendered pixels
+    render_hash: String,
+    /// Completed frames and the file each was written to
+    frames: BTreeMap<u32, FrameRecord>,
+    #[serde(skip)]
+    path: PathBuf,
+    /// Frames marked complete since the last save
+    #[serde(skip)]
+    unsaved: usize,
+    /// Whether frames of an earlier, different render were discarded
+    #[serde(skip)]
+    started_over: bool,
+}
+
+impl RenderManifest {
+    /// Load the manifest of `output_dir`, starting a new one if there is none or
+    /// it belongs to a different render
+    pub fn load(output_dir: &Path, render_hash: &str) -> Self {
+        let path = output_dir.join(MANIFEST_FILE);
+        let previous = fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| serde_json::from_str::<RenderManifest>(&json).ok());
+
+        let (frames, started_over) = match previous {
+            Some(manifest) if manifest.render_hash == render_hash => (manifest.frames, false),
+            Some(_) => (BTreeMap::new(), true),
+            None => (BTreeMap::new(), false),
+        };
+
+        Self {
+            render_hash: render_hash.to_string(),
+            frames,
+            path,
+            unsaved: 0,
+            started_over,
+        }
+    }
+
+    /// Whether the directory held frames of a different render, which are ignored
+    pub fn started_over(&self) -> bool {
+        self.started_over
+    }
+
+    /// Hash identifying a render from its serialized inputs
+    pub fn hash(parts: &[&[u8]]) -> String {
+        let mut hasher = Sha256::new();
+        for part in parts {
+            hasher.update((part.len() as u64).to_le_bytes());
+            hasher.update(part);
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Whether `frame` was completed and its file at `path` is still intact
+    pub fn is_complete(&self, frame: u32, path: &Path) -> bool {
+        let Some(record) = self.frames.get(&frame) else {
+            return false;
+        };
+        // The size rules out most changes without reading the file
+        fs::metadata(path).is_ok_and(|metadata| metadata.is_file() && metadata.len() == record.size)
+            && FrameRecord::read(path).is_ok_and(|current| current == *record)
+    }
+
+    /// Frames of `frames` that still need rendering, grouped into contiguous ranges
+    pub fn missing_ranges(
+        &self,
+        frames: Range<u32>,
+        frame_path: impl Fn(u32) -> PathBuf,
+    ) -> Vec<Range<u32>> {
+        let mut ranges: Vec<Range<u32>> = Vec::new();
+        for frame in frames {
+            if self.is_complete(frame, &frame_path(frame)) {
+                continue;
+            }
+            match ranges.last_mut() {
+                Some(range) if range.end == frame => range.end += 1,
+                _ => ranges.push(frame..frame + 1),
+            }
+        }
+        ranges
+    }
+
+    /// Record `frame` as written to `path`, saving the manifest every few frames
+    pub fn mark_complete(&mut self, frame: u32, path: &Path) -> Result<()> {
+        let record = FrameRecord::read(path)
+            .with_context(|| format!("Frame {} was not written: {}", frame, path.display()))?;
+        self.frames.insert(frame, record);
+
+        self.unsaved += 1;
+        if self.unsaved >= SAVE_INTERVAL {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Write the manifest to disk
+    pub fn save(&mut self) -> Result<()> {
+        // Write then rename, so an interruption never leaves a truncated manifest
+        let temp_path = self.path.with_extension("json.tmp");
+        fs::write(&temp_path, serde_json::to_string(self)?)
+            .with_context(|| format!("Failed to write {}", temp_path.display()))?;
+        fs::rename(&temp_path, &self.path)
+            .with_context(|| format!("Failed to write {}", self.path.display()))?;
+
+        self.unsaved = 0;
+        Ok(())
+    }
+}
+
+/// Size and content hash of a frame file
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct FrameRecord {
+    size: u64,
+    sha256: String,
+}
+
+impl FrameRecord {
+    fn read(path: &Path) -> std::io::Result<Self> {
+        let content = fs::read(path)?;
+        Ok(Self {
+            size: content.len() as u64,
+            sha256: format!("{:x}", Sha256::digest(&content)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resume_from_manifest() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        let frame_path = |frame: u32| dir.join(format!("frame_{}.ppm", frame));
+
+        let mut manifest = RenderManifest::load(dir, "abc");
+        assert_eq!(manifest.missing_ranges(0..5, frame_path), vec![0..5]);
+
+        for frame in [0, 1, 3] {
+            fs::write(frame_path(frame), b"pixels").unwrap();
+            manifest.mark_complete(frame, &frame_path(frame)).unwrap();
+        }
+        // A frame written but never recorded (interrupted mid-write) is redone
+        fs::write(frame_path(4), b"pix").unwrap();
+        manifest.save().unwrap();
+
+        let mut manifest = RenderManifest::load(dir, "abc");
+        assert_eq!(manifest.missing_ranges(0..5, frame_path), vec![2..3, 4..5]);
+
+        // Frames whose file changed (even keeping its size) or disappeared are invalid
+        fs::write(frame_path(0), b"pixels").unwrap();
+        fs::write(frame_path(1), b"PIXELS").unwrap();
+        fs::remove_file(frame_path(3)).unwrap();
+        assert_eq!(manifest.missing_ranges(0..5, frame_path), vec![1..5]);
+        assert!(manifest.mark_complete(3, &frame_path(3)).is_err());
+
+        // A different render ignores the recorded frames
+        let manifest = RenderManifest::load(dir, "def");
+        assert!(manifest.started_over());
+        assert_eq!(manifest.missing_ranges(0..2, frame_path), vec![0..2]);
+    }
+
+    #[test]
+    fn test_render_hash() {
+        let hash = RenderManifest::hash(&[b"script", b"native"]);
+        assert_eq!(hash, RenderManifest::hash(&[b"script", b"native"]));
+        assert_ne!(hash, RenderManifest::hash(&[b"script", b"blender"]));
+        // Parts are length-prefixed, so moving bytes between them changes the hash
+        assert_ne!(hash, RenderManifest::hash(&[b"scriptn", b"ative"]));
+    }
+}
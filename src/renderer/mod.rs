@@ -1,17 +1,402 @@
 pub mod blender;
 pub mod compositor;
+pub mod effects;
 pub mod encoder;
 pub mod engine;
 pub mod frame_buffer;
 pub mod gpu_context;
 pub mod gpu_renderer;
+pub mod manifest;
+pub mod scene_cache;
+pub mod storyboard;
+pub mod text;
 pub mod timeline;
+pub mod transitions;
 
 pub use blender::BlenderRenderer;
-pub use compositor::Compositor;
-pub use encoder::VideoEncoder;
+pub use compositor::{Affine2, Compositor};
+pub use effects::{CubeLut, Effects};
+pub use encoder::{EncodeOptions, EncoderStream, VideoEncoder};
 pub use engine::RenderEngine;
 pub use frame_buffer::FrameBuffer;
 pub use gpu_context::GpuContext;
 pub use gpu_renderer::GpuRenderer;
-pub use timeline::Timeline;
+pub use manifest::RenderManifest;
+pub use scene_cache::SceneCache;
+pub use storyboard::{Storyboard, StoryboardFrame};
+pub use text::TextRasterizer;
+pub use timeline::{FrameSelection, Timeline, TransitionFrame};
+pub use transitions::Transitions;
+
+use crate::assets::AssetLoader;
+use crate::error::{RenderError, Result};
+use crate::events::{ConsoleObserver, SharedObserver};
+use crate::script::VideoScript;
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Parts of a script a rendering backend can draw, by their names in scripts
+#[derive(Debug, Clone, PartialEq)]
+pub struct Capabilities {
+    pub layers: &'static [&'static str],
+    pub effects: &'static [&'static str],
+    pub transitions: &'static [&'static str],
+    /// Renders transparent backgrounds
+    pub transparency: bool,
+    /// Hands frames over in memory (`Renderer::stream_range`) instead of only
+    /// writing files
+    pub streaming: bool,
+}
+
+impl Capabilities {
+    /// Features used by `script` that the backend can't render, one message each
+    pub fn unsupported(&self, script: &VideoScript) -> Vec<String> {
+        let mut unsupported = Vec::new();
+        if script.metadata.transparent && !self.transparency {
+            unsupported.push("transparent backgrounds".to_string());
+        }
+
+        for scene in &script.scenes {
+            for layer in &scene.layers {
+                if !self.layers.contains(&layer.kind()) {
+                    unsupported.push(format!("{} layer in scene '{}'", layer.kind(), scene.id));
+                }
+                for effect in layer.effects() {
+                    if !self.effects.contains(&effect.kind()) {
+                        unsupported.push(format!(
+                            "{} effect in scene '{}'",
+                            effect.kind(),
+                            scene.id
+                        ));
+                    }
+                }
+            }
+            if let Some(transition) = &scene.transition {
+                if !self.transitions.contains(&transition.kind()) {
+                    unsupported.push(format!(
+                        "{} transition in scene '{}'",
+                        transition.kind(),
+                        scene.id
+                    ));
+                }
+            }
+        }
+
+        unsupported
+    }
+}
+
+/// Names and format of the frame files a backend writes
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameFormat {
+    /// Zero padding of the frame number (0 for none)
+    pub digits: usize,
+    pub extension: &'static str,
+}
+
+impl FrameFormat {
+    /// File name of `frame`, e.g. `frame_0042.png`
+    pub fn file_name(&self, frame: u32) -> String {
+        format!(
+            "frame_{:0width$}.{}",
+            frame,
+            self.extension,
+            width = self.digits
+        )
+    }
+
+    /// FFmpeg image sequence pattern matching the file names, e.g. `frame_%04d.png`
+    pub fn pattern(&self) -> String {
+        match self.digits {
+            0 => format!("frame_%d.{}", self.extension),
+            digits => format!("frame_%0{}d.{}", digits, self.extension),
+        }
+    }
+}
+
+/// A rendering backend
+pub trait Renderer {
+    /// Name the backend is registered under
+    fn name(&self) -> &str;
+
+    /// Layers, effects and transitions the backend can draw
+    fn capabilities(&self) -> Capabilities;
+
+    /// Format of the frames written by `render_range`
+    fn frame_format(&self) -> FrameFormat;
+
+    /// Render `frames` into `output_dir`, named by `frame_format`
+    fn render_range(
+        &mut self,
+        frames: Range<u32>,
+        output_dir: &Path,
+        asset_loader: &mut AssetLoader,
+    ) -> Result<()>;
+
+    /// Render `frames` in order, handing each to `on_frame` without writing files.
+    /// Only backends with the `streaming` capability implement it.
+    fn stream_range(
+        &mut self,
+        frames: Range<u32>,
+        asset_loader: &mut AssetLoader,
+        on_frame: &mut dyn FnMut(u32, &FrameBuffer) -> Result<()>,
+    ) -> Result<()> {
+        let _ = (frames, asset_loader, on_frame);
+        Err(RenderError::StreamingUnsupported {
+            renderer: self.name().to_string(),
+        }
+        .into())
+    }
+}
+
+/// Settings shared by all backends when they are created
+#[derive(Debug, Clone)]
+pub struct RendererOptions {
+    pub use_gpu: bool,
+    /// Frames the CPU renderer keeps in flight at once (0 = twice the cores, 1 = sequential)
+    pub max_in_flight_frames: usize,
+    /// Reuse frames of unchanged scenes from earlier renders, cached in this
+    /// directory (each backend in its own subdirectory)
+    pub scene_cache_dir: Option<PathBuf>,
+    /// Receives the backend's progress and warnings
+    pub observer: SharedObserver,
+}
+
+impl Default for RendererOptions {
+    fn default() -> Self {
+        Self {
+            use_gpu: true,
+            max_in_flight_frames: 1,
+            scene_cache_dir: None,
+            observer: Arc::new(ConsoleObserver),
+        }
+    }
+}
+
+/// Creates a backend for a script
+pub type RendererFactory = Box<
+    dyn Fn(VideoScript, &RendererOptions, &AssetLoader) -> Result<Box<dyn Renderer>> + Send + Sync,
+>;
+
+struct RegisteredRenderer {
+    description: String,
+    capabilities: Capabilities,
+    factory: RendererFactory,
+}
+
+/// Rendering backends by name. `new` registers the built-in `native` and
+/// `blender` backends; other crates can register their own.
+pub struct RendererRegistry {
+    renderers: BTreeMap<String, RegisteredRenderer>,
+}
+
+impl RendererRegistry {
+    /// Registry with the built-in backends
+    pub fn new() -> Self {
+        let mut registry = Self::empty();
+        registry.register(
+            "native",
+            "Built-in CPU/GPU compositor",
+            RenderEngine::CAPABILITIES,
+            |script, options, asset_loader| {
+                let mut engine =
+                    RenderEngine::with_observer(script, options.use_gpu, options.observer.clone());
+                engine.set_max_in_flight_frames(options.max_in_flight_frames);
+                if let Some(dir) = &options.scene_cache_dir {
+                    engine.enable_scene_cache(&dir.join("scenes"), asset_loader.base_path())?;
+                }
+                Ok(Box::new(engine))
+            },
+        );
+        registry.register(
+            "blender",
+            "Blender in parallel background jobs",
+            BlenderRenderer::CAPABILITIES,
+            |script, options, _| {
+                let mut renderer = BlenderRenderer::new(script, "output".into());
+                let cache_dir = options.scene_cache_dir.as_ref();
+                renderer.set_scene_cache_dir(cache_dir.map(|dir| dir.join("blender/scenes")));
+                renderer.set_observer(options.observer.clone());
+                Ok(Box::new(renderer))
+            },
+        );
+        registry
+    }
+
+    /// Registry without any backend
+    pub fn empty() -> Self {
+        Self {
+            renderers: BTreeMap::new(),
+        }
+    }
+
+    /// Register a backend under `name`, replacing any backend of that name
+    pub fn register(
+        &mut self,
+        name: &str,
+        description: &str,
+        capabilities: Capabilities,
+        factory: impl Fn(VideoScript, &RendererOptions, &AssetLoader) -> Result<Box<dyn Renderer>>
+            + Send
+            + Sync
+            + 'static,
+    ) {
+        self.renderers.insert(
+            name.to_string(),
+            RegisteredRenderer {
+                description: description.to_string(),
+                capabilities,
+                factory: Box::new(factory),
+            },
+        );
+    }
+
+    /// Create the backend registered under `name` for `script`
+    pub fn create(
+        &self,
+        name: &str,
+        script: VideoScript,
+        options: &RendererOptions,
+        asset_loader: &AssetLoader,
+    ) -> Result<Box<dyn Renderer>> {
+        let Some(renderer) = self.renderers.get(name) else {
+            return Err(RenderError::UnknownRenderer {
+                name: name.to_string(),
+                available: self.names().map(String::from).collect(),
+            }
+            .into());
+        };
+        (renderer.factory)(script, options, asset_loader)
+    }
+
+    /// Names of the registered backends, sorted
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.renderers.keys().map(String::as_str)
+    }
+
+    /// Description and capabilities of the backend registered under `name`
+    pub fn info(&self, name: &str) -> Option<(&str, &Capabilities)> {
+        self.renderers
+            .get(name)
+            .map(|renderer| (renderer.description.as_str(), &renderer.capabilities))
+    }
+}
+
+impl Default for RendererRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::{Effect, Layer, Metadata, Resolution, Scene, Transition, SCRIPT_VERSION};
+
+    fn create_test_script() -> VideoScript {
+        VideoScript {
+            version: SCRIPT_VERSION,
+            metadata: Metadata {
+                title: "Test".into(),
+                resolution: Resolution::Named("16x16".into()),
+                fps: 10,
+                duration: 1.0,
+                description: None,
+                citations: vec![],
+                encoder: None,
+                transparent: false,
+            },
+            scenes: vec![Scene {
+                id: "intro".into(),
+                duration: 1.0,
+                scene_type: Default::default(),
+                layers: vec![Layer::Image {
+                    source: "test.png".into(),
+                    effects: vec![Effect::Blur { radius: 2.0 }],
+                    transform: Default::default(),
+                    animation: Default::default(),
+                }],
+                transition: Some(Transition::Dissolve { duration: 0.5 }),
+            }],
+            audio: None,
+        }
+    }
+
+    #[test]
+    fn test_unsupported_features() {
+        let script = create_test_script();
+        assert!(RenderEngine::CAPABILITIES.unsupported(&script).is_empty());
+        assert_eq!(
+            BlenderRenderer::CAPABILITIES.unsupported(&script),
+            vec![
+                "blur effect in scene 'intro'".to_string(),
+                "dissolve transition in scene 'intro'".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_frame_format() {
+        let padded = FrameFormat {
+            digits: 4,
+            extension: "png",
+        };
+        assert_eq!(padded.file_name(42), "frame_0042.png");
+        assert_eq!(padded.pattern(), "frame_%04d.png");
+
+        let plain = FrameFormat {
+            digits: 0,
+            extension: "ppm",
+        };
+        assert_eq!(plain.file_name(42), "frame_42.ppm");
+        assert_eq!(plain.pattern(), "frame_%d.ppm");
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut registry = RendererRegistry::new();
+        assert_eq!(
+            registry.names().collect::<Vec<_>>(),
+            vec!["blender", "native"]
+        );
+
+        let loader = AssetLoader::new(".");
+        let options = RendererOptions {
+            use_gpu: false,
+            ..Default::default()
+        };
+        let renderer = registry
+            .create("native", create_test_script(), &options, &loader)
+            .unwrap();
+        assert_eq!(renderer.name(), "native");
+        assert!(renderer.capabilities().streaming);
+
+        let error = registry
+            .create("povray", create_test_script(), &options, &loader)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Unknown renderer 'povray' (available: blender, native)"
+        );
+
+        // Other backends register under their own name
+        registry.register(
+            "preview",
+            "Native engine without the GPU",
+            RenderEngine::CAPABILITIES,
+            |script, options, _| {
+                let mut engine = RenderEngine::new(script, false);
+                engine.set_max_in_flight_frames(options.max_in_flight_frames);
+                Ok(Box::new(engine))
+            },
+        );
+        let (description, _) = registry.info("preview").unwrap();
+        assert_eq!(description, "Native engine without the GPU");
+        assert!(registry
+            .create("preview", create_test_script(), &options, &loader)
+            .is_ok());
+    }
+}
@@ -0,0 +1,365 @@
+use crate::renderer::{FrameBuffer, RenderManifest, Timeline};
+use crate::script::{Effect, Layer, Scene, VideoScript};
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Frames of the timeline that render the same content, and the cache key of
+/// that content
+#[derive(Debug, Clone)]
+struct CacheSegment {
+    frames: Range<u32>,
+    /// Frame stored as entry 0 of the segment
+    origin: u32,
+    key: String,
+}
+
+/// Cache of rendered frames keyed per scene, so that editing one scene only
+/// re-renders that scene's frames.
+///
+/// A scene's key covers its definition, the content of the assets it references
+/// and the render settings. Transition overlaps get their own key made of both
+/// scenes' keys and the transition. Entries are PNG files in
+/// `<dir>/<key>/<index>.png`, indexed from the start of the scene (or overlap).
+#[derive(Debug, Clone)]
+pub struct SceneCache {
+    dir: PathBuf,
+    segments: Vec<CacheSegment>,
+}
+
+impl SceneCache {
+    /// Compute the cache keys of `script`'s scenes.
+    ///
+    /// Relative asset paths are resolved against `base_path`. `settings` are the
+    /// backend's render settings; with `position_dependent` a scene's key also
+    /// covers where it starts on the timeline, for backends whose frames depend on
+    /// more than the time within the scene.
+    pub fn new(
+        script: &VideoScript,
+        base_path: &Path,
+        dir: impl Into<PathBuf>,
+        settings: &[&[u8]],
+        position_dependent: bool,
+    ) -> Result<Self> {
+        let timeline = Timeline::from_script(script);
+        let ranges = timeline.scene_ranges();
+        let settings = RenderManifest::hash(settings);
+        let mut asset_hashes = HashMap::new();
+
+        let mut scene_keys = Vec::new();
+        for (scene, &(start, _)) in script.scenes.iter().zip(&ranges) {
+            let definition = serde_json::to_vec(scene)?;
+            let mut parts: Vec<Vec<u8>> = vec![settings.clone().into_bytes(), definition];
+            for path in Self::scene_assets(scene) {
+                let hash = asset_hashes
+                    .entry(path.to_path_buf())
+                    .or_insert_with(|| Self::hash_file(&resolve(base_path, path)));
+                parts.push(path.to_string_lossy().into_owned().into_bytes());
+                parts.push(hash.clone().into_bytes());
+            }
+            if position_dependent {
+                parts.push(start.to_le_bytes().to_vec());
+            }
+            scene_keys.push(Self::key(&parts));
+        }
+
+        let mut segments = Vec::new();
+        for (index, &(start, end)) in ranges.iter().enumerate() {
+            // A scene's own frames are those it doesn't share with a neighbour
+            let own_start = index
+                .checked_sub(1)
+                .map_or(start, |previous| start.max(ranges[previous].1));
+            let own_end = ranges.get(index + 1).map_or(end, |next| end.min(next.0));
+            if own_start < own_end {
+                segments.push(CacheSegment {
+                    frames: own_start..own_end,
+                    origin: start,
+                    key: scene_keys[index].clone(),
+                });
+            }
+
+            // The overlap with the next scene while the transition plays
+            let Some(&(next_start, _)) = ranges.get(index + 1) else {
+                continue;
+            };
+            if next_start < end {
+                let transition = serde_json::to_vec(&script.scenes[index].transition)?;
+                let parts = [
+                    scene_keys[index].clone().into_bytes(),
+                    scene_keys[index + 1].clone().into_bytes(),
+                    transition,
+                ];
+                segments.push(CacheSegment {
+                    frames: next_start..end,
+                    origin: next_start,
+                    key: Self::key(&parts),
+                });
+            }
+        }
+
+        Ok(Self {
+            dir: dir.into(),
+            segments,
+        })
+    }
+
+    /// Cache file for `frame`, if it belongs to a scene
+    pub fn entry_path(&self, frame: u32) -> Option<PathBuf> {
+        let segment = self
+            .segments
+            .iter()
+            .find(|segment| segment.frames.contains(&frame))?;
+        Some(
+            self.dir
+                .join(&segment.key)
+                .join(format!("{}.png", frame - segment.origin)),
+        )
+    }
+
+    /// Load the cached `frame` into `frame_buffer`, returning whether it was cached
+    pub fn load(&self, frame: u32, frame_buffer: &mut FrameBuffer) -> bool {
+        let Some(path) = self.entry_path(frame) else {
+            return false;
+        };
+        let Ok(image) = image::open(&path) else {
+            return false;
+        };
+
+        let image = image.into_rgba8();
+        if image.dimensions() != frame_buffer.dimensions() {
+            return false;
+        }
+        frame_buffer.copy_from_slice(image.as_raw());
+        true
+    }
+
+    /// Store the rendered `frame`
+    pub fn store(&self, frame: u32, frame_buffer: &FrameBuffer) -> Result<()> {
+        let Some(path) = self.entry_path(frame) else {
+            return Ok(());
+        };
+        Self::prepare_entry(&path)?;
+        // Written under a temporary name, so readers never see a partial entry
+        let temp_path = path.with_extension("tmp.png");
+        frame_buffer.save_png(&temp_path)?;
+        fs::rename(&temp_path, &path)?;
+        Ok(())
+    }
+
+    /// Copy the cached `frame` to `output`, returning whether it was cached
+    pub fn restore_file(&self, frame: u32, output: &Path) -> bool {
+        self.entry_path(frame)
+            .filter(|path| path.is_file())
+            .is_some_and(|path| fs::copy(path, output).is_ok())
+    }
+
+    /// Store an already rendered PNG file as the cache entry of `frame`
+    pub fn store_file(&self, frame: u32, rendered: &Path) -> Result<()> {
+        let Some(path) = self.entry_path(frame) else {
+            return Ok(());
+        };
+        Self::prepare_entry(&path)?;
+        let temp_path = path.with_extension("tmp.png");
+        fs::copy(rendered, &temp_path)
+            .with_context(|| format!("Failed to cache {}", rendered.display()))?;
+        fs::rename(&temp_path, &path)?;
+        Ok(())
+    }
+
+    fn prepare_entry(path: &Path) -> Result<()> {
+        let dir = path.parent().context("Cache entry has no directory")?;
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create cache directory {}", dir.display()))
+    }
+
+    /// Asset files a scene references
+    fn scene_assets(scene: &Scene) -> Vec<&Path> {
+        let mut assets = Vec::new();
+        for layer in &scene.layers {
+            match layer {
+                Layer::Image { source, .. } | Layer::Video { source, .. } => assets.push(source),
+                Layer::Text { font, .. } => assets.push(font),
+            }
+            for effect in layer.effects() {
+                if let Effect::ColorGrade(grade) = effect {
+                    assets.extend(&grade.lut);
+                }
+            }
+        }
+        assets.into_iter().map(PathBuf::as_path).collect()
+    }
+
+    /// SHA-256 of a file's content ("missing" if it can't be read, so adding the
+    /// file later changes the key)
+    fn hash_file(path: &Path) -> String {
+        let Ok(mut file) = fs::File::open(path) else {
+            return "missing".to_string();
+        };
+        let mut hasher = Sha256::new();
+        match std::io::copy(&mut file, &mut hasher) {
+            Ok(_) => format!("{:x}", hasher.finalize()),
+            Err(_) => "missing".to_string(),
+        }
+    }
+
+    fn key(parts: &[Vec<u8>]) -> String {
+        let parts: Vec<&[u8]> = parts.iter().map(Vec::as_slice).collect();
+        // Shortened: keys name directories and are not security sensitive
+        RenderManifest::hash(&parts)[..32].to_string()
+    }
+}
+
+fn resolve(base_path: &Path, path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        base_path.join(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::{Color, Metadata, Resolution, Transition, SCRIPT_VERSION};
+
+    fn create_test_script() -> VideoScript {
+        let scene = |id: &str, content: &str| Scene {
+            id: id.into(),
+            duration: 1.0,
+            scene_type: Default::default(),
+            layers: vec![Layer::Text {
+                content: content.into(),
+                font: "font.ttf".into(),
+                font_size: 12.0,
+                color: Color {
+                    r: 255,
+                    g: 255,
+                    b: 255,
+                    a: 255,
+                },
+                position: Default::default(),
+                effects: vec![],
+                transform: Default::default(),
+                animation: Default::default(),
+            }],
+            transition: None,
+        };
+
+        VideoScript {
+            version: SCRIPT_VERSION,
+            metadata: Metadata {
+                title: "Test".into(),
+                resolution: Resolution::Dimensions {
+                    width: 4,
+                    height: 4,
+                },
+                fps: 10,
+                duration: 3.0,
+                description: None,
+                citations: vec![],
+                encoder: None,
+                transparent: false,
+            },
+            scenes: vec![scene("a", "one"), scene("b", "two"), scene("c", "three")],
+            audio: None,
+        }
+    }
+
+    fn keys(cache: &SceneCache) -> Vec<Option<PathBuf>> {
+        (0..30).map(|frame| cache.entry_path(frame)).collect()
+    }
+
+    #[test]
+    fn test_scene_keys() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let base = temp_dir.path();
+        fs::write(base.join("font.ttf"), b"font v1").unwrap();
+        let new = |script: &VideoScript, position_dependent| {
+            SceneCache::new(script, base, "cache", &[b"test"], position_dependent).unwrap()
+        };
+
+        let mut script = create_test_script();
+        let cache = new(&script, false);
+        let original = keys(&cache);
+        assert!(original[0].as_ref().unwrap().ends_with("0.png"));
+        assert!(original[12].as_ref().unwrap().ends_with("2.png"));
+
+        // Editing a caption only invalidates its scene
+        script.scenes[1].layers[0] = create_test_script().scenes[2].layers[0].clone();
+        let edited = keys(&new(&script, false));
+        assert_eq!(edited[..10], original[..10]);
+        assert_ne!(edited[10..20], original[10..20]);
+        assert_eq!(edited[20..], original[20..]);
+
+        // Changing a referenced asset invalidates every scene using it
+        fs::write(base.join("font.ttf"), b"font v2").unwrap();
+        let changed = keys(&new(&script, false));
+        assert!((0..30).all(|frame| changed[frame] != edited[frame]));
+
+        // Scenes that move keep their frames unless the backend depends on position
+        let mut shorter = script.clone();
+        shorter.scenes[0].duration = 0.5;
+        let moved = keys(&new(&shorter, false));
+        assert_eq!(moved[5..15], changed[10..20]);
+        let moved = keys(&new(&shorter, true));
+        let positioned = keys(&new(&script, true));
+        assert_ne!(moved[5..15], positioned[10..20]);
+    }
+
+    #[test]
+    fn test_transition_segments() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut script = create_test_script();
+        script.scenes[0].transition = Some(Transition::Dissolve { duration: 0.5 });
+        let cache = SceneCache::new(&script, temp_dir.path(), "cache", &[], false).unwrap();
+
+        // Scene a owns 0..5, the overlap is 5..10 and scene b continues from its 5th frame
+        let dir = |frame| {
+            cache
+                .entry_path(frame)
+                .unwrap()
+                .parent()
+                .unwrap()
+                .to_owned()
+        };
+        assert_eq!(dir(0), dir(4));
+        assert_ne!(dir(4), dir(5));
+        assert_eq!(dir(5), dir(9));
+        assert_ne!(dir(9), dir(10));
+        assert!(cache.entry_path(10).unwrap().ends_with("5.png"));
+        assert!(cache.entry_path(25).is_none());
+    }
+
+    #[test]
+    fn test_store_and_load() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let script = create_test_script();
+        let cache = SceneCache::new(
+            &script,
+            temp_dir.path(),
+            temp_dir.path().join("cache"),
+            &[],
+            false,
+        )
+        .unwrap();
+
+        let mut frame_buffer = FrameBuffer::new(4, 4);
+        assert!(!cache.load(3, &mut frame_buffer));
+
+        frame_buffer.clear([10, 20, 30, 255]);
+        cache.store(3, &frame_buffer).unwrap();
+        let mut loaded = FrameBuffer::new(4, 4);
+        assert!(cache.load(3, &mut loaded));
+        assert_eq!(loaded.get_pixel(1, 1), Some([10, 20, 30, 255]));
+
+        // A frame buffer of another size doesn't match the entry
+        assert!(!cache.load(3, &mut FrameBuffer::new(2, 2)));
+
+        let output = temp_dir.path().join("frame.png");
+        assert!(cache.restore_file(3, &output));
+        assert!(!cache.restore_file(4, &output));
+    }
+}
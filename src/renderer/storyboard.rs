@@ -0,0 +1,304 @@
+use crate::assets::AssetLoader;
+use crate::error::{RenderError, Result};
+use crate::events::FrameProgress;
+use crate::renderer::{RenderEngine, TextRasterizer, Timeline};
+use crate::script::{Layer, SceneType, VideoScript};
+use ab_glyph::FontArc;
+use image::imageops::{self, FilterType};
+use image::{Rgba, RgbaImage};
+use std::path::Path;
+
+/// Background behind the tiles and captions
+const BACKGROUND: Rgba<u8> = Rgba([24, 24, 28, 255]);
+const CAPTION_COLOR: [u8; 4] = [235, 235, 235, 255];
+/// Gap between tiles and around the sheet
+const MARGIN: u32 = 12;
+
+/// A frame shown on a storyboard, with the scene it belongs to
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoryboardFrame {
+    pub frame: u32,
+    /// Timeline time of the frame in seconds
+    pub time: f32,
+    pub scene_id: String,
+    pub scene_type: SceneType,
+    pub scene_duration: f32,
+}
+
+/// Renders a contact sheet of a script: representative frames tiled into one
+/// image, each captioned with its scene and timestamp
+pub struct Storyboard;
+
+impl Storyboard {
+    /// Pick the frames to show: the middle of each scene, or `count` evenly
+    /// spaced frames across the whole timeline
+    pub fn select_frames(
+        script: &VideoScript,
+        timeline: &Timeline,
+        count: Option<u32>,
+    ) -> Vec<StoryboardFrame> {
+        let total_frames = timeline.total_frames();
+        let frames: Vec<u32> = match count {
+            Some(count) => (0..count)
+                .map(|i| ((i as f32 + 0.5) * total_frames as f32 / count as f32) as u32)
+                .collect(),
+            None => timeline
+                .scene_ranges()
+                .into_iter()
+                .map(|(start, end)| (start + end) / 2)
+                .collect(),
+        };
+
+        frames
+            .into_iter()
+            .filter(|&frame| frame < total_frames)
+            .filter_map(|frame| {
+                let scene_id = timeline.get_scene_at_frame(frame)?;
+                let scene = script.scenes.iter().find(|s| s.id == scene_id)?;
+                Some(StoryboardFrame {
+                    frame,
+                    time: timeline.frame_to_time(frame),
+                    scene_id: scene.id.clone(),
+                    scene_type: scene.scene_type.clone(),
+                    scene_duration: scene.duration,
+                })
+            })
+            .collect()
+    }
+
+    /// Font for the captions: `font` if given, otherwise the first font of the
+    /// script's text layers that loads
+    pub fn caption_font(
+        script: &VideoScript,
+        asset_loader: &mut AssetLoader,
+        font: Option<&Path>,
+    ) -> Option<FontArc> {
+        let script_fonts = script
+            .scenes
+            .iter()
+            .flat_map(|scene| &scene.layers)
+            .filter_map(|layer| match layer {
+                Layer::Text { font, .. } => Some(font.as_path()),
+                _ => None,
+            });
+
+        font.into_iter().chain(script_fonts).find_map(|path| {
+            let asset = asset_loader.load_font(path).ok()?;
+            TextRasterizer::load_font(asset.data.clone()).ok()
+        })
+    }
+
+    /// Render `frames` with `engine` and tile them `columns` wide, each scaled to
+    /// `tile_width` pixels. Captions are left out if there is no `font`.
+    pub fn render(
+        engine: &mut RenderEngine,
+        asset_loader: &mut AssetLoader,
+        frames: &[StoryboardFrame],
+        columns: u32,
+        tile_width: u32,
+        font: Option<&FontArc>,
+    ) -> Result<RgbaImage> {
+        if frames.is_empty() {
+            return Err(RenderError::NoFrames.into());
+        }
+
+        let (width, height) = engine.frame_buffer().dimensions();
+        let columns = columns.clamp(1, frames.len() as u32);
+        let rows = (frames.len() as u32).div_ceil(columns);
+        let tile_width = tile_width.max(16);
+        let tile_height = (tile_width as f32 * height as f32 / width as f32).round() as u32;
+        let font_size = (tile_width as f32 / 24.0).max(10.0);
+        let caption_height = if font.is_some() {
+            (font_size * 2.8).ceil() as u32
+        } else {
+            0
+        };
+
+        let cell_width = tile_width + MARGIN;
+        let cell_height = tile_height + caption_height + MARGIN;
+        let mut sheet = RgbaImage::from_pixel(
+            columns * cell_width + MARGIN,
+            rows * cell_height + MARGIN,
+            BACKGROUND,
+        );
+
+        let observer = engine.observer().clone();
+        let mut progress = FrameProgress::new(frames.len() as u32);
+        for (index, frame) in frames.iter().enumerate() {
+            observer.info(format!(
+                "Rendering frame {} ({} @ {})",
+                frame.frame,
+                frame.scene_id,
+                Self::timecode(frame.time)
+            ));
+            engine.render_frame(frame.frame, asset_loader)?;
+            observer.on_event(&progress.frame_done(frame.frame));
+
+            let buffer = engine.frame_buffer();
+            let image = RgbaImage::from_raw(width, height, buffer.as_bytes().to_vec())
+                .expect("frame buffer matches its dimensions");
+            let thumbnail = imageops::resize(&image, tile_width, tile_height, FilterType::Triangle);
+
+            let x = MARGIN + (index as u32 % columns) * cell_width;
+            let y = MARGIN + (index as u32 / columns) * cell_height;
+            imageops::overlay(&mut sheet, &thumbnail, x as i64, y as i64);
+
+            // Scene type marker along the top of the caption
+            let marker = Self::scene_type_color(&frame.scene_type);
+            for px in x..x + tile_width {
+                for py in y + tile_height..(y + tile_height + 3).min(sheet.height()) {
+                    sheet.put_pixel(px, py, marker);
+                }
+            }
+
+            if let Some(font) = font {
+                let caption = TextRasterizer::rasterize(
+                    font,
+                    &Self::caption(frame),
+                    font_size,
+                    CAPTION_COLOR,
+                );
+                // Keep long scene ids from spilling into the next tile
+                let caption = imageops::crop_imm(
+                    &caption,
+                    0,
+                    0,
+                    caption.width().min(tile_width),
+                    caption.height().min(caption_height),
+                )
+                .to_image();
+                let caption_y = y + tile_height + (font_size * 0.4) as u32;
+                imageops::overlay(&mut sheet, &caption, x as i64, caption_y as i64);
+            }
+        }
+
+        Ok(sheet)
+    }
+
+    /// Two caption lines: scene id and type, then scene length and timestamp
+    pub fn caption(frame: &StoryboardFrame) -> String {
+        format!(
+            "{} · {:?}\n{:.1}s scene · {}",
+            frame.scene_id,
+            frame.scene_type,
+            frame.scene_duration,
+            Self::timecode(frame.time)
+        )
+    }
+
+    /// Format seconds as `HH:MM:SS.ss`
+    pub fn timecode(time: f32) -> String {
+        let hundredths = (time.max(0.0) * 100.0).round() as u32;
+        let seconds = hundredths / 100;
+        format!(
+            "{:02}:{:02}:{:02}.{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60,
+            hundredths % 100
+        )
+    }
+
+    fn scene_type_color(scene_type: &SceneType) -> Rgba<u8> {
+        match scene_type {
+            SceneType::Hook => Rgba([240, 140, 40, 255]),
+            SceneType::Body => Rgba([80, 140, 220, 255]),
+            SceneType::Payoff => Rgba([90, 200, 120, 255]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::script::{Metadata, Resolution, Scene, SCRIPT_VERSION};
+
+    fn create_test_script() -> VideoScript {
+        let scene = |id: &str, duration, scene_type| Scene {
+            id: id.into(),
+            duration,
+            scene_type,
+            layers: vec![],
+            transition: None,
+        };
+
+        VideoScript {
+            version: SCRIPT_VERSION,
+            metadata: Metadata {
+                title: "Test".into(),
+                resolution: Resolution::Dimensions {
+                    width: 32,
+                    height: 18,
+                },
+                fps: 10,
+                duration: 3.0,
+                description: None,
+                citations: vec![],
+                encoder: None,
+                transparent: false,
+            },
+            scenes: vec![
+                scene("intro", 1.0, SceneType::Hook),
+                scene("main", 2.0, SceneType::Body),
+            ],
+            audio: None,
+        }
+    }
+
+    #[test]
+    fn test_select_frames() {
+        let script = create_test_script();
+        let timeline = Timeline::from_script(&script);
+
+        // One frame from the middle of each scene
+        let frames = Storyboard::select_frames(&script, &timeline, None);
+        let picked: Vec<_> = frames
+            .iter()
+            .map(|f| (f.frame, f.scene_id.as_str()))
+            .collect();
+        assert_eq!(picked, vec![(5, "intro"), (20, "main")]);
+        assert_eq!(frames[0].scene_type, SceneType::Hook);
+        assert_eq!(frames[1].scene_duration, 2.0);
+
+        // Evenly spaced frames across the timeline
+        let frames = Storyboard::select_frames(&script, &timeline, Some(3));
+        let picked: Vec<_> = frames.iter().map(|f| f.frame).collect();
+        assert_eq!(picked, vec![5, 15, 25]);
+        assert_eq!(frames[1].time, 1.5);
+    }
+
+    #[test]
+    fn test_caption() {
+        let frame = StoryboardFrame {
+            frame: 135,
+            time: 4.5,
+            scene_id: "intro".into(),
+            scene_type: SceneType::Hook,
+            scene_duration: 5.0,
+        };
+        assert_eq!(
+            Storyboard::caption(&frame),
+            "intro · Hook\n5.0s scene · 00:00:04.50"
+        );
+        assert_eq!(Storyboard::timecode(3725.25), "01:02:05.25");
+    }
+
+    #[test]
+    fn test_render_sheet_layout() {
+        let script = create_test_script();
+        let timeline = Timeline::from_script(&script);
+        let frames = Storyboard::select_frames(&script, &timeline, Some(3));
+
+        let mut engine = RenderEngine::new(script, false);
+        let mut asset_loader = AssetLoader::new(".");
+        let sheet =
+            Storyboard::render(&mut engine, &mut asset_loader, &frames, 2, 64, None).unwrap();
+
+        // 2 columns x 2 rows of 64x36 tiles with margins, no captions
+        assert_eq!(sheet.dimensions(), (2 * 76 + 12, 2 * 48 + 12));
+        // Frames render black; the marker under the first tile shows the hook color
+        assert_eq!(sheet.get_pixel(12, 12).0, [0, 0, 0, 255]);
+        assert_eq!(sheet.get_pixel(12, 48).0, [240, 140, 40, 255]);
+        assert_eq!(sheet.get_pixel(0, 0).0, BACKGROUND.0);
+    }
+}
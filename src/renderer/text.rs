@@ -0,0 +1,163 @@
+use ab_glyph::{Font, FontArc, GlyphId, PxScale, ScaleFont};
+use anyhow::{Context, Result};
+use image::RgbaImage;
+
+/// Rasterizes text into RGBA images using a TrueType/OpenType font
+pub struct TextRasterizer;
+
+impl TextRasterizer {
+    /// Parse font data (as read by `AssetLoader::load_font`)
+    pub fn load_font(data: Vec<u8>) -> Result<FontArc> {
+        FontArc::try_from_vec(data).context("Failed to parse font data")
+    }
+
+    /// Measure the size in pixels of the laid out text
+    pub fn measure(font: &FontArc, text: &str, font_size: f32) -> (u32, u32) {
+        let scaled = font.as_scaled(PxScale::from(font_size));
+        let line_height = scaled.height() + scaled.line_gap();
+
+        let mut width: f32 = 0.0;
+        let mut lines = 0;
+        for line in text.lines() {
+            width = width.max(Self::line_width(font, line, font_size));
+            lines += 1;
+        }
+
+        let height = if lines == 0 {
+            0.0
+        } else {
+            scaled.height() + line_height * (lines - 1) as f32
+        };
+
+        (width.ceil() as u32, height.ceil() as u32)
+    }
+
+    /// Rasterize text into an image whose top-left corner is the text origin.
+    ///
+    /// Glyph coverage is written to the alpha channel (multiplied by the color's
+    /// alpha), so the result can be alpha-blended onto a frame.
+    pub fn rasterize(font: &FontArc, text: &str, font_size: f32, color: [u8; 4]) -> RgbaImage {
+        let scale = PxScale::from(font_size);
+        let scaled = font.as_scaled(scale);
+        let line_height = scaled.height() + scaled.line_gap();
+
+        let (width, height) = Self::measure(font, text, font_size);
+        let mut image = RgbaImage::new(width.max(1), height.max(1));
+
+        for (line_idx, line) in text.lines().enumerate() {
+            let baseline = scaled.ascent() + line_height * line_idx as f32;
+            let mut caret = 0.0;
+            let mut previous: Option<GlyphId> = None;
+
+            for ch in line.chars() {
+                let glyph_id = scaled.glyph_id(ch);
+                if let Some(prev) = previous {
+                    caret += scaled.kern(prev, glyph_id);
+                }
+
+                let glyph =
+                    glyph_id.with_scale_and_position(scale, ab_glyph::point(caret, baseline));
+                caret += scaled.h_advance(glyph_id);
+                previous = Some(glyph_id);
+
+                let Some(outlined) = font.outline_glyph(glyph) else {
+                    continue; // Whitespace and glyphs without outlines
+                };
+
+                let bounds = outlined.px_bounds();
+                outlined.draw(|gx, gy, coverage| {
+                    let px = bounds.min.x as i32 + gx as i32;
+                    let py = bounds.min.y as i32 + gy as i32;
+                    if px < 0 || py < 0 || px as u32 >= image.width() || py as u32 >= image.height()
+                    {
+                        return;
+                    }
+
+                    let alpha = (coverage.clamp(0.0, 1.0) * color[3] as f32).round() as u8;
+                    let pixel = image.get_pixel_mut(px as u32, py as u32);
+                    // Overlapping glyphs keep the strongest coverage
+                    if alpha > pixel[3] {
+                        *pixel = image::Rgba([color[0], color[1], color[2], alpha]);
+                    }
+                });
+            }
+        }
+
+        image
+    }
+
+    /// Width of a single line including kerning
+    fn line_width(font: &FontArc, line: &str, font_size: f32) -> f32 {
+        let scaled = font.as_scaled(PxScale::from(font_size));
+        let mut caret = 0.0;
+        let mut previous: Option<GlyphId> = None;
+
+        for ch in line.chars() {
+            let glyph_id = scaled.glyph_id(ch);
+            if let Some(prev) = previous {
+                caret += scaled.kern(prev, glyph_id);
+            }
+            caret += scaled.h_advance(glyph_id);
+            previous = Some(glyph_id);
+        }
+
+        caret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Font bundled for the rasterization tests
+    fn test_font() -> FontArc {
+        let data = include_bytes!("../../tests/fonts/DejaVuSansMono.ttf");
+        TextRasterizer::load_font(data.to_vec()).unwrap()
+    }
+
+    #[test]
+    fn test_load_invalid_font() {
+        let result = TextRasterizer::load_font(b"fake font data".to_vec());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_measure_grows_with_text() {
+        let font = test_font();
+
+        let (short_w, short_h) = TextRasterizer::measure(&font, "Hi", 32.0);
+        let (long_w, long_h) = TextRasterizer::measure(&font, "Hello World", 32.0);
+        assert!(long_w > short_w);
+        assert_eq!(short_h, long_h);
+
+        let (_, two_lines_h) = TextRasterizer::measure(&font, "Hello\nWorld", 32.0);
+        assert!(two_lines_h > long_h);
+    }
+
+    #[test]
+    fn test_rasterize_antialiased_coverage() {
+        let font = test_font();
+
+        let image = TextRasterizer::rasterize(&font, "Test", 48.0, [255, 0, 0, 255]);
+        let alphas: Vec<u8> = image.pixels().map(|p| p[3]).collect();
+
+        // Solid glyph interiors, transparent background and partial edge coverage
+        assert!(alphas.contains(&255));
+        assert!(alphas.contains(&0));
+        assert!(alphas.iter().any(|&a| a > 0 && a < 255));
+
+        // Color is preserved on covered pixels
+        assert!(image
+            .pixels()
+            .filter(|p| p[3] > 0)
+            .all(|p| p[0] == 255 && p[1] == 0));
+    }
+
+    #[test]
+    fn test_rasterize_respects_color_alpha() {
+        let font = test_font();
+
+        let image = TextRasterizer::rasterize(&font, "I", 48.0, [255, 255, 255, 128]);
+        assert!(image.pixels().all(|p| p[3] <= 128));
+    }
+}
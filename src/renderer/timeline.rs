@@ -1,6 +1,10 @@
-use crate::script::VideoScript;
+use crate::error::RenderError;
+use crate::script::{Transition, VideoScript};
+use anyhow::{Context, Result};
+use std::ops::Range;
 
 /// Timeline for managing scene playback
+#[derive(Debug, Clone)]
 pub struct Timeline {
     fps: u32,
     total_frames: u32,
@@ -12,6 +16,21 @@ struct SceneSegment {
     scene_id: String,
     start_frame: u32,
     end_frame: u32,
+    /// Transition into the next segment, which starts before this one ends
+    transition: Option<Transition>,
+}
+
+/// Two scenes blended during the overlap of a transition
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransitionFrame<'a> {
+    pub transition: &'a Transition,
+    pub from_scene: &'a str,
+    pub to_scene: &'a str,
+    /// Time in seconds since the start of each scene
+    pub from_time: f32,
+    pub to_time: f32,
+    /// Position within the overlap, from 0.0 (outgoing) to 1.0 (incoming)
+    pub progress: f32,
 }
 
 impl Timeline {
@@ -23,15 +42,29 @@ impl Timeline {
 
         let mut segments = Vec::new();
         let mut current_frame = 0;
+        let scene_frames = |duration: f32| (duration * fps as f32) as u32;
+
+        for (idx, scene) in script.scenes.iter().enumerate() {
+            let frames = scene_frames(scene.duration);
+
+            // The next scene starts while the transition plays, so the
+            // overlap can't be longer than either scene
+            let next = script.scenes.get(idx + 1);
+            let transition = scene.transition.clone().filter(|_| next.is_some());
+            let overlap = match (&transition, next) {
+                (Some(transition), Some(next)) => scene_frames(transition.duration())
+                    .min(frames)
+                    .min(scene_frames(next.duration)),
+                _ => 0,
+            };
 
-        for scene in &script.scenes {
-            let scene_frames = (scene.duration * fps as f32) as u32;
             segments.push(SceneSegment {
                 scene_id: scene.id.clone(),
                 start_frame: current_frame,
-                end_frame: current_frame + scene_frames,
+                end_frame: current_frame + frames,
+                transition: transition.filter(|_| overlap > 0),
             });
-            current_frame += scene_frames;
+            current_frame += frames - overlap;
         }
 
         Self {
@@ -41,7 +74,7 @@ impl Timeline {
         }
     }
 
-    /// Get scene at given frame number
+    /// Get scene at given frame number (the outgoing scene during a transition)
     pub fn get_scene_at_frame(&self, frame: u32) -> Option<&str> {
         for segment in &self.scenes {
             if frame >= segment.start_frame && frame < segment.end_frame {
@@ -51,6 +84,44 @@ impl Timeline {
         None
     }
 
+    /// Get the time in seconds since the start of the scene playing at `frame`
+    pub fn scene_time_at_frame(&self, frame: u32) -> Option<f32> {
+        self.scenes
+            .iter()
+            .find(|segment| frame >= segment.start_frame && frame < segment.end_frame)
+            .map(|segment| self.frame_to_time(frame - segment.start_frame))
+    }
+
+    /// Get the transition playing at `frame`, if two scenes overlap there
+    pub fn transition_at_frame(&self, frame: u32) -> Option<TransitionFrame<'_>> {
+        self.scenes.windows(2).find_map(|pair| {
+            let (from, to) = (&pair[0], &pair[1]);
+            let transition = from.transition.as_ref()?;
+            if frame < to.start_frame || frame >= from.end_frame {
+                return None;
+            }
+
+            let overlap = from.end_frame - to.start_frame;
+            let elapsed = frame - to.start_frame;
+            Some(TransitionFrame {
+                transition,
+                from_scene: &from.scene_id,
+                to_scene: &to.scene_id,
+                from_time: self.frame_to_time(frame - from.start_frame),
+                to_time: self.frame_to_time(elapsed),
+                progress: (elapsed as f32 + 0.5) / overlap as f32,
+            })
+        })
+    }
+
+    /// Get the first and end (exclusive) frame of every scene, in script order
+    pub fn scene_ranges(&self) -> Vec<(u32, u32)> {
+        self.scenes
+            .iter()
+            .map(|segment| (segment.start_frame, segment.end_frame))
+            .collect()
+    }
+
     /// Get total frame count
     pub fn total_frames(&self) -> u32 {
         self.total_frames
@@ -65,12 +136,92 @@ impl Timeline {
     pub fn frame_to_time(&self, frame: u32) -> f32 {
         frame as f32 / self.fps as f32
     }
+
+    /// Frame on screen at `time` seconds
+    pub fn time_to_frame(&self, time: f32) -> u32 {
+        // The epsilon keeps exact frame boundaries from rounding down a frame
+        (time.max(0.0) * self.fps as f32 + 1e-3).floor() as u32
+    }
+
+    /// Frames covered by `selection`, checked against the length of the timeline
+    pub fn frame_range(&self, selection: &FrameSelection) -> Result<Range<u32>, RenderError> {
+        let range = match *selection {
+            FrameSelection::Range { start, end } => start..end.unwrap_or(self.total_frames),
+            FrameSelection::At(time) => {
+                let frame = self.time_to_frame(time);
+                frame..frame + 1
+            }
+        };
+
+        if range.is_empty() || range.end > self.total_frames {
+            return Err(RenderError::FrameRange {
+                frames: range,
+                total: self.total_frames,
+            });
+        }
+        Ok(range)
+    }
+}
+
+/// Part of the timeline to render
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FrameSelection {
+    /// Frames `start..end`, up to the last frame if `end` is unset
+    Range { start: u32, end: Option<u32> },
+    /// The single frame on screen at a time in seconds
+    At(f32),
+}
+
+impl FrameSelection {
+    /// Parse a frame range such as `120..240`, `120..=239`, `120..` or `..240`
+    pub fn parse_range(spec: &str) -> Result<Self> {
+        let (start, end) = spec
+            .split_once("..")
+            .with_context(|| format!("Invalid frame range '{}' (expected e.g. 120..240)", spec))?;
+        let frame = |value: &str| {
+            value
+                .trim()
+                .parse::<u32>()
+                .with_context(|| format!("Invalid frame number '{}' in '{}'", value, spec))
+        };
+
+        let start = if start.trim().is_empty() {
+            0
+        } else {
+            frame(start)?
+        };
+        let end =
+            match end.strip_prefix('=') {
+                Some(last) => Some(frame(last)?.checked_add(1).with_context(|| {
+                    format!("Frame number '{}' in '{}' is too large", last, spec)
+                })?),
+                None if end.trim().is_empty() => None,
+                None => Some(frame(end)?),
+            };
+        Ok(Self::Range { start, end })
+    }
+
+    /// Parse a timestamp such as `00:00:04.5`, `01:30` or `4.5` (seconds)
+    pub fn parse_time(spec: &str) -> Result<Self> {
+        let mut seconds = 0.0;
+        for part in spec.split(':') {
+            let value: f32 = part.trim().parse().with_context(|| {
+                format!("Invalid timestamp '{}' (expected e.g. 00:00:04.5)", spec)
+            })?;
+            seconds = seconds * 60.0 + value;
+        }
+
+        if spec.split(':').count() > 3 || !seconds.is_finite() || seconds < 0.0 {
+            anyhow::bail!("Invalid timestamp '{}' (expected e.g. 00:00:04.5)", spec);
+        }
+        Ok(Self::At(seconds))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::script::{Layer, Metadata, Resolution, Scene};
+    use crate::script::{Layer, Metadata, Resolution, Scene, SCRIPT_VERSION};
     use std::path::PathBuf;
 
     #[test]
@@ -96,8 +247,53 @@ mod tests {
         assert_eq!(timeline.get_scene_at_frame(200), Some("scene2"));
     }
 
+    #[test]
+    fn test_scene_time_at_frame() {
+        let script = create_test_script();
+        let timeline = Timeline::from_script(&script);
+
+        assert_eq!(timeline.scene_time_at_frame(0), Some(0.0));
+        assert_eq!(timeline.scene_time_at_frame(45), Some(1.5));
+        // Scene time restarts at the second scene
+        assert_eq!(timeline.scene_time_at_frame(165), Some(0.5));
+        assert_eq!(timeline.scene_time_at_frame(300), None);
+    }
+
+    #[test]
+    fn test_transition_overlap() {
+        let mut script = create_test_script();
+        script.scenes[0].transition = Some(Transition::Dissolve { duration: 1.0 });
+        let timeline = Timeline::from_script(&script);
+
+        // The second scene starts 1s (30 frames) before the first one ends
+        assert_eq!(timeline.scene_ranges(), vec![(0, 150), (120, 270)]);
+        assert!(timeline.transition_at_frame(119).is_none());
+        assert!(timeline.transition_at_frame(150).is_none());
+        assert_eq!(timeline.get_scene_at_frame(130), Some("scene1"));
+
+        let frame = timeline.transition_at_frame(135).unwrap();
+        assert_eq!(frame.from_scene, "scene1");
+        assert_eq!(frame.to_scene, "scene2");
+        assert_eq!(frame.from_time, 4.5);
+        assert_eq!(frame.to_time, 0.5);
+        assert!((frame.progress - 0.5167).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_transition_overlap_clamped() {
+        let mut script = create_test_script();
+        script.scenes[0].transition = Some(Transition::Fade { duration: 60.0 });
+        // The last scene has no next scene to transition into
+        script.scenes[1].transition = Some(Transition::Fade { duration: 1.0 });
+        let timeline = Timeline::from_script(&script);
+
+        assert_eq!(timeline.scene_ranges(), vec![(0, 150), (0, 150)]);
+        assert!(timeline.transition_at_frame(149).is_some());
+    }
+
     fn create_test_script() -> VideoScript {
         VideoScript {
+            version: SCRIPT_VERSION,
             metadata: Metadata {
                 title: "Test".into(),
                 resolution: Resolution::Named("1920x1080".into()),
@@ -105,6 +301,8 @@ mod tests {
                 duration: 10.0,
                 description: None,
                 citations: vec![],
+                encoder: None,
+                transparent: false,
             },
             scenes: vec![
                 Scene {
@@ -115,6 +313,7 @@ mod tests {
                         source: PathBuf::from("test.png"),
                         effects: vec![],
                         transform: Default::default(),
+                        animation: Default::default(),
                     }],
                     transition: None,
                 },
@@ -126,6 +325,7 @@ mod tests {
                         source: PathBuf::from("test2.png"),
                         effects: vec![],
                         transform: Default::default(),
+                        animation: Default::default(),
                     }],
                     transition: None,
                 },
@@ -133,4 +333,60 @@ mod tests {
             audio: None,
         }
     }
+
+    #[test]
+    fn test_frame_selection_parsing() {
+        let range = |start, end| FrameSelection::Range { start, end };
+        assert_eq!(
+            FrameSelection::parse_range("120..240").unwrap(),
+            range(120, Some(240))
+        );
+        assert_eq!(
+            FrameSelection::parse_range("120..=239").unwrap(),
+            range(120, Some(240))
+        );
+        assert_eq!(
+            FrameSelection::parse_range("120..").unwrap(),
+            range(120, None)
+        );
+        assert_eq!(
+            FrameSelection::parse_range("..10").unwrap(),
+            range(0, Some(10))
+        );
+        assert!(FrameSelection::parse_range("120").is_err());
+        assert!(FrameSelection::parse_range("a..b").is_err());
+        assert!(FrameSelection::parse_range("..=4294967295").is_err());
+
+        assert_eq!(
+            FrameSelection::parse_time("00:00:04.5").unwrap(),
+            FrameSelection::At(4.5)
+        );
+        assert_eq!(
+            FrameSelection::parse_time("01:30").unwrap(),
+            FrameSelection::At(90.0)
+        );
+        assert_eq!(
+            FrameSelection::parse_time("2").unwrap(),
+            FrameSelection::At(2.0)
+        );
+        assert!(FrameSelection::parse_time("4.5s").is_err());
+        assert!(FrameSelection::parse_time("-1").is_err());
+    }
+
+    #[test]
+    fn test_frame_range() {
+        let timeline = Timeline::from_script(&create_test_script());
+
+        let at = FrameSelection::At(4.5);
+        assert_eq!(timeline.frame_range(&at).unwrap(), 135..136);
+
+        let rest = FrameSelection::parse_range("120..").unwrap();
+        assert_eq!(timeline.frame_range(&rest).unwrap(), 120..300);
+
+        let past_end = FrameSelection::parse_range("250..400").unwrap();
+        assert!(timeline.frame_range(&past_end).is_err());
+        assert!(timeline.frame_range(&FrameSelection::At(10.0)).is_err());
+        let empty = FrameSelection::parse_range("20..20").unwrap();
+        assert!(timeline.frame_range(&empty).is_err());
+    }
 }
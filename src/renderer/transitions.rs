@@ -0,0 +1,173 @@
+use crate::renderer::FrameBuffer;
+use crate::script::{Transition, WipeDirection};
+use rayon::prelude::*;
+
+/// Blends the overlapping frames of two scenes during a transition
+pub struct Transitions;
+
+impl Transitions {
+    /// Blend the outgoing scene's frame into `incoming`, which receives the result.
+    ///
+    /// `progress` runs from 0.0 (only the outgoing scene) to 1.0 (only the incoming one).
+    pub fn blend(
+        outgoing: &FrameBuffer,
+        incoming: &mut FrameBuffer,
+        transition: &Transition,
+        progress: f32,
+    ) {
+        let progress = progress.clamp(0.0, 1.0);
+        let (width, height) = incoming.dimensions();
+        let row_bytes = width as usize * 4;
+
+        incoming
+            .as_bytes_mut()
+            .par_chunks_mut(row_bytes)
+            .zip(outgoing.as_bytes().par_chunks(row_bytes))
+            .enumerate()
+            .for_each(|(y, (to_row, from_row))| {
+                for (x, (to, from)) in to_row
+                    .chunks_exact_mut(4)
+                    .zip(from_row.chunks_exact(4))
+                    .enumerate()
+                {
+                    match transition {
+                        Transition::Cut => {
+                            if progress < 0.5 {
+                                to.copy_from_slice(from);
+                            }
+                        }
+                        // Dip to black: outgoing fades out over the first half,
+                        // incoming fades in over the second (black is laid over the
+                        // pixel, so transparent frames turn black at the midpoint)
+                        Transition::Fade { .. } => {
+                            let (pixel, brightness) = if progress < 0.5 {
+                                (from, 1.0 - progress * 2.0)
+                            } else {
+                                (&*to, progress * 2.0 - 1.0)
+                            };
+                            let alpha = pixel[3] as f32 / 255.0;
+                            let out_alpha = (1.0 - brightness) + alpha * brightness;
+                            let mut faded = [0, 0, 0, (out_alpha * 255.0).round() as u8];
+                            if out_alpha > 0.0 {
+                                for c in 0..3 {
+                                    let color = pixel[c] as f32 * alpha * brightness / out_alpha;
+                                    faded[c] = color.round() as u8;
+                                }
+                            }
+                            to.copy_from_slice(&faded);
+                        }
+                        // Crossfade with premultiplied alpha, so transparent pixels
+                        // don't darken the colors they mix with
+                        Transition::Dissolve { .. } => {
+                            let mix = |from: f32, to: f32| from + (to - from) * progress;
+                            let (from_alpha, to_alpha) =
+                                (from[3] as f32 / 255.0, to[3] as f32 / 255.0);
+                            let alpha = mix(from_alpha, to_alpha);
+                            for c in 0..3 {
+                                to[c] = if alpha > 0.0 {
+                                    let premultiplied =
+                                        mix(from[c] as f32 * from_alpha, to[c] as f32 * to_alpha);
+                                    (premultiplied / alpha).round() as u8
+                                } else {
+                                    0
+                                };
+                            }
+                            to[3] = (alpha * 255.0).round() as u8;
+                        }
+                        Transition::Wipe { direction, .. } => {
+                            let (cx, cy) = (x as f32 + 0.5, y as f32 + 0.5);
+                            let revealed = match direction {
+                                WipeDirection::Right => cx < progress * width as f32,
+                                WipeDirection::Left => cx > (1.0 - progress) * width as f32,
+                                WipeDirection::Down => cy < progress * height as f32,
+                                WipeDirection::Up => cy > (1.0 - progress) * height as f32,
+                            };
+                            if !revealed {
+                                to.copy_from_slice(from);
+                            }
+                        }
+                    }
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames() -> (FrameBuffer, FrameBuffer) {
+        let mut outgoing = FrameBuffer::new(10, 10);
+        outgoing.clear([200, 0, 0, 255]);
+        let mut incoming = FrameBuffer::new(10, 10);
+        incoming.clear([0, 0, 100, 255]);
+        (outgoing, incoming)
+    }
+
+    #[test]
+    fn test_fade_dips_to_black() {
+        let fade = Transition::Fade { duration: 1.0 };
+
+        let (outgoing, mut incoming) = frames();
+        Transitions::blend(&outgoing, &mut incoming, &fade, 0.25);
+        assert_eq!(incoming.get_pixel(5, 5), Some([100, 0, 0, 255]));
+
+        let (outgoing, mut incoming) = frames();
+        Transitions::blend(&outgoing, &mut incoming, &fade, 0.5);
+        assert_eq!(incoming.get_pixel(5, 5), Some([0, 0, 0, 255]));
+
+        let (outgoing, mut incoming) = frames();
+        Transitions::blend(&outgoing, &mut incoming, &fade, 0.75);
+        assert_eq!(incoming.get_pixel(5, 5), Some([0, 0, 50, 255]));
+    }
+
+    #[test]
+    fn test_dissolve_crossfades() {
+        let (outgoing, mut incoming) = frames();
+        let dissolve = Transition::Dissolve { duration: 1.0 };
+        Transitions::blend(&outgoing, &mut incoming, &dissolve, 0.5);
+        assert_eq!(incoming.get_pixel(0, 0), Some([100, 0, 50, 255]));
+    }
+
+    #[test]
+    fn test_transitions_keep_transparency() {
+        let (outgoing, _) = frames();
+        let transparent = || {
+            let mut frame = FrameBuffer::new(10, 10);
+            frame.clear([0, 0, 0, 0]);
+            frame
+        };
+
+        // Dissolving into nothing fades coverage without darkening the color
+        let mut incoming = transparent();
+        let dissolve = Transition::Dissolve { duration: 1.0 };
+        Transitions::blend(&outgoing, &mut incoming, &dissolve, 0.5);
+        assert_eq!(incoming.get_pixel(0, 0), Some([200, 0, 0, 128]));
+
+        // A dip to black covers transparent frames with black
+        let mut incoming = transparent();
+        let fade = Transition::Fade { duration: 1.0 };
+        Transitions::blend(&transparent(), &mut incoming, &fade, 0.25);
+        assert_eq!(incoming.get_pixel(0, 0), Some([0, 0, 0, 128]));
+    }
+
+    #[test]
+    fn test_wipe_directions() {
+        let wipe = |direction| Transition::Wipe {
+            duration: 1.0,
+            direction,
+        };
+
+        // Edge moving right: the left 30% shows the incoming scene
+        let (outgoing, mut incoming) = frames();
+        Transitions::blend(&outgoing, &mut incoming, &wipe(WipeDirection::Right), 0.3);
+        assert_eq!(incoming.get_pixel(2, 5), Some([0, 0, 100, 255]));
+        assert_eq!(incoming.get_pixel(3, 5), Some([200, 0, 0, 255]));
+
+        // Edge moving up: the bottom 30% shows the incoming scene
+        let (outgoing, mut incoming) = frames();
+        Transitions::blend(&outgoing, &mut incoming, &wipe(WipeDirection::Up), 0.3);
+        assert_eq!(incoming.get_pixel(5, 7), Some([0, 0, 100, 255]));
+        assert_eq!(incoming.get_pixel(5, 6), Some([200, 0, 0, 255]));
+    }
+}
@@ -0,0 +1,410 @@
+//! Edit a script file in place so it reads as another document, keeping the
+//! comments and formatting of everything that doesn't change
+
+use crate::diagnostics::{escape_pointer, SourceMap, Span};
+use crate::formats::{yaml_scalar, ScriptFormat};
+use serde_json::Value;
+use std::ops::Range;
+use toml_edit::{DocumentMut, Item, TableLike};
+
+/// Text of `source` changed to read as `document`, or `None` if that can't
+/// be done with edits in place
+pub(crate) fn rewrite(format: ScriptFormat, source: &str, document: &Value) -> Option<String> {
+    let (original, source_map) = format.read(source).ok()?;
+    let mut changes = Vec::new();
+    diff(&original, document, "", &mut changes);
+    if changes.is_empty() {
+        return Some(source.to_string());
+    }
+
+    let text = match format {
+        ScriptFormat::Toml => edit_toml(source, &changes)?,
+        ScriptFormat::Json | ScriptFormat::Yaml => TextEdits {
+            format,
+            source,
+            source_map: &source_map,
+            original: &original,
+            edits: Vec::new(),
+        }
+        .apply(&changes)?,
+    };
+    // Only keep the edits if they read back as the document asked for
+    let (edited, _) = format.read(&text).ok()?;
+    (edited == *document).then_some(text)
+}
+
+/// A difference between two documents, at a JSON pointer
+#[derive(Debug, PartialEq)]
+enum Change<'a> {
+    Replace {
+        pointer: String,
+        value: &'a Value,
+    },
+    Remove {
+        parent: String,
+        key: String,
+    },
+    /// Add a member to an object, before an existing one (or last)
+    Insert {
+        parent: String,
+        before: Option<String>,
+        key: String,
+        value: &'a Value,
+    },
+}
+
+/// The changes that turn `from` into `to`, down to the values that differ
+fn diff<'a>(from: &Value, to: &'a Value, pointer: &str, changes: &mut Vec<Change<'a>>) {
+    if from == to {
+        return;
+    }
+    match (from, to) {
+        (Value::Object(old), Value::Object(new)) if new.keys().any(|key| old.contains_key(key)) => {
+            for key in old.keys().filter(|key| !new.contains_key(*key)) {
+                changes.push(Change::Remove {
+                    parent: pointer.to_string(),
+                    key: key.clone(),
+                });
+            }
+            let keys: Vec<&String> = new.keys().collect();
+            for (index, (key, value)) in new.iter().enumerate() {
+                match old.get(key) {
+                    Some(old) => diff(
+                        old,
+                        value,
+                        &format!("{}/{}", pointer, escape_pointer(key)),
+                        changes,
+                    ),
+                    None => changes.push(Change::Insert {
+                        parent: pointer.to_string(),
+                        before: keys[index + 1..]
+                            .iter()
+                            .find(|key| old.contains_key(key.as_str()))
+                            .map(|key| key.to_string()),
+                        key: key.clone(),
+                        value,
+                    }),
+                }
+            }
+        }
+        (Value::Array(old), Value::Array(new)) if old.len() == new.len() => {
+            for (index, (old, new)) in old.iter().zip(new).enumerate() {
+                diff(old, new, &format!("{}/{}", pointer, index), changes);
+            }
+        }
+        _ => changes.push(Change::Replace {
+            pointer: pointer.to_string(),
+            value: to,
+        }),
+    }
+}
+
+/// Changes to JSON or YAML text, made by replacing the spans of values
+struct TextEdits<'a> {
+    format: ScriptFormat,
+    source: &'a str,
+    source_map: &'a SourceMap,
+    original: &'a Value,
+    edits: Vec<(Range<usize>, String)>,
+}
+
+impl TextEdits<'_> {
+    fn apply(mut self, changes: &[Change]) -> Option<String> {
+        for change in changes {
+            self.change(change)?;
+        }
+
+        // From the end, so earlier offsets still hold
+        self.edits
+            .sort_by_key(|(range, _)| std::cmp::Reverse((range.start, range.end)));
+        let mut text = self.source.to_string();
+        let mut limit = text.len();
+        for (range, replacement) in self.edits {
+            if range.end > limit {
+                return None;
+            }
+            limit = range.start;
+            text.replace_range(range, &replacement);
+        }
+        Some(text)
+    }
+
+    fn change(&mut self, change: &Change) -> Option<()> {
+        match change {
+            Change::Replace { pointer, value } => {
+                let span = self.source_map.span(pointer)?;
+                self.edits.push((span.value.clone(), self.inline(value)));
+            }
+            Change::Remove { parent, key } => {
+                let members = self.members(parent)?;
+                let index = members.iter().position(|member| member == key)?;
+                let member = |index: usize| self.member(parent, &members[index]);
+                let range = if index + 1 < members.len() {
+                    // Up to the next key, with the separator and indentation between them
+                    member(index)?.key?..member(index + 1)?.key?
+                } else if index == 0 {
+                    return None;
+                } else if self.is_flow(parent)? {
+                    member(index - 1)?.value.end..member(index)?.value.end
+                } else {
+                    // The whole line, keeping any comment after the value before it
+                    self.line_end(member(index - 1)?.value.end)
+                        ..self.line_end(member(index)?.value.end)
+                };
+                self.edits.push((range, String::new()));
+            }
+            Change::Insert {
+                parent,
+                before,
+                key,
+                value,
+            } => {
+                let entry = format!("{}: {}", self.key(key), self.inline(value));
+                let flow = self.is_flow(parent)?;
+                let edit = match before {
+                    Some(next) => {
+                        let at = self.member(parent, next)?.key?;
+                        let text = if flow {
+                            format!("{},{}", entry, self.separator(at))
+                        } else {
+                            format!("{}\n{}", entry, self.indentation(at))
+                        };
+                        (at..at, text)
+                    }
+                    None => {
+                        let members = self.members(parent)?;
+                        let last = self.member(parent, members.last()?)?;
+                        let key = last.key?;
+                        if flow {
+                            let at = last.value.end;
+                            (at..at, format!(",{}{}", self.separator(key), entry))
+                        } else {
+                            let at = self.line_end(last.value.end);
+                            (at..at, format!("\n{}{}", self.indentation(key), entry))
+                        }
+                    }
+                };
+                self.edits.push(edit);
+            }
+        }
+        Some(())
+    }
+
+    /// Keys of the object at `pointer`, in the order of the file
+    fn members(&self, pointer: &str) -> Option<Vec<String>> {
+        let object = self.original.pointer(pointer)?.as_object()?;
+        Some(object.keys().cloned().collect())
+    }
+
+    fn member(&self, parent: &str, key: &str) -> Option<&Span> {
+        self.source_map
+            .span(&format!("{}/{}", parent, escape_pointer(key)))
+    }
+
+    /// Whether the object at `pointer` is written with braces (all of JSON)
+    fn is_flow(&self, pointer: &str) -> Option<bool> {
+        let start = self.source_map.span(pointer)?.value.start;
+        Some(self.format == ScriptFormat::Json || self.source.as_bytes().get(start) == Some(&b'{'))
+    }
+
+    /// Whitespace before `offset`, to put after a new `,` (a space if there's none)
+    fn separator(&self, offset: usize) -> &str {
+        let before = &self.source[..offset];
+        match &before[before.trim_end().len()..] {
+            "" => " ",
+            whitespace => whitespace,
+        }
+    }
+
+    /// Indentation that lines up with `offset` on its line
+    fn indentation(&self, offset: usize) -> String {
+        let line_start = self.source[..offset].rfind('\n').map_or(0, |i| i + 1);
+        self.source[line_start..offset]
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect()
+    }
+
+    /// Offset of the end of the line `offset` is on, before its newline
+    fn line_end(&self, offset: usize) -> usize {
+        self.source[offset..]
+            .find('\n')
+            .map_or(self.source.len(), |i| offset + i)
+    }
+
+    fn key(&self, key: &str) -> String {
+        match self.format {
+            ScriptFormat::Yaml if is_plain(key) => key.to_string(),
+            _ => Value::from(key).to_string(),
+        }
+    }
+
+    /// A value written on one line (YAML takes JSON's brackets too)
+    fn inline(&self, value: &Value) -> String {
+        match value {
+            Value::Object(map) => {
+                let entries: Vec<String> = map
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", self.key(key), self.inline(value)))
+                    .collect();
+                format!("{{{}}}", entries.join(", "))
+            }
+            Value::Array(items) => {
+                let items: Vec<String> = items.iter().map(|item| self.inline(item)).collect();
+                format!("[{}]", items.join(", "))
+            }
+            Value::String(s) => self.key(s),
+            value => value.to_string(),
+        }
+    }
+}
+
+/// Whether a YAML string can be written without quotes (e.g. `left`, not `true` or `1`)
+fn is_plain(text: &str) -> bool {
+    text.starts_with(|c: char| c.is_ascii_alphabetic())
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        && yaml_scalar(text).as_str() == Some(text)
+}
+
+fn edit_toml(source: &str, changes: &[Change]) -> Option<String> {
+    let mut document: DocumentMut = source.parse().ok()?;
+    for change in changes {
+        match change {
+            Change::Replace { pointer, value } => {
+                let (parent, key) = pointer.rsplit_once('/')?;
+                let value = toml_value(value)?;
+                let old = match TomlNode::find(document.as_item_mut(), parent)? {
+                    TomlNode::Table(table) => table.get_mut(&unescape(key))?.as_value_mut()?,
+                    TomlNode::Array(array) => array.get_mut(key.parse().ok()?)?,
+                    TomlNode::Tables(_) => return None,
+                };
+                // Keep the whitespace and comments around the value
+                let decor = old.decor().clone();
+                *old = value;
+                *old.decor_mut() = decor;
+            }
+            Change::Remove { parent, key } => match TomlNode::find(document.as_item_mut(), parent)?
+            {
+                TomlNode::Table(table) => {
+                    table.remove(key)?;
+                }
+                _ => return None,
+            },
+            Change::Insert {
+                parent, key, value, ..
+            } => {
+                match TomlNode::find(document.as_item_mut(), parent)? {
+                    TomlNode::Table(table) => {
+                        table.insert(key, Item::Value(toml_value(value)?));
+                    }
+                    _ => return None,
+                }
+                if parent.is_empty() {
+                    keep_header(&mut document, key);
+                }
+            }
+        }
+    }
+    Some(document.to_string())
+}
+
+/// Keys of the root table come before all tables; if `key` is the only one,
+/// move the comments above the first table (e.g. a `#:schema` line) above it
+fn keep_header(document: &mut DocumentMut, key: &str) {
+    let root = document.as_table_mut();
+    if root.iter().filter(|(_, item)| item.is_value()).count() > 1 {
+        return;
+    }
+    let first = root
+        .iter_mut()
+        .filter_map(|(_, item)| match item {
+            Item::Table(table) => Some(table),
+            Item::ArrayOfTables(tables) => tables.iter_mut().next(),
+            _ => None,
+        })
+        .min_by_key(|table| table.position());
+    let Some(first) = first else {
+        return;
+    };
+    let header = first
+        .decor()
+        .prefix()
+        .and_then(|prefix| prefix.as_str())
+        .unwrap_or_default()
+        .to_string();
+    first.decor_mut().set_prefix("\n");
+    if let Some(mut key) = root.key_mut(key) {
+        key.leaf_decor_mut().set_prefix(header);
+    }
+}
+
+/// A container of a TOML document being edited
+enum TomlNode<'a> {
+    /// A table or an inline table
+    Table(&'a mut dyn TableLike),
+    Array(&'a mut toml_edit::Array),
+    Tables(&'a mut toml_edit::ArrayOfTables),
+}
+
+impl<'a> TomlNode<'a> {
+    /// The container at a JSON pointer under `item`
+    fn find(item: &'a mut Item, pointer: &str) -> Option<Self> {
+        let mut node = Self::item(item)?;
+        for segment in pointer.split('/').skip(1) {
+            node = match node {
+                TomlNode::Table(table) => Self::item(table.get_mut(&unescape(segment))?)?,
+                TomlNode::Array(array) => Self::value(array.get_mut(segment.parse().ok()?)?)?,
+                TomlNode::Tables(tables) => TomlNode::Table(tables.get_mut(segment.parse().ok()?)?),
+            };
+        }
+        Some(node)
+    }
+
+    fn item(item: &'a mut Item) -> Option<Self> {
+        match item {
+            Item::Table(table) => Some(TomlNode::Table(table)),
+            Item::ArrayOfTables(tables) => Some(TomlNode::Tables(tables)),
+            Item::Value(value) => Self::value(value),
+            Item::None => None,
+        }
+    }
+
+    fn value(value: &'a mut toml_edit::Value) -> Option<Self> {
+        match value {
+            toml_edit::Value::InlineTable(table) => Some(TomlNode::Table(table)),
+            toml_edit::Value::Array(array) => Some(TomlNode::Array(array)),
+            _ => None,
+        }
+    }
+}
+
+/// A JSON value as TOML, `None` if it has a `null`
+fn toml_value(value: &Value) -> Option<toml_edit::Value> {
+    Some(match value {
+        Value::Null => return None,
+        Value::Bool(b) => (*b).into(),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => i.into(),
+            None => n.as_f64()?.into(),
+        },
+        Value::String(s) => s.as_str().into(),
+        Value::Array(items) => toml_edit::Value::Array(
+            items
+                .iter()
+                .map(toml_value)
+                .collect::<Option<toml_edit::Array>>()?,
+        ),
+        Value::Object(map) => toml_edit::Value::InlineTable(
+            map.iter()
+                .map(|(key, value)| Some((key.as_str(), toml_value(value)?)))
+                .collect::<Option<toml_edit::InlineTable>>()?,
+        ),
+    })
+}
+
+/// Object key of a JSON pointer segment
+fn unescape(segment: &str) -> String {
+    segment.replace("~1", "/").replace("~0", "~")
+}
@@ -0,0 +1,391 @@
+use crate::diagnostics::{escape_pointer, Diagnostic};
+use crate::script::VideoScript;
+use serde_json::{json, Map, Value};
+
+/// JSON Schema (draft 2020-12) of video scripts, generated from the script types
+/// so editors can validate and complete scripts.
+///
+/// Scripts point editors at a saved copy with a top-level `"$schema"` key,
+/// which the parser ignores.
+pub struct ScriptSchema;
+
+impl ScriptSchema {
+    pub fn generate() -> Value {
+        let mut schema = schemars::schema_for!(VideoScript);
+        schema.insert(
+            "title".into(),
+            "Interstellar Triangulum video script".into(),
+        );
+        if let Some(Value::Object(properties)) = schema.get_mut("properties") {
+            properties.insert(
+                "$schema".into(),
+                json!({
+                    "description": "Path or URL of this schema, for editors",
+                    "type": "string"
+                }),
+            );
+            properties.insert(
+                "include".into(),
+                json!({
+                    "description": "Script fragments to read definitions and variables from, relative to this file",
+                    "type": "array",
+                    "items": {"type": "string"}
+                }),
+            );
+            properties.insert(
+                "definitions".into(),
+                json!({
+                    "description": "Reusable scenes and layer groups, used by name with `use`",
+                    "type": "object",
+                    "properties": {
+                        "scenes": {
+                            "type": "object",
+                            "additionalProperties": {"$ref": "#/$defs/SceneDefinition"}
+                        },
+                        "layers": {
+                            "type": "object",
+                            "additionalProperties": {
+                                "anyOf": [
+                                    {"$ref": "#/$defs/Layer"},
+                                    {"type": "array", "items": {"$ref": "#/$defs/Layer"}}
+                                ]
+                            }
+                        }
+                    }
+                }),
+            );
+            properties.insert(
+                "variables".into(),
+                json!({
+                    "description": "Values of the `{{name}}` placeholders in strings; a string that is only a placeholder takes the value as it is",
+                    "type": "object"
+                }),
+            );
+        }
+        let mut schema = schema.to_value();
+        add_instances(&mut schema);
+        schema
+    }
+
+    /// Keys of `document` that aren't script fields (serde skips them), as
+    /// `unknown-field` warnings naming the field that was probably meant
+    pub fn unknown_fields(document: &Value) -> Vec<Diagnostic> {
+        let schema = Self::generate();
+        let mut checker = FieldChecker {
+            root: &schema,
+            diagnostics: Vec::new(),
+        };
+        checker.check(&schema, document, "");
+        checker.diagnostics
+    }
+}
+
+/// Let scenes and scene layers be instances of definitions, which take the
+/// fields of what they use as overrides
+fn add_instances(schema: &mut Value) {
+    let defs = &mut schema["$defs"];
+    defs["Scene"]["properties"]["layers"]["items"] = json!({
+        "anyOf": [{"$ref": "#/$defs/Layer"}, {"$ref": "#/$defs/LayerGroupInstance"}]
+    });
+    let mut definition = defs["Scene"].clone();
+    if let Value::Object(definition) = &mut definition {
+        definition.shift_remove("required");
+        definition.insert(
+            "description".into(),
+            "A scene to use in others, whose strings can have `{{name}}` placeholders for `with` values".into(),
+        );
+    }
+    let mut instance = definition.clone();
+    instance["description"] = "A scene using a defined one".into();
+    instance["required"] = json!(["use"]);
+    if let Some(Value::Object(properties)) = instance.get_mut("properties") {
+        properties.shift_insert(
+            0,
+            "use".into(),
+            json!({
+                "description": "Name of the scene definition; the other fields replace its own",
+                "type": "string"
+            }),
+        );
+        properties.shift_insert(1, "with".into(), with_schema());
+    }
+    defs["SceneDefinition"] = definition;
+    defs["SceneInstance"] = instance;
+    defs["LayerGroupInstance"] = json!({
+        "description": "The layers of a layer group definition",
+        "type": "object",
+        "properties": {
+            "use": {"description": "Name of the layer definition", "type": "string"},
+            "with": with_schema()
+        },
+        "required": ["use"]
+    });
+    schema["properties"]["scenes"]["items"] = json!({
+        "anyOf": [{"$ref": "#/$defs/Scene"}, {"$ref": "#/$defs/SceneInstance"}]
+    });
+}
+
+fn with_schema() -> Value {
+    json!({
+        "description": "Values of the definition's `{{name}}` placeholders",
+        "type": "object"
+    })
+}
+
+/// Walks a document along the schema, following the variant each value takes
+struct FieldChecker<'a> {
+    root: &'a Value,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> FieldChecker<'a> {
+    /// Follow `$ref`s to the schema they point at
+    fn resolve(&self, schema: &'a Value) -> &'a Value {
+        let target = schema
+            .get("$ref")
+            .and_then(Value::as_str)
+            .and_then(|reference| reference.strip_prefix('#'))
+            .and_then(|pointer| self.root.pointer(pointer));
+        match target {
+            Some(target) => self.resolve(target),
+            None => schema,
+        }
+    }
+
+    /// Schemas a value of `schema` can take, with nested alternatives flattened
+    fn variants(&self, schema: &'a Value, variants: &mut Vec<&'a Value>) {
+        let schema = self.resolve(schema);
+        match alternatives(schema) {
+            Some(alternatives) => {
+                for alternative in alternatives {
+                    self.variants(alternative, variants);
+                }
+            }
+            None => variants.push(schema),
+        }
+    }
+
+    fn check(&mut self, schema: &'a Value, value: &Value, pointer: &str) {
+        let schema = self.resolve(schema);
+        if alternatives(schema).is_some() {
+            let mut candidates = Vec::new();
+            self.variants(schema, &mut candidates);
+            candidates.retain(|candidate| accepts(candidate, value));
+            return match (value, candidates.as_slice()) {
+                (_, []) => {}
+                (Value::Object(map), [_, _, ..]) => {
+                    // The first of the variants with the most matching keys
+                    let best = candidates
+                        .iter()
+                        .rev()
+                        .max_by_key(|candidate| {
+                            map.keys().filter(|key| has_field(candidate, key)).count()
+                        })
+                        .expect("candidates aren't empty");
+                    if map.keys().any(|key| has_field(best, key)) {
+                        self.check(best, value, pointer);
+                    } else {
+                        // No variant matched any key (e.g. a misspelled effect name)
+                        self.fields(&candidates, map, pointer);
+                    }
+                }
+                (_, [first, ..]) => self.check(first, value, pointer),
+            };
+        }
+
+        match value {
+            Value::Object(map) => self.fields(&[schema], map, pointer),
+            Value::Array(items) => {
+                if let Some(item_schema) = schema.get("items") {
+                    for (index, item) in items.iter().enumerate() {
+                        self.check(item_schema, item, &format!("{}/{}", pointer, index));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Check the keys of an object that can be any of `schemas`
+    fn fields(&mut self, schemas: &[&'a Value], map: &Map<String, Value>, pointer: &str) {
+        for (key, value) in map {
+            let key_pointer = format!("{}/{}", pointer, escape_pointer(key));
+            let field = schemas.iter().find_map(|schema| {
+                properties(schema)
+                    .and_then(|fields| fields.get(key))
+                    .or_else(|| {
+                        schema
+                            .get("additionalProperties")
+                            .filter(|additional| additional.is_object())
+                    })
+            });
+            if let Some(field) = field {
+                self.check(field, value, &key_pointer);
+            } else if schemas.iter().any(|schema| properties(schema).is_some()) {
+                let names = schemas
+                    .iter()
+                    .filter_map(|schema| properties(schema))
+                    .flat_map(Map::keys);
+                let message = match closest(key, names) {
+                    Some(name) => format!("unknown field `{}`, did you mean `{}`?", key, name),
+                    None => format!("unknown field `{}`", key),
+                };
+                self.diagnostics
+                    .push(Diagnostic::warning("unknown-field", key_pointer, message));
+            }
+        }
+    }
+}
+
+fn alternatives(schema: &Value) -> Option<&Vec<Value>> {
+    schema
+        .get("oneOf")
+        .or_else(|| schema.get("anyOf"))
+        .and_then(Value::as_array)
+}
+
+/// Named fields of an object schema
+fn properties(schema: &Value) -> Option<&Map<String, Value>> {
+    schema.get("properties").and_then(Value::as_object)
+}
+
+fn has_field(schema: &Value, key: &str) -> bool {
+    properties(schema).is_some_and(|fields| fields.contains_key(key))
+}
+
+/// Whether `value` can be an instance of `schema`, judging by its type and
+/// by the constant fields that tell variants apart (e.g. a layer's `type`)
+fn accepts(schema: &Value, value: &Value) -> bool {
+    let kind = match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    };
+    let typed = match schema.get("type") {
+        Some(Value::String(name)) => name == kind || (name == "number" && kind == "integer"),
+        Some(Value::Array(names)) => names
+            .iter()
+            .any(|name| name == kind || (name == "number" && kind == "integer")),
+        _ => true,
+    };
+    let Value::Object(map) = value else {
+        return typed;
+    };
+    typed
+        && properties(schema)
+            .into_iter()
+            .flatten()
+            .all(|(key, field)| match (field.get("const"), map.get(key)) {
+                (Some(expected), Some(actual)) => expected == actual,
+                _ => true,
+            })
+}
+
+/// Known name closest to `key`, if it's close enough to be a typo
+pub(crate) fn closest<'a>(key: &str, names: impl Iterator<Item = &'a String>) -> Option<&'a str> {
+    names
+        .map(|name| (strsim::jaro_winkler(key, name), name))
+        .filter(|(similarity, _)| *similarity > 0.8)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, name)| name.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate() {
+        let schema = ScriptSchema::generate();
+        assert_eq!(
+            schema["$schema"],
+            "https://json-schema.org/draft/2020-12/schema"
+        );
+        assert_eq!(schema["required"], json!(["metadata", "scenes"]));
+        assert!(schema["properties"]["$schema"].is_object());
+
+        let defs = &schema["$defs"];
+        assert_eq!(
+            defs["Scene"]["properties"]["id"]["description"],
+            "Unique name of the scene, used in reports and the scene cache"
+        );
+        assert_eq!(
+            defs["WipeDirection"]["enum"],
+            json!(["left", "right", "up", "down"])
+        );
+        // Flattened playback options are fields of the video layer
+        let video = defs["Layer"]["oneOf"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|layer| layer["properties"]["type"]["const"] == "video")
+            .unwrap();
+        assert_eq!(video["properties"]["playback_rate"]["default"], 1.0);
+        assert_eq!(video["properties"]["loop"]["default"], false);
+
+        // Scenes and layers can use definitions
+        assert_eq!(defs["SceneInstance"]["required"], json!(["use"]));
+        assert!(defs["SceneDefinition"].get("required").is_none());
+        assert!(schema["properties"]["definitions"]["properties"]["layers"].is_object());
+    }
+
+    #[test]
+    fn test_unknown_fields() {
+        let document = json!({
+            "$schema": "./script.schema.json",
+            "version": 2,
+            "metadata": {"title": "T", "resolution": {"width": 640, "heigth": 360}, "fps": 30, "duration": 2.0},
+            "scenes": [{
+                "id": "intro",
+                "duration": 2.0,
+                "scene_typ": "hook",
+                "layers": [
+                    {"type": "image", "source": "a.png", "transform": {"opactiy": 0.5}},
+                    {"type": "video", "source": "b.mp4", "loop": true, "trim_inn": 1.0,
+                        "effects": [{"fade_inn": {}}, {"blur": {"radius": 2.0}}],
+                        "animation": {"scale": [{"time": 0.0, "value": 1.0, "easing": "linear"}]}},
+                    {"type": "text", "content": "Hi", "font": "f.ttf", "font_size": 12.0,
+                        "color": {"r": 0, "g": 0, "b": 0}, "xyzzy": 1}
+                ],
+                "transition": {"wipe": {"duration": 0.5, "direction": "left"}}
+            }]
+        });
+        let found: Vec<_> = ScriptSchema::unknown_fields(&document)
+            .into_iter()
+            .map(|d| (d.pointer, d.message))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "/metadata/resolution/heigth".to_string(),
+                    "unknown field `heigth`, did you mean `height`?".to_string()
+                ),
+                (
+                    "/scenes/0/scene_typ".into(),
+                    "unknown field `scene_typ`, did you mean `scene_type`?".into()
+                ),
+                (
+                    "/scenes/0/layers/0/transform/opactiy".into(),
+                    "unknown field `opactiy`, did you mean `opacity`?".into()
+                ),
+                (
+                    "/scenes/0/layers/1/trim_inn".into(),
+                    "unknown field `trim_inn`, did you mean `trim_in`?".into()
+                ),
+                (
+                    "/scenes/0/layers/1/effects/0/fade_inn".into(),
+                    "unknown field `fade_inn`, did you mean `fade_in`?".into()
+                ),
+                (
+                    "/scenes/0/layers/2/xyzzy".into(),
+                    "unknown field `xyzzy`".into()
+                ),
+            ]
+        );
+    }
+}
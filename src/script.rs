@@ -1,34 +1,170 @@
+use crate::animation::Animation;
+use schemars::JsonSchema;
+use serde::de::IntoDeserializer;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Version of the script format this build reads. Changes that break existing
+/// scripts bump it and add a migration to `ScriptParser`.
+pub const SCRIPT_VERSION: u32 = 2;
 
 /// Main video script structure that defines the entire video
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct VideoScript {
+    /// Version of the script format; scripts without one are version 1
+    #[serde(default = "first_version")]
+    pub version: u32,
     pub metadata: Metadata,
+    /// Scenes in playing order
     pub scenes: Vec<Scene>,
+    /// Soundtrack mixed under the video
     #[serde(default)]
     pub audio: Option<AudioConfig>,
 }
 
+fn first_version() -> u32 {
+    1
+}
+
 /// Video metadata and configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Metadata {
     pub title: String,
     pub resolution: Resolution,
+    /// Frames per second
     pub fps: u32,
+    /// Total length in seconds; scene durations should add up to it
     pub duration: f32,
     #[serde(default)]
     pub description: Option<String>,
+    /// Sources backing the script's claims, checked by the credibility analysis
     #[serde(default)]
     pub citations: Vec<String>,
+    /// Encoder settings for this script, overriding the `[encoder]` config section
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoder: Option<EncoderSettings>,
+    /// Render with a transparent background instead of black
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub transparent: bool,
+}
+
+/// Output file and codec options; unset fields fall back to the next layer
+/// (command line, then script metadata, then config, then built-in defaults)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct EncoderSettings {
+    /// Output video file, relative to the script (default: `output.<container>`
+    /// in the working directory)
+    pub output: Option<PathBuf>,
+    /// Container format (default: from the output extension, else mp4)
+    pub container: Option<Container>,
+    /// Video codec (default: VP9 for webm, H.264 otherwise)
+    pub codec: Option<VideoCodec>,
+    /// Constant rate factor (lower is better quality)
+    pub crf: Option<u32>,
+    /// Target video bitrate in FFmpeg notation, e.g. "8M"
+    pub bitrate: Option<String>,
+    /// Encoder speed preset, e.g. "slow" (the profile for ProRes, e.g. "hq")
+    pub preset: Option<String>,
+    /// Output pixel format, e.g. "yuv420p"
+    pub pixel_format: Option<String>,
+    /// Audio bitrate in FFmpeg notation, e.g. "192k"
+    pub audio_bitrate: Option<String>,
+}
+
+impl EncoderSettings {
+    /// Use these settings, falling back to `base` for unset fields
+    pub fn or(self, base: &EncoderSettings) -> Self {
+        Self {
+            output: self.output.or_else(|| base.output.clone()),
+            container: self.container.or(base.container),
+            codec: self.codec.or(base.codec),
+            crf: self.crf.or(base.crf),
+            bitrate: self.bitrate.or_else(|| base.bitrate.clone()),
+            preset: self.preset.or_else(|| base.preset.clone()),
+            pixel_format: self.pixel_format.or_else(|| base.pixel_format.clone()),
+            audio_bitrate: self.audio_bitrate.or_else(|| base.audio_bitrate.clone()),
+        }
+    }
+}
+
+/// Video container format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Container {
+    Mp4,
+    Mov,
+    Mkv,
+    Webm,
+    /// Numbered PNG image sequence (the output path needs a pattern like `frame_%04d.png`)
+    Png,
+}
+
+impl Container {
+    /// File extension for the container
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Container::Mp4 => "mp4",
+            Container::Mov => "mov",
+            Container::Mkv => "mkv",
+            Container::Webm => "webm",
+            Container::Png => "png",
+        }
+    }
+
+    /// Container matching a file extension
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "mp4" | "m4v" => Some(Container::Mp4),
+            "mov" => Some(Container::Mov),
+            "mkv" => Some(Container::Mkv),
+            "webm" => Some(Container::Webm),
+            "png" => Some(Container::Png),
+            _ => None,
+        }
+    }
+}
+
+/// Video codec
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VideoCodec {
+    H264,
+    H265,
+    Vp9,
+    Av1,
+    Prores,
+}
+
+impl FromStr for Container {
+    type Err = serde::de::value::Error;
+
+    /// Parse a container by its name in scripts (`mp4`)
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::deserialize(name.into_deserializer())
+    }
+}
+
+impl FromStr for VideoCodec {
+    type Err = serde::de::value::Error;
+
+    /// Parse a codec by its name in scripts (`h264`)
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::deserialize(name.into_deserializer())
+    }
 }
 
 /// Video resolution
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(untagged)]
 pub enum Resolution {
-    Named(String), // e.g., "1920x1080", "1280x720"
-    Dimensions { width: u32, height: u32 },
+    /// "WIDTHxHEIGHT", e.g. "1920x1080"
+    Named(String),
+    Dimensions {
+        width: u32,
+        height: u32,
+    },
 }
 
 impl Resolution {
@@ -50,71 +186,247 @@ impl Resolution {
 }
 
 /// A scene in the video
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Scene {
+    /// Unique name of the scene, used in reports and the scene cache
     pub id: String,
+    /// Length in seconds
     pub duration: f32,
     #[serde(default)]
     pub scene_type: SceneType,
+    /// Layers drawn bottom to top
     pub layers: Vec<Layer>,
+    /// Transition into the next scene; the two scenes overlap for its duration
     #[serde(default)]
     pub transition: Option<Transition>,
 }
 
 /// Type of scene for narrative structure
-#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum SceneType {
     #[default]
     Body,
+    /// Opening that grabs attention
     Hook,
+    /// Conclusion that delivers on the hook
     Payoff,
 }
 
 /// A layer within a scene (can be video, image, text, etc.)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type")]
 pub enum Layer {
     #[serde(rename = "video")]
     Video {
+        /// Video file, relative to the working directory
         source: PathBuf,
         #[serde(default)]
         effects: Vec<Effect>,
         #[serde(default)]
         transform: Transform,
+        #[serde(default)]
+        animation: Animation,
+        /// Trim, speed, looping and audio options
+        #[serde(flatten)]
+        playback: VideoPlayback,
     },
     #[serde(rename = "image")]
     Image {
+        /// Image file, relative to the working directory
         source: PathBuf,
         #[serde(default)]
         effects: Vec<Effect>,
         #[serde(default)]
         transform: Transform,
+        #[serde(default)]
+        animation: Animation,
     },
     #[serde(rename = "text")]
     Text {
         content: String,
+        /// TrueType or OpenType font file
         font: PathBuf,
+        /// Font size in pixels
         font_size: f32,
         color: Color,
+        /// Frame position of the top-left corner of the text
         #[serde(default)]
         position: Position,
         #[serde(default)]
         effects: Vec<Effect>,
+        /// Scale, rotation, opacity and anchor (its position offsets `position`)
+        #[serde(default)]
+        transform: Transform,
+        #[serde(default)]
+        animation: Animation,
     },
 }
 
+impl Layer {
+    /// Layer type as written in scripts
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Layer::Video { .. } => "video",
+            Layer::Image { .. } => "image",
+            Layer::Text { .. } => "text",
+        }
+    }
+
+    /// Effects applied to the layer
+    pub fn effects(&self) -> &[Effect] {
+        match self {
+            Layer::Video { effects, .. }
+            | Layer::Image { effects, .. }
+            | Layer::Text { effects, .. } => effects,
+        }
+    }
+
+    /// Mutable access to the layer transform
+    pub fn transform_mut(&mut self) -> &mut Transform {
+        match self {
+            Layer::Video { transform, .. }
+            | Layer::Image { transform, .. }
+            | Layer::Text { transform, .. } => transform,
+        }
+    }
+
+    /// Keyframe tracks of the layer
+    pub fn animation(&self) -> &Animation {
+        match self {
+            Layer::Video { animation, .. }
+            | Layer::Image { animation, .. }
+            | Layer::Text { animation, .. } => animation,
+        }
+    }
+
+    /// The layer with animated properties evaluated at `time` seconds into its scene
+    pub fn at_time(&self, time: f32) -> Layer {
+        let mut layer = self.clone();
+        match &mut layer {
+            Layer::Video {
+                transform,
+                animation,
+                ..
+            }
+            | Layer::Image {
+                transform,
+                animation,
+                ..
+            } => {
+                *transform = animation.transform_at(transform, time);
+            }
+            Layer::Text {
+                transform,
+                color,
+                animation,
+                ..
+            } => {
+                *transform = animation.transform_at(transform, time);
+                *color = animation.color_at(color, time);
+            }
+        }
+        layer
+    }
+}
+
+/// Playback options of a video layer
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct VideoPlayback {
+    /// Source time in seconds where the clip starts
+    #[serde(default)]
+    pub trim_in: f32,
+    /// Source time in seconds where the clip ends (the end of the source by default)
+    #[serde(default)]
+    pub trim_out: Option<f32>,
+    /// Speed multiplier (2.0 plays twice as fast)
+    #[serde(default = "default_factor")]
+    pub playback_rate: f32,
+    /// Restart at `trim_in` when the clip ends instead of disappearing
+    #[serde(default, rename = "loop")]
+    pub looping: bool,
+    /// Mix the clip's audio into the soundtrack
+    #[serde(default)]
+    pub audio: bool,
+    /// Volume of the clip's audio
+    #[serde(default = "default_factor")]
+    pub volume: f32,
+}
+
+impl Default for VideoPlayback {
+    fn default() -> Self {
+        Self {
+            trim_in: 0.0,
+            trim_out: None,
+            playback_rate: default_factor(),
+            looping: false,
+            audio: false,
+            volume: default_factor(),
+        }
+    }
+}
+
+impl VideoPlayback {
+    /// Source time shown `time` seconds into the scene, or `None` once a
+    /// non-looping clip has ended
+    pub fn source_time(&self, time: f32, source_duration: f32) -> Option<f32> {
+        let end = self
+            .trim_out
+            .unwrap_or(source_duration)
+            .min(source_duration);
+        let length = end - self.trim_in;
+        if length <= 0.0 || time < 0.0 {
+            return None;
+        }
+
+        let elapsed = time * self.playback_rate;
+        if self.looping {
+            Some(self.trim_in + elapsed.rem_euclid(length))
+        } else if elapsed < length {
+            Some(self.trim_in + elapsed)
+        } else {
+            None
+        }
+    }
+}
+
 /// Transform for positioning and scaling layers
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Transform {
+    /// Frame position of the layer's anchor point
     #[serde(default)]
     pub position: Position,
+    /// Size multiplier around the anchor point
     #[serde(default = "default_scale")]
     pub scale: f32,
+    /// Clockwise rotation in degrees around the anchor point
     #[serde(default)]
     pub rotation: f32,
+    /// From 0.0 (invisible) to 1.0 (opaque)
     #[serde(default = "default_opacity")]
     pub opacity: f32,
+    #[serde(default)]
+    pub anchor: Anchor,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            position: Position::default(),
+            scale: default_scale(),
+            rotation: 0.0,
+            opacity: default_opacity(),
+            anchor: Anchor::default(),
+        }
+    }
+}
+
+/// Pivot point for scale and rotation, normalized to the layer size
+/// (0,0 is the top-left corner, 0.5,0.5 the center)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default, JsonSchema)]
+pub struct Anchor {
+    pub x: f32,
+    pub y: f32,
 }
 
 fn default_scale() -> f32 {
@@ -125,19 +437,20 @@ fn default_opacity() -> f32 {
     1.0
 }
 
-/// Position in the frame
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+/// Position in the frame, in pixels from the top-left corner
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default, JsonSchema)]
 pub struct Position {
     pub x: i32,
     pub y: i32,
 }
 
-/// Color representation
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// RGBA color with 0-255 channels
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Color {
     pub r: u8,
     pub g: u8,
     pub b: u8,
+    /// Opacity (255 is opaque)
     #[serde(default = "default_alpha")]
     pub a: u8,
 }
@@ -147,39 +460,145 @@ fn default_alpha() -> u8 {
 }
 
 /// Visual effects
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum Effect {
-    FadeIn,
-    FadeOut,
-    Blur { radius: f32 },
-    ColorGrade { adjustment: String },
+    /// Opacity ramp from 0 at the start of the scene
+    FadeIn {
+        #[serde(default = "default_fade_duration")]
+        duration: f32,
+    },
+    /// Opacity ramp to 0 at the end of the scene
+    FadeOut {
+        #[serde(default = "default_fade_duration")]
+        duration: f32,
+    },
+    /// Gaussian blur; `radius` is the kernel extent in layer pixels (3 standard deviations)
+    Blur {
+        radius: f32,
+    },
+    ColorGrade(ColorGrade),
+}
+
+impl Effect {
+    /// Effect name as written in scripts
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Effect::FadeIn { .. } => "fade_in",
+            Effect::FadeOut { .. } => "fade_out",
+            Effect::Blur { .. } => "blur",
+            Effect::ColorGrade(_) => "color_grade",
+        }
+    }
+}
+
+fn default_fade_duration() -> f32 {
+    0.5
+}
+
+/// Color grading parameters, applied in this order
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct ColorGrade {
+    /// Exposure adjustment in stops
+    #[serde(default)]
+    pub exposure: f32,
+    /// Contrast around mid-gray (1.0 leaves the image unchanged)
+    #[serde(default = "default_factor")]
+    pub contrast: f32,
+    /// Saturation (0.0 is grayscale, 1.0 leaves the image unchanged)
+    #[serde(default = "default_factor")]
+    pub saturation: f32,
+    /// White balance shift from -1.0 (cool) to 1.0 (warm)
+    #[serde(default)]
+    pub temperature: f32,
+    /// Optional 3D LUT in Adobe `.cube` format, applied last
+    #[serde(default)]
+    pub lut: Option<PathBuf>,
+}
+
+impl Default for ColorGrade {
+    fn default() -> Self {
+        Self {
+            exposure: 0.0,
+            contrast: default_factor(),
+            saturation: default_factor(),
+            temperature: 0.0,
+            lut: None,
+        }
+    }
+}
+
+fn default_factor() -> f32 {
+    1.0
 }
 
 /// Transition between scenes
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum Transition {
+    /// Switch straight to the next scene
     Cut,
+    /// Fade the outgoing scene to black, then the incoming scene in
     Fade { duration: f32 },
+    /// Cross-dissolve from the outgoing to the incoming scene
     Dissolve { duration: f32 },
-    Wipe { duration: f32, direction: String },
+    /// Reveal the incoming scene behind an edge moving in `direction`
+    Wipe {
+        duration: f32,
+        direction: WipeDirection,
+    },
+}
+
+impl Transition {
+    /// Transition name as written in scripts
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Transition::Cut => "cut",
+            Transition::Fade { .. } => "fade",
+            Transition::Dissolve { .. } => "dissolve",
+            Transition::Wipe { .. } => "wipe",
+        }
+    }
+
+    /// Length of the overlap between the two scenes in seconds
+    pub fn duration(&self) -> f32 {
+        match self {
+            Transition::Cut => 0.0,
+            Transition::Fade { duration }
+            | Transition::Dissolve { duration }
+            | Transition::Wipe { duration, .. } => *duration,
+        }
+    }
+}
+
+/// Direction in which a wipe edge travels across the frame
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WipeDirection {
+    Left,
+    Right,
+    Up,
+    Down,
 }
 
 /// Audio configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AudioConfig {
+    /// Tracks mixed together
     pub tracks: Vec<AudioTrack>,
 }
 
 /// Individual audio track
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AudioTrack {
+    /// Audio file (WAV, MP3, FLAC, OGG...)
     pub source: PathBuf,
     #[serde(default)]
     pub track_type: AudioTrackType,
+    /// Volume multiplier
     #[serde(default = "default_volume")]
     pub volume: f32,
+    /// Time in seconds into the video where the track starts
     #[serde(default)]
     pub start_time: f32,
 }
@@ -189,7 +608,7 @@ fn default_volume() -> f32 {
 }
 
 /// Type of audio track
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum AudioTrackType {
     #[default]
@@ -283,6 +702,37 @@ mod tests {
         assert_eq!(transform.rotation, 0.0); // Uses serde default (0.0)
         assert_eq!(transform.position.x, 0); // Uses Position::default()
         assert_eq!(transform.position.y, 0);
+        assert_eq!(transform.anchor, Anchor { x: 0.0, y: 0.0 });
+    }
+
+    #[test]
+    fn test_transform_default_matches_serde() {
+        // Layers without a transform must stay visible at their natural size
+        let transform = Transform::default();
+        assert_eq!(transform.scale, 1.0);
+        assert_eq!(transform.opacity, 1.0);
+        assert_eq!(transform.rotation, 0.0);
+    }
+
+    #[test]
+    fn test_text_layer_transform() {
+        let json = r#"{
+            "type": "text",
+            "content": "Hello",
+            "font": "font.ttf",
+            "font_size": 24.0,
+            "color": {"r": 255, "g": 255, "b": 255},
+            "transform": {"rotation": 45.0, "anchor": {"x": 0.5, "y": 0.5}}
+        }"#;
+        let layer: Layer = serde_json::from_str(json).unwrap();
+        match layer {
+            Layer::Text { transform, .. } => {
+                assert_eq!(transform.rotation, 45.0);
+                assert_eq!(transform.scale, 1.0);
+                assert_eq!(transform.anchor, Anchor { x: 0.5, y: 0.5 });
+            }
+            _ => panic!("Expected Text layer"),
+        }
     }
 
     #[test]
@@ -318,6 +768,36 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_layer_at_time() {
+        let json = r#"{
+            "type": "text",
+            "content": "Hello",
+            "font": "font.ttf",
+            "font_size": 24.0,
+            "color": {"r": 0, "g": 0, "b": 0},
+            "animation": {
+                "opacity": [{"time": 0.0, "value": 0.0}, {"time": 1.0, "value": 1.0}],
+                "color": [
+                    {"time": 0.0, "value": {"r": 0, "g": 0, "b": 0}},
+                    {"time": 1.0, "value": {"r": 200, "g": 100, "b": 0}}
+                ]
+            }
+        }"#;
+        let layer: Layer = serde_json::from_str(json).unwrap();
+        assert!(!layer.animation().is_empty());
+
+        match layer.at_time(0.5) {
+            Layer::Text {
+                transform, color, ..
+            } => {
+                assert_eq!(transform.opacity, 0.5);
+                assert_eq!((color.r, color.g, color.b), (100, 50, 0));
+            }
+            _ => panic!("Expected Text layer"),
+        }
+    }
+
     #[test]
     fn test_audio_track_defaults() {
         let json = r#"{"source": "music.mp3"}"#;
@@ -329,4 +809,109 @@ mod tests {
             _ => panic!("Expected Music as default track type"),
         }
     }
+
+    #[test]
+    fn test_effects_deserialize() {
+        let json = r#"[
+            {"fade_in": {}},
+            {"fade_out": {"duration": 1.5}},
+            {"blur": {"radius": 4.0}},
+            {"color_grade": {"exposure": 0.5, "lut": "looks/film.cube"}}
+        ]"#;
+        let effects: Vec<Effect> = serde_json::from_str(json).unwrap();
+
+        assert_eq!(effects[0], Effect::FadeIn { duration: 0.5 });
+        assert_eq!(effects[1], Effect::FadeOut { duration: 1.5 });
+        assert_eq!(effects[2], Effect::Blur { radius: 4.0 });
+        assert_eq!(
+            effects[3],
+            Effect::ColorGrade(ColorGrade {
+                exposure: 0.5,
+                lut: Some(PathBuf::from("looks/film.cube")),
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_transition_deserialize() {
+        let wipe: Transition =
+            serde_json::from_str(r#"{"wipe": {"duration": 0.5, "direction": "left"}}"#).unwrap();
+        assert_eq!(
+            wipe,
+            Transition::Wipe {
+                duration: 0.5,
+                direction: WipeDirection::Left
+            }
+        );
+        assert_eq!(wipe.duration(), 0.5);
+
+        let cut: Transition = serde_json::from_str(r#""cut""#).unwrap();
+        assert_eq!(cut.duration(), 0.0);
+
+        // Unknown directions are rejected when parsing
+        let invalid = serde_json::from_str::<Transition>(
+            r#"{"wipe": {"duration": 0.5, "direction": "diagonal"}}"#,
+        );
+        assert!(invalid.is_err());
+    }
+
+    #[test]
+    fn test_video_playback() {
+        let json = r#"{"type": "video", "source": "clip.mp4", "trim_in": 2.0, "trim_out": 4.0,
+            "playback_rate": 2.0, "loop": true, "audio": true}"#;
+        let layer: Layer = serde_json::from_str(json).unwrap();
+        let Layer::Video { playback, .. } = layer else {
+            panic!("Expected a video layer");
+        };
+        assert!(playback.looping && playback.audio);
+        assert_eq!(playback.volume, 1.0);
+
+        // 2s clip played at double speed restarts every second of scene time
+        assert_eq!(playback.source_time(0.0, 10.0), Some(2.0));
+        assert_eq!(playback.source_time(0.5, 10.0), Some(3.0));
+        assert_eq!(playback.source_time(1.25, 10.0), Some(2.5));
+
+        // Without looping the clip ends after its trimmed length
+        let once = VideoPlayback {
+            looping: false,
+            ..playback
+        };
+        assert_eq!(once.source_time(0.75, 10.0), Some(3.5));
+        assert_eq!(once.source_time(1.0, 10.0), None);
+
+        // Trim points past the end of the source are clamped
+        let defaults = VideoPlayback::default();
+        assert_eq!(defaults.source_time(2.0, 3.0), Some(2.0));
+        assert_eq!(defaults.source_time(3.0, 3.0), None);
+    }
+
+    #[test]
+    fn test_encoder_settings_layering() {
+        let json = r#"{"title": "T", "resolution": "1280x720", "fps": 30, "duration": 1.0,
+            "encoder": {"codec": "h265", "crf": 20}}"#;
+        let metadata: Metadata = serde_json::from_str(json).unwrap();
+        let script = metadata.encoder.unwrap();
+        assert_eq!(script.codec, Some(VideoCodec::H265));
+
+        let config = EncoderSettings {
+            crf: Some(28),
+            preset: Some("slow".into()),
+            ..Default::default()
+        };
+        let cli = EncoderSettings {
+            output: Some(PathBuf::from("out.mkv")),
+            ..Default::default()
+        };
+
+        let settings = cli.or(&script.or(&config));
+        assert_eq!(settings.output, Some(PathBuf::from("out.mkv")));
+        assert_eq!(settings.codec, Some(VideoCodec::H265));
+        assert_eq!(settings.crf, Some(20));
+        assert_eq!(settings.preset.as_deref(), Some("slow"));
+        assert_eq!(Container::from_extension("MKV"), Some(Container::Mkv));
+        assert_eq!("webm".parse::<Container>().unwrap(), Container::Webm);
+        assert_eq!("prores".parse::<VideoCodec>().unwrap(), VideoCodec::Prores);
+        assert!("avi".parse::<Container>().is_err());
+    }
 }
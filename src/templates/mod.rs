@@ -1,4 +1,6 @@
-use crate::script::{Color, Layer, Metadata, Position, Resolution, Scene, SceneType, VideoScript};
+use crate::script::{
+    Color, Layer, Metadata, Position, Resolution, Scene, SceneType, VideoScript, SCRIPT_VERSION,
+};
 use clap::ValueEnum;
 
 #[derive(Debug, Clone, ValueEnum)]
@@ -25,6 +27,7 @@ impl ScriptTemplate {
         let body_duration = total_duration - hook_duration - payoff_duration;
 
         VideoScript {
+            version: SCRIPT_VERSION,
             metadata: Metadata {
                 title: "Explainer Video".into(),
                 resolution: Resolution::Named("1920x1080".into()),
@@ -32,6 +35,8 @@ impl ScriptTemplate {
                 duration: total_duration,
                 description: Some("Generated explainer template".into()),
                 citations: vec![],
+                encoder: None,
+                transparent: false,
             },
             scenes: vec![
                 Self::create_scene(
@@ -63,6 +68,7 @@ impl ScriptTemplate {
         let steps_duration = total_duration - intro_duration - recap_duration;
 
         VideoScript {
+            version: SCRIPT_VERSION,
             metadata: Metadata {
                 title: "Tutorial Video".into(),
                 resolution: Resolution::Named("1920x1080".into()),
@@ -70,6 +76,8 @@ impl ScriptTemplate {
                 duration: total_duration,
                 description: Some("Generated tutorial template".into()),
                 citations: vec![],
+                encoder: None,
+                transparent: false,
             },
             scenes: vec![
                 Self::create_scene(
@@ -101,6 +109,7 @@ impl ScriptTemplate {
         let resolution_duration = total_duration * 0.30;
 
         VideoScript {
+            version: SCRIPT_VERSION,
             metadata: Metadata {
                 title: "Story Video".into(),
                 resolution: Resolution::Named("1920x1080".into()),
@@ -108,6 +117,8 @@ impl ScriptTemplate {
                 duration: total_duration,
                 description: Some("Generated storytelling template".into()),
                 citations: vec![],
+                encoder: None,
+                transparent: false,
             },
             scenes: vec![
                 Self::create_scene(
@@ -150,6 +161,8 @@ impl ScriptTemplate {
                 },
                 position: Position { x: 960, y: 540 },
                 effects: vec![],
+                transform: Default::default(),
+                animation: Default::default(),
             }],
             transition: None,
         }
@@ -0,0 +1,398 @@
+use crate::script::VideoPlayback;
+use anyhow::{Context, Result};
+use image::RgbaImage;
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
+
+/// Properties of a video file, as reported by ffprobe
+#[derive(Debug, Clone, PartialEq)]
+pub struct VideoInfo {
+    pub width: u32,
+    pub height: u32,
+    pub fps: f32,
+    pub duration: f32,
+    pub has_audio: bool,
+}
+
+#[derive(Deserialize)]
+struct ProbeOutput {
+    #[serde(default)]
+    streams: Vec<ProbeStream>,
+    format: Option<ProbeFormat>,
+}
+
+#[derive(Deserialize)]
+struct ProbeStream {
+    codec_type: String,
+    width: Option<u32>,
+    height: Option<u32>,
+    avg_frame_rate: Option<String>,
+    r_frame_rate: Option<String>,
+    duration: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ProbeFormat {
+    duration: Option<String>,
+}
+
+impl VideoInfo {
+    /// Parse the output of `ffprobe -show_streams -show_format -of json`
+    pub fn from_ffprobe_json(json: &str) -> Result<Self> {
+        let probe: ProbeOutput = serde_json::from_str(json).context("Invalid ffprobe output")?;
+
+        let video = probe
+            .streams
+            .iter()
+            .find(|s| s.codec_type == "video")
+            .context("No video stream found")?;
+
+        // avg_frame_rate is "0/0" for some containers, so fall back to r_frame_rate
+        let fps = [&video.avg_frame_rate, &video.r_frame_rate]
+            .into_iter()
+            .flatten()
+            .find_map(|rate| parse_frame_rate(rate))
+            .context("Missing video frame rate")?;
+
+        let duration = video
+            .duration
+            .as_deref()
+            .or(probe.format.as_ref().and_then(|f| f.duration.as_deref()))
+            .and_then(|d| d.parse::<f32>().ok())
+            .context("Missing video duration")?;
+
+        Ok(Self {
+            width: video.width.context("Missing video width")?,
+            height: video.height.context("Missing video height")?,
+            fps,
+            duration,
+            has_audio: probe.streams.iter().any(|s| s.codec_type == "audio"),
+        })
+    }
+}
+
+/// Parse an ffprobe rational frame rate such as "30000/1001"
+fn parse_frame_rate(rate: &str) -> Option<f32> {
+    let (num, den) = rate.split_once('/').unwrap_or((rate, "1"));
+    let (num, den): (f32, f32) = (num.parse().ok()?, den.parse().ok()?);
+    (num > 0.0 && den > 0.0).then(|| num / den)
+}
+
+/// Decodes video frames through an ffmpeg subprocess writing raw RGBA to a pipe.
+///
+/// Frames are read sequentially; the process is restarted at a seek point when
+/// a frame before the recently decoded ones (or far ahead of them) is requested.
+pub struct VideoDecoder {
+    path: PathBuf,
+    info: VideoInfo,
+    /// Running ffmpeg process and the index of the next frame it will output
+    stream: Option<(Child, u64)>,
+    /// Most recently decoded frames, oldest first
+    recent: VecDeque<(u64, Arc<RgbaImage>)>,
+    /// Number of decoded frames kept in `recent`
+    history: usize,
+}
+
+impl VideoDecoder {
+    /// Check if ffmpeg and ffprobe are available
+    pub fn is_available() -> bool {
+        ["ffmpeg", "ffprobe"]
+            .iter()
+            .all(|tool| Command::new(tool).arg("-version").output().is_ok())
+    }
+
+    /// Read the properties of a video file
+    pub fn probe(path: &Path) -> Result<VideoInfo> {
+        let output = Command::new("ffprobe")
+            .args([
+                "-v",
+                "error",
+                "-show_streams",
+                "-show_format",
+                "-of",
+                "json",
+            ])
+            .arg(path)
+            .output()
+            .context("Failed to execute ffprobe")?;
+
+        if !output.status.success() {
+            anyhow::bail!(
+                "ffprobe failed for {}: {}",
+                path.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        VideoInfo::from_ffprobe_json(&String::from_utf8_lossy(&output.stdout))
+            .with_context(|| format!("Failed to probe video: {}", path.display()))
+    }
+
+    /// Open a video file for decoding
+    pub fn open(path: &Path) -> Result<Self> {
+        let info = Self::probe(path)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            info,
+            stream: None,
+            recent: VecDeque::new(),
+            history: 1,
+        })
+    }
+
+    /// Keep the last `frames` decoded frames, so frames requested slightly out
+    /// of order (e.g. by parallel render workers) don't restart ffmpeg
+    pub fn set_history(&mut self, frames: usize) {
+        self.history = frames.max(1);
+    }
+
+    /// Properties of the video
+    pub fn info(&self) -> &VideoInfo {
+        &self.info
+    }
+
+    /// Number of frames in the video
+    pub fn frame_count(&self) -> u64 {
+        ((self.info.duration * self.info.fps).round() as u64).max(1)
+    }
+
+    /// Index of the source frame on screen `time` seconds into the video
+    pub fn frame_index(&self, time: f32) -> u64 {
+        // The epsilon keeps exact frame boundaries from rounding down a frame
+        let index = (time.max(0.0) * self.info.fps + 1e-3).floor() as u64;
+        index.min(self.frame_count() - 1)
+    }
+
+    /// Decode the frame at `index`
+    pub fn frame(&mut self, index: u64) -> Result<Arc<RgbaImage>> {
+        if let Some((_, image)) = self.recent.iter().find(|(recent, _)| *recent == index) {
+            return Ok(image.clone());
+        }
+
+        // Reading forward is cheaper than restarting ffmpeg for nearby frames
+        let max_skip = (self.info.fps * 2.0) as u64;
+        let needs_seek = match &self.stream {
+            Some((_, next)) => index < *next || index - *next > max_skip,
+            None => true,
+        };
+        if needs_seek {
+            self.start_stream(index)?;
+        }
+
+        let (width, height) = (self.info.width, self.info.height);
+        let mut buffer = vec![0u8; (width * height * 4) as usize];
+        let (child, next) = self.stream.as_mut().context("Video stream not started")?;
+        let stdout = child
+            .stdout
+            .as_mut()
+            .context("Video stream has no output")?;
+
+        while *next <= index {
+            match stdout.read_exact(&mut buffer) {
+                Ok(()) => *next += 1,
+                // The reported duration can overshoot the last frame; hold the
+                // last decoded frame in that case
+                Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                    return self
+                        .recent
+                        .back()
+                        .map(|(_, image)| image.clone())
+                        .with_context(|| format!("Video ended before frame {}", index));
+                }
+                Err(e) => return Err(e).context("Failed to read video frame"),
+            }
+
+            let decoded = *next - 1;
+            if index - decoded < self.history as u64 {
+                let image = RgbaImage::from_raw(width, height, buffer.clone())
+                    .context("Invalid frame size")?;
+                self.recent.push_back((decoded, Arc::new(image)));
+                if self.recent.len() > self.history {
+                    self.recent.pop_front();
+                }
+            }
+        }
+
+        let (_, image) = self.recent.back().context("No frame decoded")?;
+        Ok(image.clone())
+    }
+
+    /// Build the audio of a clip for `duration` seconds of scene time, applying
+    /// its trim, playback rate and looping (pitch follows the playback rate)
+    pub fn clip_audio(
+        samples: &[f32],
+        sample_rate: u32,
+        channels: u32,
+        playback: &VideoPlayback,
+        duration: f32,
+    ) -> Vec<f32> {
+        let channels = channels.max(1) as usize;
+        let source_frames = samples.len() / channels;
+        let source_duration = source_frames as f32 / sample_rate as f32;
+        let output_frames = (duration * sample_rate as f32) as usize;
+
+        let mut clip = vec![0.0; output_frames * channels];
+        for (frame, out) in clip.chunks_exact_mut(channels).enumerate() {
+            let time = frame as f32 / sample_rate as f32;
+            let Some(source_time) = playback.source_time(time, source_duration) else {
+                continue;
+            };
+
+            let source_frame = (source_time * sample_rate as f32) as usize;
+            if source_frame < source_frames {
+                let start = source_frame * channels;
+                out.copy_from_slice(&samples[start..start + channels]);
+            }
+        }
+
+        clip
+    }
+
+    /// Restart ffmpeg so that its next output is the frame at `index`
+    fn start_stream(&mut self, index: u64) -> Result<()> {
+        self.stop_stream();
+
+        // Seek half a frame early so the requested frame isn't skipped by rounding
+        let seek = ((index as f64 - 0.5) / self.info.fps as f64).max(0.0);
+        let child = Command::new("ffmpeg")
+            .args(["-v", "error", "-ss"])
+            .arg(format!("{:.6}", seek))
+            .arg("-i")
+            .arg(&self.path)
+            .args(["-an", "-sn", "-f", "rawvideo", "-pix_fmt", "rgba", "-"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("Failed to execute ffmpeg")?;
+
+        self.stream = Some((child, index));
+        Ok(())
+    }
+
+    fn stop_stream(&mut self) {
+        if let Some((mut child, _)) = self.stream.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+impl Drop for VideoDecoder {
+    fn drop(&mut self) {
+        self.stop_stream();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROBE_JSON: &str = r#"{
+        "streams": [
+            {"codec_type": "video", "width": 640, "height": 360,
+             "avg_frame_rate": "0/0", "r_frame_rate": "30000/1001"},
+            {"codec_type": "audio", "sample_rate": "48000"}
+        ],
+        "format": {"duration": "12.500000"}
+    }"#;
+
+    #[test]
+    fn test_parse_ffprobe_output() {
+        let info = VideoInfo::from_ffprobe_json(PROBE_JSON).unwrap();
+        assert_eq!(info.width, 640);
+        assert_eq!(info.height, 360);
+        assert!((info.fps - 29.97).abs() < 0.01);
+        assert_eq!(info.duration, 12.5);
+        assert!(info.has_audio);
+
+        let no_video = r#"{"streams": [{"codec_type": "audio"}]}"#;
+        assert!(VideoInfo::from_ffprobe_json(no_video).is_err());
+    }
+
+    #[test]
+    fn test_parse_frame_rate() {
+        assert_eq!(parse_frame_rate("25/1"), Some(25.0));
+        assert_eq!(parse_frame_rate("24"), Some(24.0));
+        assert_eq!(parse_frame_rate("0/0"), None);
+        assert_eq!(parse_frame_rate("abc"), None);
+    }
+
+    #[test]
+    fn test_clip_audio_trim_and_loop() {
+        // Mono ramp: sample i has value i, at 10 samples per second
+        let samples: Vec<f32> = (0..20).map(|i| i as f32).collect();
+        let playback = VideoPlayback {
+            trim_in: 0.5,
+            trim_out: Some(1.0),
+            looping: true,
+            ..Default::default()
+        };
+
+        let clip = VideoDecoder::clip_audio(&samples, 10, 1, &playback, 1.2);
+        assert_eq!(
+            clip,
+            vec![5.0, 6.0, 7.0, 8.0, 9.0, 5.0, 6.0, 7.0, 8.0, 9.0, 5.0, 6.0]
+        );
+
+        // Double speed without looping plays the trimmed half second once
+        let once = VideoPlayback {
+            looping: false,
+            playback_rate: 2.0,
+            ..playback
+        };
+        let clip = VideoDecoder::clip_audio(&samples, 10, 1, &once, 0.5);
+        assert_eq!(clip, vec![5.0, 7.0, 9.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_decode_frames() {
+        if !VideoDecoder::is_available() {
+            return;
+        }
+
+        // 2s at 10fps: red for the first second, blue for the second
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("clip.mp4");
+        let status = Command::new("ffmpeg")
+            .args([
+                "-v",
+                "error",
+                "-f",
+                "lavfi",
+                "-i",
+                "color=c=red:s=32x16:r=10:d=1",
+            ])
+            .args(["-f", "lavfi", "-i", "color=c=blue:s=32x16:r=10:d=1"])
+            .args(["-filter_complex", "[0][1]concat=n=2:v=1"])
+            .args(["-c:v", "libx264", "-pix_fmt", "yuv444p", "-crf", "0"])
+            .arg(&path)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let mut decoder = VideoDecoder::open(&path).unwrap();
+        assert_eq!((decoder.info().width, decoder.info().height), (32, 16));
+        assert_eq!(decoder.frame_count(), 20);
+        assert_eq!(decoder.frame_index(1.0), 10);
+
+        let is_red = |image: &RgbaImage| image.get_pixel(0, 0)[0] > 200;
+        assert!(!is_red(&decoder.frame(15).unwrap()));
+        // Seeking backwards restarts the stream
+        assert!(is_red(&decoder.frame(9).unwrap()));
+        assert!(!is_red(&decoder.frame(10).unwrap()));
+
+        // Recent frames are kept without restarting
+        decoder.set_history(4);
+        decoder.frame(14).unwrap();
+        let next = |decoder: &VideoDecoder| decoder.stream.as_ref().map(|(_, next)| *next);
+        assert_eq!(next(&decoder), Some(15));
+        assert!(!is_red(&decoder.frame(12).unwrap()));
+        assert!(is_red(&decoder.frame(9).unwrap()));
+        assert_eq!(next(&decoder), Some(10));
+    }
+}
@@ -36,27 +36,492 @@ fn test_cli_validate_simple() {
         .stdout(predicate::str::contains("Validation complete"));
 }
 
+#[test]
+fn test_cli_validate_diagnostics() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let script = temp_dir.path().join("broken.json");
+    fs::write(
+        &script,
+        r#"{
+  "version": 2,
+  "metadata": {"title": "Broken", "resolution": "640x360", "fps": 0, "duration": 2.0},
+  "scenes": [{"id": "intro", "duration": 2.0, "layers": []}]
+}"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("validate")
+        .arg(&script)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("error[invalid-fps]"))
+        .stderr(predicate::str::contains("broken.json:4:57"))
+        .stderr(predicate::str::contains("= at /scenes/0/layers"));
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    let output = cmd
+        .arg("validate")
+        .arg(&script)
+        .arg("--format")
+        .arg("json")
+        .assert()
+        .failure()
+        .get_output()
+        .stdout
+        .clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(report["errors"], 2);
+    assert_eq!(report["diagnostics"][0]["pointer"], "/metadata/fps");
+    assert_eq!(report["diagnostics"][1]["scene"], "intro");
+
+    // JSON progress reports each diagnostic as an event on stdout
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    let assert = cmd
+        .arg("validate")
+        .arg(&script)
+        .arg("--progress")
+        .arg("json")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("error[invalid-fps]").not());
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    let diagnostics: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .filter(|event| event["event"] == "diagnostic")
+        .collect();
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0]["code"], "invalid-fps");
+    assert_eq!(diagnostics[0]["line"], 3);
+    assert!(diagnostics[0]["path"]
+        .as_str()
+        .unwrap()
+        .ends_with("broken.json"));
+}
+
+#[test]
+fn test_cli_convert() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let yaml = temp_dir.path().join("simple.yaml");
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("convert")
+        .arg("examples/simple.json")
+        .arg(&yaml)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("from JSON to YAML"));
+    assert!(fs::read_to_string(&yaml)
+        .unwrap()
+        .starts_with("version: 2\nmetadata:"));
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("validate").arg(&yaml).assert().success();
+}
+
+#[test]
+fn test_cli_schema() {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    let output = cmd
+        .arg("schema")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let schema: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(
+        schema["$schema"],
+        "https://json-schema.org/draft/2020-12/schema"
+    );
+    assert!(schema["$defs"]["Layer"].is_object());
+}
+
+#[test]
+fn test_cli_migrate() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let script = temp_dir.path().join("old.json");
+    let old = r#"{
+  "metadata": {"title": "Old", "resolution": "640x360", "fps": 30, "duration": 1.0},
+  "scenes": [{"id": "intro", "duration": 1.0, "layers": [
+    {"type": "image", "source": "bg.png", "effects": ["fade_in"]}
+  ]}]
+}"#;
+    fs::write(&script, old).unwrap();
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("migrate")
+        .arg(&script)
+        .arg("--dry-run")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("+  \"version\": 2,"))
+        .stdout(predicate::str::contains("-    {\"type\": \"image\""));
+    assert_eq!(fs::read_to_string(&script).unwrap(), old);
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("migrate")
+        .arg(&script)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("from version 1 to 2"));
+    let migrated: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&script).unwrap()).unwrap();
+    assert_eq!(
+        migrated["scenes"][0]["layers"][0]["effects"][0],
+        serde_json::json!({"fade_in": {}})
+    );
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    let output = cmd
+        .arg("validate")
+        .arg(&script)
+        .arg("--format")
+        .arg("json")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(report["warnings"], 0);
+}
+
+#[test]
+fn test_cli_migrate_keeps_comments() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let script = temp_dir.path().join("old.yaml");
+    fs::write(
+        &script,
+        "# yaml-language-server: $schema=schema.json
+metadata: {title: Old, resolution: 640x360, fps: 30, duration: 1.0}
+scenes:
+  - id: intro   # first
+    duration: 1.0
+    layers:
+      - type: image
+        source: bg.png
+        effects: [fade_in]   # slow
+      - type: image
+        source: fg.png
+        effects: [{color_grade: {adjustment: warm}}]
+",
+    )
+    .unwrap();
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("migrate")
+        .arg(&script)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "warning[dropped-adjustment]: color grade adjustment \"warm\" is dropped",
+        ))
+        .stderr(predicate::str::contains("old.yaml:12:"));
+    assert_eq!(
+        fs::read_to_string(&script).unwrap(),
+        "# yaml-language-server: $schema=schema.json
+version: 2
+metadata: {title: Old, resolution: 640x360, fps: 30, duration: 1.0}
+scenes:
+  - id: intro   # first
+    duration: 1.0
+    layers:
+      - type: image
+        source: bg.png
+        effects: [{fade_in: {}}]   # slow
+      - type: image
+        source: fg.png
+        effects: [{color_grade: {}}]
+"
+    );
+}
+
+#[test]
+fn test_cli_validate_unknown_fields() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let script = temp_dir.path().join("typo.json");
+    fs::write(
+        &script,
+        r#"{
+  "version": 2,
+  "metadata": {"title": "Typo", "resolution": "640x360", "fps": 30, "duration": 1.0},
+  "scenes": [{"id": "intro", "duration": 1.0, "scene_typ": "hook", "layers": [
+    {"type": "image", "source": "bg.png"}
+  ]}]
+}"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("validate")
+        .arg(&script)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "error[unknown-field]: unknown field `scene_typ`, did you mean `scene_type`?",
+        ))
+        .stderr(predicate::str::contains("= at /scenes/0/scene_typ"));
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("validate")
+        .arg(&script)
+        .arg("--no-strict")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("warning[unknown-field]"));
+}
+
+#[test]
+fn test_cli_variables() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let script = temp_dir.path().join("explainer.json");
+    fs::write(
+        &script,
+        r#"{
+  "version": 2,
+  "variables": {"product": "Widget", "length": 1.0},
+  "metadata": {"title": "{{product}} explained", "resolution": "640x360", "fps": 30, "duration": "{{length}}"},
+  "scenes": [{"id": "intro", "duration": "{{length}}", "layers": [
+    {"type": "image", "source": "{{product}}.png"}
+  ]}]
+}"#,
+    )
+    .unwrap();
+    let vars = temp_dir.path().join("vars.yaml");
+    fs::write(&vars, "product: Gadget\nlength: 2.5\n").unwrap();
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("validate")
+        .arg(&script)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Title: Widget explained"));
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("validate")
+        .arg(&script)
+        .arg("--vars")
+        .arg(&vars)
+        .arg("--var")
+        .arg("product=Gizmo")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Title: Gizmo explained"))
+        .stdout(predicate::str::contains("Duration: 2.50s"));
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("validate")
+        .arg(&script)
+        .arg("--var")
+        .arg("length=long")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "variable `length` must be a number like its default",
+        ));
+}
+
+#[test]
+fn test_cli_definitions() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    fs::create_dir(temp_dir.path().join("shared")).unwrap();
+    fs::write(
+        temp_dir.path().join("shared/brand.yaml"),
+        "version: 2\nvariables:\n  brand: Acme\n\
+         definitions:\n\
+         \x20 scenes:\n\
+         \x20   outro:\n\
+         \x20     id: outro\n\
+         \x20     duration: 1.0\n\
+         \x20     layers:\n\
+         \x20       - {type: image, source: logo.png}\n\
+         \x20       - {type: text, content: \"{{cta}} at {{brand}}\", font: f.ttf, font_size: 24.0, color: {r: 0, g: 0, b: 0}}\n",
+    )
+    .unwrap();
+    let script = temp_dir.path().join("ad.json");
+    fs::write(
+        &script,
+        r#"{
+  "version": 2,
+  "include": ["shared/brand.yaml"],
+  "metadata": {"title": "{{brand}} ad", "resolution": "640x360", "fps": 30, "duration": 2.0},
+  "scenes": [
+    {"id": "pitch", "duration": 1.0, "layers": [{"type": "image", "source": "product.png"}]},
+    {"use": "outro", "with": {"cta": "Buy now"}, "id": "buy"}
+  ]
+}"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("validate")
+        .arg(&script)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Title: Acme ad"))
+        .stdout(predicate::str::contains("Scenes: 2"));
+
+    // Issues are reported in the file that has them
+    fs::write(
+        temp_dir.path().join("shared/brand.yaml"),
+        "include: [../ad.json]\n",
+    )
+    .unwrap();
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("validate")
+        .arg(&script)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "error[include-cycle]: include cycle: ",
+        ))
+        .stderr(predicate::str::contains("ad.json:3:15"));
+}
+
 #[test]
 fn test_cli_render_simple() {
-    // Ensure output directory is clean
-    let _ = fs::remove_dir_all("tests/output_test");
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let output = temp_dir.path().join("output");
 
     let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
     cmd.arg("render")
         .arg("tests/test_config.json")
         .arg("--output")
-        .arg("tests/output_test")
+        .arg(&output)
         .arg("--renderer")
         .arg("native")
         .arg("--force-cpu")
+        .arg("--keep-frames")
         .assert()
         .success()
         .stdout(predicate::str::contains("Video created successfully"));
 
     // Check if output directory was created and contains files
-    assert!(fs::metadata("tests/output_test").is_ok());
-    assert!(fs::metadata("tests/output_test/frame_0.ppm").is_ok());
+    assert!(fs::metadata(&output).is_ok());
+    assert!(fs::metadata(output.join("frame_0.ppm")).is_ok());
+}
+
+#[test]
+fn test_cli_still() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let output = temp_dir.path().join("still.png");
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("still")
+        .arg("tests/test_config.json")
+        .arg("--at")
+        .arg("00:00:00.5")
+        .arg("--output")
+        .arg(&output)
+        .arg("--force-cpu")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("saved to"));
+
+    assert!(fs::metadata(&output).is_ok());
+
+    // Times past the end of the timeline are rejected
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("still")
+        .arg("tests/test_config.json")
+        .arg("--at")
+        .arg("01:00:00")
+        .arg("--output")
+        .arg(&output)
+        .arg("--force-cpu")
+        .assert()
+        .failure();
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("still")
+        .arg("tests/test_config.json")
+        .arg("--frame")
+        .arg(u32::MAX.to_string())
+        .arg("--output")
+        .arg(&output)
+        .arg("--force-cpu")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Frame number is too large"));
+}
+
+#[test]
+fn test_cli_storyboard() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let output = temp_dir.path().join("storyboard.png");
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("storyboard")
+        .arg("tests/test_config.json")
+        .arg("--count")
+        .arg("3")
+        .arg("--tile-width")
+        .arg("160")
+        .arg("--output")
+        .arg(&output)
+        .arg("--force-cpu")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Storyboard of 3 frames"));
+
+    assert!(fs::metadata(&output).is_ok());
+}
+
+#[test]
+fn test_cli_renderers() {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("renderers")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("native"))
+        .stdout(predicate::str::contains("blender"));
+
+    // Unknown backends are rejected with the list of available ones
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    cmd.arg("still")
+        .arg("tests/test_config.json")
+        .arg("--renderer")
+        .arg("povray")
+        .arg("--output")
+        .arg(std::env::temp_dir().join("unused.png"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("available: blender, native"));
+}
+
+#[test]
+fn test_cli_progress_json() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let output = temp_dir.path().join("storyboard.png");
+
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_interstellar-triangulum"));
+    let assert = cmd
+        .arg("storyboard")
+        .arg("tests/test_config.json")
+        .arg("--count")
+        .arg("2")
+        .arg("--tile-width")
+        .arg("160")
+        .arg("--output")
+        .arg(&output)
+        .arg("--force-cpu")
+        .arg("--progress")
+        .arg("json")
+        .assert()
+        .success();
 
-    // Clean up
-    let _ = fs::remove_dir_all("tests/output_test");
+    // Every line of stdout is a JSON event
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    let events: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let count = |event: &str| events.iter().filter(|e| e["event"] == event).count();
+    assert_eq!(count("frame_rendered"), 2);
+    assert_eq!(count("output_written"), 1);
+    assert!(events
+        .iter()
+        .any(|e| e["event"] == "stage_started" && e["stage"] == "render"));
 }